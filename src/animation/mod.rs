@@ -0,0 +1,346 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress in `[0, 1]` onto the eased curve.
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            // Cubic: slow start, fast middle, slow finish
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    // Kept up to date by the app running on this thread, from its clock
+    static NOW: Cell<Option<Duration>> = const { Cell::new(None) };
+    // What `now` counts from on threads without an app
+    static STARTED: Instant = Instant::now();
+}
+
+/// The time animations on this thread go by. While an [`App`](crate::app::App)
+/// runs on the thread it's the time on the app's clock, so animations hold
+/// still while that clock is paused or set, and skip stalls along with it
+/// (see [`App::with_max_frame_delta`](crate::app::App::with_max_frame_delta)).
+/// Otherwise it's real time since the thread first asked.
+pub fn now() -> Duration {
+    NOW.get().unwrap_or_else(|| STARTED.with(Instant::elapsed))
+}
+
+pub(crate) fn set_now(time: Duration) {
+    NOW.set(Some(time));
+}
+
+/// A value moving from `from` to `to` over `duration`, starting at [`now`]
+/// when it's created unless given another start with [`Self::starting_at`].
+/// Times are on the same clock as the start.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    from: f64,
+    to: f64,
+    duration: Duration,
+    easing: Easing,
+    start: Duration,
+}
+
+impl Animation {
+    pub fn new(from: f64, to: f64, duration: Duration, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            easing,
+            start: now(),
+        }
+    }
+
+    /// Starts at `start` on some other clock, like a render context's.
+    pub fn starting_at(mut self, start: Duration) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Linear progress through the animation at `now`, clamped to `[0, 1]`.
+    pub fn progress(&self, now: Duration) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let elapsed = now.saturating_sub(self.start);
+        (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
+
+    pub fn sample(&self, now: Duration) -> f64 {
+        let t = self.easing.apply(self.progress(now));
+        self.from + (self.to - self.from) * t
+    }
+
+    pub fn is_finished(&self, now: Duration) -> bool {
+        self.progress(now) >= 1.0
+    }
+
+    pub fn target(&self) -> f64 {
+        self.to
+    }
+}
+
+/// Elapsed time that can be paused, reset or set outright, so animated
+/// content can be rendered at exact moments.
+///
+/// Code that draws frames calls [`Self::tick`] once per frame. With a
+/// maximum delta (see [`Self::set_max_delta`]), a tick after a long stall only
+/// moves the clock on by that much, and the rest of the stall is skipped.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    // Time already accumulated when `running_since` was taken
+    offset: Duration,
+    running_since: Instant,
+    // Set while paused, holding the time the clock stopped at
+    paused_at: Option<Duration>,
+    max_delta: Option<Duration>,
+    // When the last tick happened, and the clock's time then
+    last_tick: Option<(Instant, Duration)>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    /// Starts running from zero.
+    pub fn new() -> Self {
+        Self {
+            offset: Duration::ZERO,
+            running_since: Instant::now(),
+            paused_at: None,
+            max_delta: None,
+            last_tick: None,
+        }
+    }
+
+    /// Caps how far one [`Self::tick`] moves the running clock, or `None` to let
+    /// it follow real time however long a frame took.
+    pub fn set_max_delta(&mut self, max_delta: Option<Duration>) {
+        self.max_delta = max_delta;
+    }
+
+    /// Starts a frame, returning how far the clock has moved since the last one.
+    /// While running, a gap longer than the maximum delta is skipped down to it.
+    /// Pausing, setting or resetting the clock still moves it by exactly as much
+    /// as was asked.
+    pub fn tick(&mut self) -> Duration {
+        self.catch_up();
+        let now = Instant::now();
+        let elapsed = self.elapsed();
+        let delta = match self.last_tick {
+            Some((_, last)) => elapsed.saturating_sub(last),
+            None => Duration::ZERO,
+        };
+        self.last_tick = Some((now, elapsed));
+        delta
+    }
+
+    /// Skips a stall since the last [`Self::tick`] down to the maximum delta
+    /// without starting a frame, for reading [`Self::elapsed`] between frames.
+    /// The next tick's delta still counts from the last one.
+    pub fn catch_up(&mut self) {
+        let now = Instant::now();
+        if let (Some(max_delta), Some((last_tick, last)), None) = (self.max_delta, self.last_tick, self.paused_at) {
+            let gap = now.saturating_duration_since(last_tick);
+            // Only the part of the gap the clock actually spent running
+            let skipped = gap.saturating_sub(max_delta).min(now.saturating_duration_since(self.running_since));
+            self.running_since += skipped;
+            // What's left of the gap is measured from here on
+            self.last_tick = Some((now - gap.min(max_delta), last));
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.paused_at {
+            Some(time) => time,
+            None => self.offset + self.running_since.elapsed(),
+        }
+    }
+
+    /// Back to zero, staying paused if it was.
+    pub fn reset(&mut self) {
+        self.offset = Duration::ZERO;
+        self.running_since = Instant::now();
+        if self.paused_at.is_some() {
+            self.paused_at = Some(Duration::ZERO);
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.elapsed());
+        }
+    }
+
+    /// Carries on from wherever the clock was paused or set.
+    pub fn resume(&mut self) {
+        if let Some(time) = self.paused_at.take() {
+            self.offset = time;
+            self.running_since = Instant::now();
+        }
+    }
+
+    /// Stops the clock at `time` until [`Self::resume`].
+    pub fn set(&mut self, time: Duration) {
+        self.paused_at = Some(time);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+}
+
+/// Splits frame deltas into whole steps of a fixed length, carrying what's
+/// left over into the next frame, for logic that should advance at the same
+/// rate whatever the frame rate.
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    step: Duration,
+    // Time not yet used up by a step
+    accumulated: Duration,
+}
+
+impl FixedTimestep {
+    /// Steps `step` long. A zero step is taken as one nanosecond.
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step: step.max(Duration::from_nanos(1)),
+            accumulated: Duration::ZERO,
+        }
+    }
+
+    /// `hz` steps a second. Panics unless `hz` is positive.
+    pub fn from_hz(hz: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Adds a frame's `delta`, returning how many steps are due.
+    pub fn advance(&mut self, delta: Duration) -> u32 {
+        self.accumulated += delta;
+        let steps = (self.accumulated.as_nanos() / self.step.as_nanos()).min(u32::MAX as u128) as u32;
+        self.accumulated -= self.step * steps;
+        steps
+    }
+
+    /// How far into the next step the time is, from 0 to 1: 0 right after a
+    /// step, approaching 1 just before the next. Drawing the state before the
+    /// last step blended this far towards the one after it hides the steps.
+    pub fn alpha(&self) -> f64 {
+        self.accumulated.as_secs_f64() / self.step.as_secs_f64()
+    }
+}
+
+/// How far through its current cycle a looping animation of length `period` is
+/// at `time`, in `[0, 1)`.
+pub fn phase(time: Duration, period: Duration) -> f64 {
+    if period.is_zero() {
+        return 0.0;
+    }
+    (time.as_secs_f64() / period.as_secs_f64()).fract()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn paused_clock_holds_still_until_resumed() {
+        let mut clock = Clock::new();
+        clock.pause();
+        let paused = clock.elapsed();
+        std::thread::sleep(TICK);
+        assert!(clock.is_paused());
+        assert_eq!(clock.elapsed(), paused);
+        assert_eq!(clock.tick(), Duration::ZERO);
+
+        clock.resume();
+        std::thread::sleep(TICK);
+        assert!(!clock.is_paused());
+        assert!(clock.elapsed() >= paused + TICK);
+    }
+
+    #[test]
+    fn set_clock_stops_at_the_time_given() {
+        let mut clock = Clock::new();
+        clock.set(Duration::from_secs(5));
+        std::thread::sleep(TICK);
+        assert_eq!(clock.elapsed(), Duration::from_secs(5));
+
+        clock.resume();
+        std::thread::sleep(TICK);
+        assert!(clock.elapsed() >= Duration::from_secs(5) + TICK);
+    }
+
+    #[test]
+    fn reset_clock_goes_back_to_zero() {
+        let mut clock = Clock::new();
+        clock.set(Duration::from_secs(5));
+        clock.reset();
+        assert!(clock.is_paused());
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+
+        clock.resume();
+        clock.reset();
+        assert!(!clock.is_paused());
+        assert!(clock.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn max_delta_skips_stalls() {
+        let mut clock = Clock::new();
+        clock.set_max_delta(Some(Duration::from_millis(5)));
+        clock.tick();
+        std::thread::sleep(TICK * 5);
+        clock.catch_up();
+        let caught_up = clock.elapsed();
+        assert!(caught_up < TICK * 2, "{caught_up:?}");
+        assert!(clock.tick() < TICK * 2);
+    }
+
+    #[test]
+    fn animations_go_by_the_time_they_are_given() {
+        let animation = Animation::new(0.0, 10.0, Duration::from_secs(2), Easing::Linear).starting_at(Duration::from_secs(1));
+        assert_eq!(animation.sample(Duration::ZERO), 0.0);
+        assert_eq!(animation.sample(Duration::from_secs(2)), 5.0);
+        assert!(!animation.is_finished(Duration::from_secs(2)));
+        assert!(animation.is_finished(Duration::from_secs(3)));
+        assert_eq!(animation.sample(Duration::from_secs(4)), 10.0);
+    }
+
+    #[test]
+    fn animations_start_at_the_apps_clock() {
+        set_now(Duration::from_secs(7));
+        let animation = Animation::new(0.0, 1.0, Duration::from_secs(1), Easing::Linear);
+        assert_eq!(animation.progress(now()), 0.0);
+        // Nothing moves while the clock is held
+        std::thread::sleep(TICK);
+        assert_eq!(animation.progress(now()), 0.0);
+        set_now(Duration::from_millis(7500));
+        assert_eq!(animation.progress(now()), 0.5);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+use accesskit::{Action, ActionHandler, ActionRequest, ActivationHandler, DeactivationHandler, Node, NodeId, Role, TreeId, TreeInfo, TreeUpdate};
+use accesskit_winit::Adapter;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::Window;
+
+use crate::components::Widget;
+
+// The window itself; widget ids are derived from their paths and never 0
+const WINDOW_ID: NodeId = NodeId(0);
+
+/// Mirrors the widget tree into an accesskit tree for screen readers, and
+/// queues up the actions they request.
+pub(super) struct Accessibility {
+    adapter: Adapter,
+    actions: Arc<Mutex<Vec<ActionRequest>>>,
+    // Which widget each node of the last tree stood for
+    paths: HashMap<NodeId, Vec<usize>>,
+}
+
+// The tree lives with the widgets on the event loop thread, so it can't be
+// handed over synchronously; the first update after activation is a full tree
+struct Activation;
+
+impl ActivationHandler for Activation {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        None
+    }
+}
+
+struct Actions {
+    queue: Arc<Mutex<Vec<ActionRequest>>>,
+    window: Weak<Window>,
+}
+
+impl ActionHandler for Actions {
+    // May be called on any thread
+    fn do_action(&mut self, request: ActionRequest) {
+        self.queue.lock().unwrap().push(request);
+        if let Some(window) = self.window.upgrade() {
+            window.request_redraw();
+        }
+    }
+}
+
+struct Deactivation;
+
+impl DeactivationHandler for Deactivation {
+    fn deactivate_accessibility(&mut self) {}
+}
+
+impl Accessibility {
+    /// Must be called before `window` is first made visible.
+    pub(super) fn new(event_loop: &ActiveEventLoop, window: &Arc<Window>) -> Self {
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        let handler = Actions {
+            queue: actions.clone(),
+            window: Arc::downgrade(window),
+        };
+        Self {
+            adapter: Adapter::with_direct_handlers(event_loop, window, Activation, handler, Deactivation),
+            actions,
+            paths: HashMap::new(),
+        }
+    }
+
+    pub(super) fn process_event(&mut self, window: &Window, event: &WindowEvent) {
+        self.adapter.process_event(window, event);
+    }
+
+    /// Sends the whole tree under `root`, if assistive technology is listening.
+    pub(super) fn update(&mut self, window: &Window, root: &dyn Widget, focused: Option<&[usize]>) {
+        let paths = &mut self.paths;
+        self.adapter.update_if_active(|| {
+            paths.clear();
+            let mut nodes = Vec::new();
+            let mut children = Vec::new();
+            collect(root, &mut Vec::new(), &mut children, &mut nodes, paths);
+
+            let mut window_node = Node::new(Role::Window);
+            window_node.set_label(window.title());
+            window_node.set_children(children);
+            nodes.push((WINDOW_ID, window_node));
+
+            // Focus on a widget without a node falls back to the window
+            let focus = focused
+                .map(node_id)
+                .filter(|id| paths.contains_key(id))
+                .unwrap_or(WINDOW_ID);
+            let mut tree = TreeInfo::new(WINDOW_ID);
+            tree.toolkit_name = Some("RasmalaiUI".to_string());
+            TreeUpdate {
+                nodes,
+                tree: Some(tree),
+                tree_id: TreeId::ROOT,
+                focus,
+            }
+        });
+    }
+
+    /// Actions requested since the last call, with the path of the widget each
+    /// one targets. Requests for nodes that are gone are dropped.
+    pub(super) fn take_actions(&mut self) -> Vec<(Action, Vec<usize>)> {
+        let requests = std::mem::take(&mut *self.actions.lock().unwrap());
+        requests
+            .into_iter()
+            .filter_map(|request| Some((request.action, self.paths.get(&request.target_node)?.clone())))
+            .collect()
+    }
+}
+
+// Widgets without a node of their own are skipped, their children attached to
+// the nearest ancestor that has one
+fn collect(
+    widget: &dyn Widget,
+    path: &mut Vec<usize>,
+    parent_children: &mut Vec<NodeId>,
+    nodes: &mut Vec<(NodeId, Node)>,
+    paths: &mut HashMap<NodeId, Vec<usize>>,
+) {
+    let Some(mut node) = widget.accessibility() else {
+        for (index, child) in widget.children().iter().enumerate() {
+            path.push(index);
+            collect(child.as_ref(), path, parent_children, nodes, paths);
+            path.pop();
+        }
+        return;
+    };
+
+    let bounds = widget.bounds();
+    node.set_bounds(accesskit::Rect {
+        x0: bounds.x0,
+        y0: bounds.y0,
+        x1: bounds.x1,
+        y1: bounds.y1,
+    });
+    if widget.is_focusable() {
+        node.add_action(Action::Focus);
+    }
+    let mut children = Vec::new();
+    for (index, child) in widget.children().iter().enumerate() {
+        path.push(index);
+        collect(child.as_ref(), path, &mut children, nodes, paths);
+        path.pop();
+    }
+    node.set_children(children);
+
+    let id = node_id(path);
+    parent_children.push(id);
+    nodes.push((id, node));
+    paths.insert(id, path.clone());
+}
+
+// Stable for as long as the widget keeps its place in the tree
+fn node_id(path: &[usize]) -> NodeId {
+    // FNV-1a over the path, nudged off the window's id
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &index in path {
+        hash ^= index as u64 + 1;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    NodeId(hash.max(1))
+}
@@ -0,0 +1,165 @@
+use vello::kurbo::{Affine, Line, Point, Rect, Size, Stroke};
+use vello::peniko::Fill;
+use winit::event::MouseButton;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::components::DrawCtx;
+use crate::event::UiEvent;
+use crate::text::{self, TextStyle};
+use crate::theme::Theme;
+
+// Narrowest the menu gets, however short its labels
+const MIN_WIDTH: f64 = 120.0;
+
+pub(super) enum MenuAction {
+    Nothing,
+    Redraw,
+    Close,
+    Select(usize),
+}
+
+/// A [`ContextMenu`](crate::components::ContextMenu) on screen. It takes all
+/// pointer and keyboard input while open, closing on a click outside or
+/// Escape and choosing an item on a left click or Enter.
+pub(super) struct OpenContextMenu {
+    // Widget the menu belongs to, relative to whatever took input when it opened
+    pub(super) path: Vec<usize>,
+    // How many modals were open then; the path means nothing once that changes
+    pub(super) modal_depth: usize,
+    // One per entry, with `None` labels for separators
+    rows: Vec<(Option<String>, Rect)>,
+    bounds: Rect,
+    highlighted: Option<usize>,
+}
+
+impl OpenContextMenu {
+    /// Lays the menu out with its top-left corner at `anchor`, moved as far as
+    /// needed to keep it inside a window of `window` size.
+    pub(super) fn new(
+        path: Vec<usize>,
+        modal_depth: usize,
+        labels: Vec<Option<String>>,
+        anchor: Point,
+        theme: &Theme,
+        window: Size,
+    ) -> Self {
+        let padding = theme.spacing;
+        let style = TextStyle::new(theme.font_size, theme.foreground);
+        let sizes: Vec<Option<Size>> = labels
+            .iter()
+            .map(|label| label.as_deref().map(|label| text::text_size(label, &style)))
+            .collect();
+        let label_width = sizes.iter().flatten().map(|size| size.width).fold(0.0, f64::max);
+        let width = (label_width + padding * 4.0).max(MIN_WIDTH);
+        let heights: Vec<f64> = sizes
+            .iter()
+            .map(|size| match size {
+                Some(size) => size.height + padding,
+                None => padding,
+            })
+            .collect();
+        let height = heights.iter().sum::<f64>() + padding;
+
+        let origin = Point::new(
+            anchor.x.min(window.width - width).max(0.0),
+            anchor.y.min(window.height - height).max(0.0),
+        );
+        let bounds = Rect::from_origin_size(origin, Size::new(width, height));
+        let mut top = bounds.y0 + padding / 2.0;
+        let rows = labels
+            .into_iter()
+            .zip(heights)
+            .map(|(label, height)| {
+                let row = Rect::new(bounds.x0, top, bounds.x1, top + height);
+                top = row.y1;
+                (label, row)
+            })
+            .collect();
+        Self {
+            path,
+            modal_depth,
+            rows,
+            bounds,
+            highlighted: None,
+        }
+    }
+
+    fn item_at(&self, position: Point) -> Option<usize> {
+        self.rows
+            .iter()
+            .position(|(label, row)| label.is_some() && row.contains(position))
+    }
+
+    // The next item after the highlighted one, skipping separators and wrapping around
+    fn step(&self, reverse: bool) -> Option<usize> {
+        let count = self.rows.len();
+        let start = self.highlighted.unwrap_or(if reverse { 0 } else { count - 1 });
+        (1..=count)
+            .map(|offset| if reverse { (start + count - offset) % count } else { (start + offset) % count })
+            .find(|&index| self.rows[index].0.is_some())
+    }
+
+    pub(super) fn handle_event(&mut self, event: &UiEvent) -> MenuAction {
+        match event {
+            UiEvent::PointerMove { position } => {
+                let hovered = self.item_at(*position);
+                // Keeps a keyboard highlight until the pointer is over another item
+                if hovered.is_some() && hovered != self.highlighted {
+                    self.highlighted = hovered;
+                    MenuAction::Redraw
+                } else {
+                    MenuAction::Nothing
+                }
+            }
+            UiEvent::PointerDown { position, .. } if !self.bounds.contains(*position) => MenuAction::Close,
+            // Released rather than pressed, so the press that opened the menu
+            // can't choose anything
+            UiEvent::PointerUp { position, button: MouseButton::Left } => match self.item_at(*position) {
+                Some(index) => MenuAction::Select(index),
+                None => MenuAction::Nothing,
+            },
+            UiEvent::KeyDown { key: Key::Named(key), .. } => match key {
+                NamedKey::Escape => MenuAction::Close,
+                NamedKey::ArrowDown | NamedKey::ArrowUp => {
+                    self.highlighted = self.step(*key == NamedKey::ArrowUp);
+                    MenuAction::Redraw
+                }
+                NamedKey::Enter | NamedKey::Space => match self.highlighted {
+                    Some(index) => MenuAction::Select(index),
+                    None => MenuAction::Nothing,
+                },
+                _ => MenuAction::Nothing,
+            },
+            _ => MenuAction::Nothing,
+        }
+    }
+
+    /// Draws into the overlay, above everything but tooltips.
+    pub(super) fn draw(&self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let padding = theme.spacing;
+        let style = TextStyle::new(theme.font_size, theme.foreground);
+        let overlay = ctx.overlay();
+        let shape = self.bounds.to_rounded_rect(theme.corner_radius);
+        overlay.fill(Fill::NonZero, Affine::IDENTITY, theme.background, None, &shape);
+        for (index, (label, row)) in self.rows.iter().enumerate() {
+            match label {
+                Some(label) => {
+                    if self.highlighted == Some(index) {
+                        let highlight = row.inset((-padding / 2.0, 0.0)).to_rounded_rect(theme.corner_radius / 2.0);
+                        overlay.fill(Fill::NonZero, Affine::IDENTITY, theme.accent.with_alpha(0.3), None, &highlight);
+                    }
+                    let size = text::text_size(label, &style);
+                    let origin = Point::new(row.x0 + padding * 2.0, row.center().y - size.height / 2.0);
+                    text::draw_text(overlay, label, origin, &style);
+                }
+                None => {
+                    let y = row.center().y.round() + 0.5;
+                    let line = Line::new((row.x0 + padding, y), (row.x1 - padding, y));
+                    overlay.stroke(&Stroke::new(1.0), Affine::IDENTITY, theme.foreground.with_alpha(0.2), None, &line);
+                }
+            }
+        }
+        overlay.stroke(&Stroke::new(1.0), Affine::IDENTITY, theme.foreground.with_alpha(0.3), None, &shape);
+    }
+}
@@ -0,0 +1,59 @@
+use vello::kurbo::{Affine, Point, Rect, Size};
+use vello::peniko::Fill;
+
+use super::profiler::Profiler;
+use crate::components::DrawCtx;
+use crate::render::RenderDiagnostics;
+use crate::text::{self, TextStyle};
+
+/// Lists what the app is rendering with, and how fast, in the top-left corner
+/// of the window. Toggled with [`App::with_diagnostics_key`](super::App::with_diagnostics_key).
+pub(super) struct DiagnosticsOverlay {
+    frames: Profiler,
+}
+
+impl DiagnosticsOverlay {
+    pub(super) fn new() -> Self {
+        Self { frames: Profiler::new() }
+    }
+
+    pub(super) fn frame_started(&mut self) {
+        self.frames.frame_started();
+    }
+
+    /// Draws into the overlay. `render` is `None` until the renderer has
+    /// something to report.
+    pub(super) fn draw(&self, ctx: &mut DrawCtx, render: Option<&RenderDiagnostics>) {
+        let theme = ctx.theme;
+        let margin = theme.spacing;
+        let style = TextStyle::new(theme.font_size * 0.75, theme.foreground);
+
+        let mut lines = vec![match self.frames.stats() {
+            Some((latest, average)) => format!("Frame: {:.1} ms ({:.0} fps average)", latest, 1000.0 / average),
+            None => "Frame: -".to_string(),
+        }];
+        match render {
+            Some(render) => lines.extend(render.to_string().lines().map(str::to_string)),
+            None => lines.push("Renderer: not started".to_string()),
+        }
+
+        let sizes: Vec<Size> = lines.iter().map(|line| text::text_size(line, &style)).collect();
+        let width = sizes.iter().map(|size| size.width).fold(0.0, f64::max);
+        let height: f64 = sizes.iter().map(|size| size.height).sum();
+        let rect = Rect::new(margin, margin, margin * 3.0 + width, margin * 3.0 + height);
+
+        let overlay = ctx.overlay();
+        overlay.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            theme.background.with_alpha(0.85),
+            None,
+            &rect.to_rounded_rect(theme.corner_radius / 2.0),
+        );
+        let mut origin = Point::new(rect.x0 + margin, rect.y0 + margin);
+        for (line, size) in lines.iter().zip(&sizes) {
+            text::draw_text(overlay, line, origin, &style);
+            origin.y += size.height;
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+thread_local! {
+    // Set by the app while files are dragged over the window, for widgets to read while drawing
+    static HOVERED: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The files being dragged over the window right now, empty when there
+/// aren't any, e.g. for a widget to highlight itself as a drop zone while
+/// drawing. The app redraws whenever this changes. Only meaningful on the
+/// thread running the [`App`](super::App).
+pub fn hovered_files() -> Vec<PathBuf> {
+    HOVERED.with_borrow(|hovered| hovered.clone())
+}
+
+// Platforms report a drag of several files as one event per file
+pub(super) fn hover(path: PathBuf) {
+    HOVERED.with_borrow_mut(|hovered| hovered.push(path));
+}
+
+pub(super) fn end_hover() {
+    HOVERED.with_borrow_mut(Vec::clear);
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use winit::keyboard::NamedKey;
+
+use crate::event::FocusDirection;
+
+// gilrs can't wake the event loop, so it's checked on a timer: often while a
+// gamepad is connected, and now and then otherwise to notice one being plugged in
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+// How far a stick has to be pushed to move focus, and how far back towards the
+// centre before it can again. The gap keeps a stick resting near the threshold
+// from moving focus over and over.
+const STICK_PRESS: f32 = 0.6;
+const STICK_RELEASE: f32 = 0.3;
+
+pub(super) enum GamepadInput {
+    Navigate(FocusDirection),
+    Activate,
+}
+
+impl GamepadInput {
+    // The key it's delivered to widgets as
+    pub(super) fn key(&self) -> NamedKey {
+        match self {
+            GamepadInput::Navigate(FocusDirection::Up) => NamedKey::ArrowUp,
+            GamepadInput::Navigate(FocusDirection::Down) => NamedKey::ArrowDown,
+            GamepadInput::Navigate(FocusDirection::Left) => NamedKey::ArrowLeft,
+            GamepadInput::Navigate(FocusDirection::Right) => NamedKey::ArrowRight,
+            GamepadInput::Activate => NamedKey::Enter,
+        }
+    }
+}
+
+// Every connected gamepad, turned into navigation. Any of them can drive the UI.
+pub(super) struct Gamepads {
+    gilrs: Gilrs,
+    // Per gamepad, whether the left stick is pushed past the threshold on the
+    // x and y axes, so a push moves focus once rather than on every change
+    sticks: HashMap<GamepadId, [bool; 2]>,
+}
+
+impl Gamepads {
+    pub(super) fn new() -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => {
+                for (_, gamepad) in gilrs.gamepads() {
+                    log::info!("Gamepad connected: {}", gamepad.name());
+                }
+                Some(Self {
+                    gilrs,
+                    sticks: HashMap::new(),
+                })
+            }
+            Err(e) => {
+                log::warn!("Gamepad input unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    pub(super) fn wake_at(&self) -> Instant {
+        let interval = if self.gilrs.gamepads().next().is_some() {
+            POLL_INTERVAL
+        } else {
+            IDLE_POLL_INTERVAL
+        };
+        Instant::now() + interval
+    }
+
+    // Everything that happened since the last poll
+    pub(super) fn poll(&mut self) -> Vec<GamepadInput> {
+        let mut inputs = Vec::new();
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::Connected => log::info!("Gamepad connected: {}", self.gilrs.gamepad(event.id).name()),
+                EventType::Disconnected => {
+                    log::info!("Gamepad disconnected: {}", self.gilrs.gamepad(event.id).name());
+                    self.sticks.remove(&event.id);
+                }
+                // Held buttons don't repeat; gilrs only sends those with its repeat filter
+                EventType::ButtonPressed(button, _) => inputs.extend(match button {
+                    Button::DPadUp => Some(GamepadInput::Navigate(FocusDirection::Up)),
+                    Button::DPadDown => Some(GamepadInput::Navigate(FocusDirection::Down)),
+                    Button::DPadLeft => Some(GamepadInput::Navigate(FocusDirection::Left)),
+                    Button::DPadRight => Some(GamepadInput::Navigate(FocusDirection::Right)),
+                    Button::South => Some(GamepadInput::Activate),
+                    _ => None,
+                }),
+                EventType::AxisChanged(axis @ (Axis::LeftStickX | Axis::LeftStickY), value, _) => {
+                    let index = if axis == Axis::LeftStickX { 0 } else { 1 };
+                    let engaged = &mut self.sticks.entry(event.id).or_default()[index];
+                    if *engaged {
+                        *engaged = value.abs() > STICK_RELEASE;
+                    } else if value.abs() >= STICK_PRESS {
+                        *engaged = true;
+                        // Up is positive on gamepads
+                        inputs.push(GamepadInput::Navigate(match (axis, value > 0.0) {
+                            (Axis::LeftStickX, true) => FocusDirection::Right,
+                            (Axis::LeftStickX, false) => FocusDirection::Left,
+                            (_, true) => FocusDirection::Up,
+                            (_, false) => FocusDirection::Down,
+                        }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        inputs
+    }
+}
@@ -0,0 +1,60 @@
+use vello::kurbo::{Affine, Rect, Shape};
+use vello::peniko::{Color, Fill};
+
+use crate::components::{DrawCtx, Widget};
+use crate::render;
+
+// One per nesting level, repeating past the last
+const DEPTH_COLORS: [Color; 6] = [
+    Color::from_rgb8(230, 60, 60),
+    Color::from_rgb8(240, 150, 30),
+    Color::from_rgb8(210, 200, 30),
+    Color::from_rgb8(60, 180, 75),
+    Color::from_rgb8(40, 140, 230),
+    Color::from_rgb8(160, 80, 220),
+];
+const PADDING_ALPHA: f32 = 0.2;
+
+/// Outlines the bounds every widget was laid out at, coloured by how deep it
+/// sits in the tree. Toggled with [`App::with_layout_debug_key`](super::App::with_layout_debug_key).
+pub(super) struct LayoutDebugOverlay {
+    // Whether to also shade the space between bounds and content
+    padding: bool,
+}
+
+impl LayoutDebugOverlay {
+    pub(super) fn new(padding: bool) -> Self {
+        Self { padding }
+    }
+
+    pub(super) fn shows_padding(&self) -> bool {
+        self.padding
+    }
+
+    /// Draws `root` and everything below it into the overlay.
+    pub(super) fn draw(&self, ctx: &mut DrawCtx, root: &dyn Widget) {
+        self.draw_widget(ctx, root, 0);
+    }
+
+    fn draw_widget(&self, ctx: &mut DrawCtx, widget: &dyn Widget, depth: usize) {
+        let color = DEPTH_COLORS[depth % DEPTH_COLORS.len()];
+        let bounds = widget.bounds();
+        let overlay = ctx.overlay();
+        if self.padding
+            && let Some(content) = widget.content_bounds()
+        {
+            // Even-odd leaves the content itself clear
+            let mut ring = bounds.to_path(0.1);
+            ring.extend(content.to_path(0.1));
+            overlay.fill(Fill::EvenOdd, Affine::IDENTITY, color.with_alpha(PADDING_ALPHA), None, &ring);
+        }
+        if bounds.width() > 0.0 && bounds.height() > 0.0 {
+            // Inset by half the line so it stays inside the widget
+            let outline = Rect::new(bounds.x0 + 0.5, bounds.y0 + 0.5, bounds.x1 - 0.5, bounds.y1 - 0.5);
+            render::stroke_hairline(overlay, Affine::IDENTITY, color, &outline);
+        }
+        for child in widget.children() {
+            self.draw_widget(ctx, child.as_ref(), depth + 1);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::any::{type_name, Any};
+use std::cell::RefCell;
+
+use super::State;
+
+// A message along with its type's name, for the warning if it can't be handled
+type Queued = (Box<dyn Any>, &'static str);
+
+pub(super) type MessageHook = Box<dyn FnMut(&mut State, Box<dyn Any>, &'static str)>;
+
+thread_local! {
+    // Each message with its type's name, for the warning given when no update
+    // function is set. Drained in emit order after the event's snapshots
+    static MESSAGES: RefCell<Vec<Queued>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sends `msg` to the app's update function, see
+/// [`App::with_update`](super::App::with_update). Messages are handled in the
+/// order they were emitted, once the current event has been handled, from
+/// any callback on the thread running the [`App`](super::App).
+pub fn emit<Msg: 'static>(msg: Msg) {
+    MESSAGES.with_borrow_mut(|messages| messages.push((Box::new(msg), type_name::<Msg>())));
+}
+
+/// Turns a callback that returns a message into one a widget takes, emitting
+/// whatever it returns.
+///
+/// ```ignore
+/// Button::new("+").on_click(emitting(|| Some(Msg::Increment)))
+/// ```
+pub fn emitting<Msg: 'static>(mut f: impl FnMut() -> Option<Msg> + 'static) -> impl FnMut() + 'static {
+    move || {
+        if let Some(msg) = f() {
+            emit(msg);
+        }
+    }
+}
+
+/// Like [`emitting`], for callbacks that are passed a value.
+///
+/// ```ignore
+/// Checkbox::new("Dark mode").on_toggle(emitting_with(|on| Some(Msg::SetDark(on))))
+/// ```
+pub fn emitting_with<T, Msg: 'static>(mut f: impl FnMut(T) -> Option<Msg> + 'static) -> impl FnMut(T) + 'static {
+    move |value| {
+        if let Some(msg) = f(value) {
+            emit(msg);
+        }
+    }
+}
+
+/// Wraps a typed update function to take any message, warning about those of
+/// another type.
+pub(super) fn hook<Msg: 'static>(mut update: impl FnMut(&mut State, Msg) + 'static) -> MessageHook {
+    Box::new(move |state, msg, name| match msg.downcast::<Msg>() {
+        Ok(msg) => update(state, *msg),
+        Err(_) => log::warn!("Dropping a {} message; the update function takes {}", name, type_name::<Msg>()),
+    })
+}
+
+pub(super) fn take_messages() -> Vec<Queued> {
+    MESSAGES.with_borrow_mut(std::mem::take)
+}
@@ -1,77 +1,1400 @@
 use winit::application::ApplicationHandler;
-use winit::event::WindowEvent;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, MouseButton, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
-use winit::window::{Window, WindowAttributes, WindowId};
+use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+use winit::window::{CursorIcon, Fullscreen, Icon, UserAttentionType, Window, WindowAttributes, WindowId, WindowLevel};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use log::info;
+use serde::{Deserialize, Serialize};
 
-use crate::render::{RenderContext, PollsterBlockOn};
+use vello::Scene;
+use vello::wgpu;
+use vello::kurbo::{Point, Rect, Size, Vec2};
+
+use crate::animation::{self, Clock, FixedTimestep};
+use crate::components::{DrawCtx, LayoutCtx, Widget};
+use crate::event::{EventDispatcher, GestureConfig, KeyCombination, UiEvent};
+use crate::render::{DeviceLostAction, PostProcessHook, RenderDiagnostics};
+#[cfg(not(feature = "threaded-render"))]
+use crate::render::{PollsterBlockOn, RenderContext};
+#[cfg(feature = "threaded-render")]
+use crate::render::RenderThread;
+use crate::theme::Theme;
+use crate::ui::{ImmediateView, Ui};
+
+#[cfg(feature = "accesskit")]
+mod accessibility;
+mod context_menu;
+mod diagnostics;
+mod file_drop;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod layout_debug;
+mod message;
+mod modal;
+mod monitor;
+mod profiler;
+mod sequence;
+mod snapshot;
+mod toast;
+mod tooltip;
+mod user_event;
+mod window_state;
+
+pub use file_drop::hovered_files;
+pub use message::{emit, emitting, emitting_with};
+pub use modal::{pop_modal, push_modal};
+pub use monitor::{available_monitors, current_monitor, refresh_rate, set_always_on_top, set_fullscreen};
+pub use snapshot::{restore_snapshot, take_snapshot, UiSnapshot};
+pub use toast::show_toast;
+pub use user_event::request_attention;
+pub(crate) use user_event::{send_user_event, UserEvent};
+
+#[cfg(feature = "accesskit")]
+use accessibility::Accessibility;
+use context_menu::{MenuAction, OpenContextMenu};
+use diagnostics::DiagnosticsOverlay;
+#[cfg(feature = "gamepad")]
+use gamepad::Gamepads;
+use layout_debug::LayoutDebugOverlay;
+use message::MessageHook;
+use snapshot::SnapshotRequest;
+use modal::{Modal, ModalRequest};
+use profiler::Profiler;
+use toast::Toasts;
+use tooltip::Tooltips;
+use window_state::WindowGeometry;
+
+type DeviceLostHook = Box<dyn FnMut(&str) -> DeviceLostAction>;
+type ShortcutHook = Box<dyn FnMut(&mut State)>;
+type UpdateHook = Box<dyn FnMut(&mut State)>;
+type ExitHook = Box<dyn FnOnce(&State)>;
+type FileDropHook = Box<dyn FnMut(&[PathBuf], &mut State)>;
+type EventFilter = Box<dyn FnMut(&WindowEvent) -> bool>;
 
 pub struct App {
     script_path: Option<String>,
     use_cpu: bool,
+    backends: Option<wgpu::Backends>,
+    srgb: bool,
+    blit_filter: wgpu::FilterMode,
+    render_scale: f64,
+    frame_latency: Option<u32>,
+    smooth_resize: bool,
+    direct_surface: bool,
+    transparent: bool,
+    theme: Theme,
+    on_device_lost: Option<DeviceLostHook>,
+    on_exit: Option<ExitHook>,
+    on_file_drop: Option<FileDropHook>,
+    event_filter: Option<EventFilter>,
+    logging: bool,
+    post_process: Option<PostProcessHook>,
+    fullscreen: bool,
+    always_on_top: bool,
+    fullscreen_key: Option<KeyCode>,
+    diagnostics_key: Option<KeyCode>,
+    layout_debug: bool,
+    layout_debug_key: Option<KeyCode>,
+    icon_path: Option<PathBuf>,
+    state_file: Option<PathBuf>,
+    min_size: Option<PhysicalSize<u32>>,
+    max_size: Option<PhysicalSize<u32>>,
+    auto_size: bool,
+    gestures: GestureConfig,
+    max_frame_delta: Duration,
+    unfocused_frame_interval: Option<Duration>,
+    fixed_update: Option<(FixedTimestep, UpdateHook)>,
+    tooltip_delay: Duration,
+    shortcuts: Vec<(KeyCombination, ShortcutHook)>,
+    update: Option<MessageHook>,
+    state: State,
+    profiler_overlay: bool,
+    root: Option<Box<dyn Widget>>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
     pub fn new() -> Self {
-        env_logger::init();
         Self {
             script_path: None,
             use_cpu: false,
+            backends: None,
+            srgb: false,
+            blit_filter: wgpu::FilterMode::Linear,
+            render_scale: 1.0,
+            frame_latency: None,
+            smooth_resize: false,
+            direct_surface: false,
+            transparent: false,
+            theme: Theme::default(),
+            on_device_lost: None,
+            on_exit: None,
+            on_file_drop: None,
+            event_filter: None,
+            logging: true,
+            post_process: None,
+            fullscreen: false,
+            always_on_top: false,
+            fullscreen_key: Some(KeyCode::F11),
+            diagnostics_key: None,
+            layout_debug: false,
+            layout_debug_key: None,
+            icon_path: None,
+            state_file: None,
+            min_size: None,
+            max_size: None,
+            auto_size: false,
+            gestures: GestureConfig::default(),
+            max_frame_delta: Duration::from_millis(100),
+            unfocused_frame_interval: None,
+            fixed_update: None,
+            tooltip_delay: Duration::from_millis(500),
+            shortcuts: Vec::new(),
+            update: None,
+            state: State::new(0),
+            profiler_overlay: false,
+            root: None,
         }
     }
 
+    /// Whether `run` sets up env_logger. Turn this off when the host
+    /// application installs its own logger.
+    pub fn with_logging(mut self, logging: bool) -> Self {
+        self.logging = logging;
+        self
+    }
+
     pub fn with_use_cpu(mut self, use_cpu: bool) -> Self {
         self.use_cpu = use_cpu;
         self
     }
 
+    /// Limits which graphics APIs wgpu may use, e.g. `wgpu::Backends::VULKAN`.
+    /// Startup fails if none of them has an adapter. Without this, the
+    /// `WGPU_BACKEND` environment variable decides.
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = Some(backends);
+        self
+    }
+
+    /// Asks for an sRGB surface format instead of the default linear one, for
+    /// platforms that composite linear surfaces wrongly. Colours are meant to
+    /// look the same either way; see
+    /// [`RenderContext::is_srgb`](crate::render::RenderContext::is_srgb).
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// How the frame is filtered when it's copied to the window: `Linear` by
+    /// default, or `Nearest` for crisp pixels. See
+    /// [`RenderContext::set_blit_filter`](crate::render::RenderContext::set_blit_filter).
+    pub fn with_blit_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.blit_filter = filter;
+        self
+    }
+
+    /// Renders at `scale` times the window's resolution and stretches the
+    /// result over it. See
+    /// [`RenderContext::set_render_scale`](crate::render::RenderContext::set_render_scale).
+    pub fn with_render_scale(mut self, scale: f64) -> Self {
+        self.render_scale = scale;
+        self
+    }
+
+    /// How many frames the surface may queue ahead of the one on screen,
+    /// trading latency for throughput. wgpu's default of 2 without this. See
+    /// [`RenderContext::set_desired_maximum_frame_latency`](crate::render::RenderContext::set_desired_maximum_frame_latency).
+    pub fn with_desired_maximum_frame_latency(mut self, frames: u32) -> Self {
+        self.frame_latency = Some(frames);
+        self
+    }
+
+    /// Draws a frame for every new size while the window is being resized,
+    /// before the platform shows it, at a reduced resolution that snaps back
+    /// to full once the size settles. See
+    /// [`RenderContext::set_smooth_resize`](crate::render::RenderContext::set_smooth_resize).
+    pub fn with_smooth_resize(mut self, smooth: bool) -> Self {
+        self.smooth_resize = smooth;
+        self
+    }
+
+    /// Renders straight into the window's surface where it supports that,
+    /// saving the pass that copies each frame over from a texture. Falls back
+    /// to the copy elsewhere, with a post-process hook, or at a render scale
+    /// other than 1. See
+    /// [`RenderContext::set_direct_surface`](crate::render::RenderContext::set_direct_surface).
+    pub fn with_direct_surface(mut self, direct: bool) -> Self {
+        self.direct_surface = direct;
+        self
+    }
+
+    /// Opens a transparent window, so the desktop shows through wherever the
+    /// theme's background colour, or what's drawn over it, isn't opaque.
+    /// Depends on the platform's compositor; see
+    /// [`RenderContext::set_transparent`](crate::render::RenderContext::set_transparent).
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Decides what happens when the GPU device is lost (driver reset, update, ...).
+    /// Receives the loss reason. Without a hook the app recovers.
+    pub fn on_device_lost(mut self, f: impl FnMut(&str) -> DeviceLostAction + 'static) -> Self {
+        self.on_device_lost = Some(Box::new(f));
+        self
+    }
+
+    /// Runs `f` once, just before the app exits, for saving state or flushing
+    /// logs. The window and renderer are already gone by then.
+    pub fn on_exit(mut self, f: impl FnOnce(&State) + 'static) -> Self {
+        self.on_exit = Some(Box::new(f));
+        self
+    }
+
+    /// Calls `f` with the files dropped onto the window that no widget took,
+    /// all those of one drop together. See [`UiEvent::FileDrop`].
+    pub fn on_file_drop(mut self, f: impl FnMut(&[PathBuf], &mut State) + 'static) -> Self {
+        self.on_file_drop = Some(Box::new(f));
+        self
+    }
+
+    /// Sees every window event before the app does. Returning `false` swallows
+    /// it, skipping the app's own handling, e.g. for devtools or custom input.
+    ///
+    /// Swallowing `CloseRequested` keeps the window open, which is how to ask
+    /// about unsaved work first. `RedrawRequested` is always handled, whatever
+    /// the filter returns.
+    pub fn with_event_filter(mut self, f: impl FnMut(&WindowEvent) -> bool + 'static) -> Self {
+        self.event_filter = Some(Box::new(f));
+        self
+    }
+
+    /// See [`RenderContext::set_post_process`](crate::render::RenderContext::set_post_process).
+    /// Not supported with the `threaded-render` feature, since the hook isn't `Send`.
+    pub fn with_post_process(
+        mut self,
+        hook: impl FnMut(&mut wgpu::CommandEncoder, &wgpu::TextureView) + 'static,
+    ) -> Self {
+        self.post_process = Some(Box::new(hook));
+        self
+    }
+
+    /// Start in borderless fullscreen on the window's monitor. Use
+    /// [`set_fullscreen`] to pick another monitor or a video mode once running.
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Keeps the window above all others. Use [`set_always_on_top`] to change
+    /// it once running.
+    pub fn with_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// The key that toggles fullscreen at runtime (F11 by default), or `None` to disable.
+    pub fn with_fullscreen_key(mut self, key: Option<KeyCode>) -> Self {
+        self.fullscreen_key = key;
+        self
+    }
+
+    /// The key that shows or hides an overlay listing the GPU adapter, backend,
+    /// device limits, surface setup and frame times, for bug reports. Off
+    /// (`None`) by default.
+    pub fn with_diagnostics_key(mut self, key: Option<KeyCode>) -> Self {
+        self.diagnostics_key = key;
+        self
+    }
+
+    /// Outlines the bounds every widget was laid out at, above everything
+    /// else, in a colour for each level of nesting. For tracking down layout
+    /// that misbehaves. Off by default.
+    pub fn with_layout_debug(mut self, enabled: bool) -> Self {
+        self.layout_debug = enabled;
+        self
+    }
+
+    /// The key that steps the layout outlines from off, to on, to on with the
+    /// padding of widgets that have some shaded in, and back to off. See
+    /// [`Self::with_layout_debug`]. Off (`None`) by default.
+    pub fn with_layout_debug_key(mut self, key: Option<KeyCode>) -> Self {
+        self.layout_debug_key = key;
+        self
+    }
+
+    /// Window icon, loaded from a PNG when the window is created.
+    pub fn with_icon(mut self, path: impl AsRef<Path>) -> Self {
+        self.icon_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Remembers where the window was and how big in `path`, a small JSON
+    /// file, when the app exits, and reopens it there next time. A saved
+    /// position that would leave the window off every monitor is dropped and
+    /// the platform places the window instead. Nothing is saved while
+    /// fullscreen.
+    pub fn with_state_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.state_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Smallest size, in physical pixels, the window can be resized to.
+    pub fn with_min_size(mut self, width: u32, height: u32) -> Self {
+        self.min_size = Some(PhysicalSize::new(width, height));
+        self
+    }
+
+    /// Largest size, in physical pixels, the window can be resized to. The
+    /// render surface is capped to it too, in case the platform ignores the hint.
+    pub fn with_max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = Some(PhysicalSize::new(width, height));
+        self
+    }
+
+    /// Sizes the window to fit the root widget, for tool windows and dialogs.
+    /// The root is measured against the monitor's size once it's first laid
+    /// out and again whenever its layout goes out of date, and the window asked
+    /// to take that size within the min and max sizes and the monitor. The
+    /// platform has the last word: a size it refuses isn't asked for again until
+    /// the content's size changes. Left alone while fullscreen or maximized.
+    pub fn with_auto_size(mut self, auto_size: bool) -> Self {
+        self.auto_size = auto_size;
+        self
+    }
+
+    /// Caps how far the clock behind [`DrawCtx::time`](crate::components::DrawCtx::time)
+    /// and [`animation::now`] moves between two frames, 100ms by default. After a longer frame, such
+    /// as a stall or a debugger pause, animations carry on from where they were
+    /// rather than jumping ahead.
+    pub fn with_max_frame_delta(mut self, max: Duration) -> Self {
+        self.max_frame_delta = max;
+        self
+    }
+
+    /// Draws at most `fps` frames a second while the window doesn't have
+    /// focus, to save power in the background. Animations keep running, just
+    /// less smoothly, and the normal rate comes back as soon as the window is
+    /// focused again. Input still redraws straight away. Off by default.
+    pub fn with_unfocused_fps(mut self, fps: u32) -> Self {
+        if fps == 0 {
+            log::warn!("Unfocused frame rate must be positive; ignoring 0");
+            return self;
+        }
+        self.unfocused_frame_interval = Some(Duration::from_secs(1) / fps);
+        self
+    }
+
+    /// Calls `update` `hz` times a second, by the same clock, for game or
+    /// simulation logic that should advance at a steady rate whatever the
+    /// frame rate. Each frame runs the updates that have come due before it
+    /// draws, and the app keeps drawing frames while this is set. Widgets can
+    /// blend between the state before and after the last update by
+    /// [`DrawCtx::interpolation`](crate::components::DrawCtx::interpolation).
+    ///
+    /// A frame capped by [`Self::with_max_frame_delta`] only catches up that
+    /// far, so a stall can't set off a burst of updates.
+    pub fn with_fixed_update(mut self, hz: f64, update: impl FnMut(&mut State) + 'static) -> Self {
+        if !(hz > 0.0 && hz.is_finite()) {
+            log::warn!("Fixed update rate must be positive; ignoring {}", hz);
+            return self;
+        }
+        self.fixed_update = Some((FixedTimestep::from_hz(hz), Box::new(update)));
+        self
+    }
+
+    /// How long the pointer has to rest on a widget before its tooltip shows.
+    /// Half a second by default.
+    pub fn with_tooltip_delay(mut self, delay: Duration) -> Self {
+        self.tooltip_delay = delay;
+        self
+    }
+
+    /// Runs `f` whenever `keys` is pressed, whatever has focus. Shortcuts are
+    /// checked before widgets see the key, so a bound combination never
+    /// reaches them; holding it down doesn't fire it again.
+    ///
+    /// ```ignore
+    /// App::new().bind_shortcut("Ctrl+S".parse()?, |state| save(state));
+    /// ```
+    pub fn bind_shortcut(mut self, keys: KeyCombination, f: impl FnMut(&mut State) + 'static) -> Self {
+        self.shortcuts.push((keys, Box::new(f)));
+        self
+    }
+
+    /// Handles the messages widgets send with [`emit`], Elm style: callbacks
+    /// describe what happened as a `Msg` and `update` is the one place state
+    /// changes. Messages are applied in order once the event that produced
+    /// them has been handled, and the window is redrawn after. Messages of
+    /// any other type are dropped with a warning.
+    ///
+    /// ```ignore
+    /// enum Msg { Increment, Reset }
+    ///
+    /// App::new()
+    ///     .with_update(|state, msg| match msg {
+    ///         Msg::Increment => state.count += 1,
+    ///         Msg::Reset => state.count = 0,
+    ///     })
+    ///     .with_root(Button::new("+").on_click(emitting(|| Some(Msg::Increment))))
+    /// ```
+    pub fn with_update<Msg: 'static>(mut self, update: impl FnMut(&mut State, Msg) + 'static) -> Self {
+        self.update = Some(message::hook(update));
+        self
+    }
+
+    /// The state handed to shortcuts and the update function.
+    pub fn with_state(mut self, state: State) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Renders as fast as possible, without waiting for vsync, and graphs the
+    /// time each of the last 120 frames took in the top-right corner. The
+    /// graph is drawn over the root widget, see [`Self::with_root`].
+    pub fn with_profiler_overlay(mut self, enabled: bool) -> Self {
+        self.profiler_overlay = enabled;
+        self
+    }
+
+    /// Timing and distance thresholds for double-clicks and drags.
+    pub fn with_gestures(mut self, config: GestureConfig) -> Self {
+        self.gestures = config;
+        self
+    }
+
+    /// The widget tree to show. It fills the window, is laid out and drawn every
+    /// frame and receives the window's input. Without one the built-in demo runs.
+    pub fn with_root(mut self, root: impl Widget + 'static) -> Self {
+        self.root = Some(Box::new(root));
+        self
+    }
+
+    /// Describes the UI immediate-mode style instead, with `build` running
+    /// every time a frame is drawn. See [`Ui`]. Replaces any root widget.
+    pub fn with_ui(self, build: impl FnMut(&mut Ui) + 'static) -> Self {
+        self.with_root(ImmediateView::new(build))
+    }
+
     pub fn with_script(mut self, path: &str) -> Self {
         self.script_path = Some(path.to_string());
         self
     }
 
     pub fn run(self) {
-        let event_loop = EventLoop::new().unwrap();
+        if self.logging {
+            // Someone else may already have installed a logger, which is fine
+            let _ = env_logger::try_init();
+        }
+
+        let event_loop = EventLoop::<UserEvent>::with_user_event().build().unwrap();
         event_loop.set_control_flow(ControlFlow::Poll);
+        // Lets other threads wake the loop, e.g. once an image has been decoded
+        user_event::set_proxy(event_loop.create_proxy());
 
-        let mut app_state = AppState::new(self.script_path, self.use_cpu);
+        let mut app_state = AppState::new(self);
         let _ = event_loop.run_app(&mut app_state);
     }
 }
 
 struct AppState {
     window: Option<Arc<Window>>,
+    #[cfg(not(feature = "threaded-render"))]
     render_context: Option<RenderContext>,
+    #[cfg(feature = "threaded-render")]
+    render_thread: Option<RenderThread>,
+    #[allow(dead_code)] // read once the script engine is wired up
     script_path: Option<String>,
     use_cpu: bool,
-    resize_request: Option<winit::dpi::PhysicalSize<u32>>,
+    backends: Option<wgpu::Backends>,
+    srgb: bool,
+    blit_filter: wgpu::FilterMode,
+    render_scale: f64,
+    frame_latency: Option<u32>,
+    smooth_resize: bool,
+    direct_surface: bool,
+    transparent: bool,
+    theme: Theme,
+    on_device_lost: Option<DeviceLostHook>,
+    // Taken when it runs, so it only ever does once
+    on_exit: Option<ExitHook>,
+    on_file_drop: Option<FileDropHook>,
+    event_filter: Option<EventFilter>,
+    // Handed to the render context once it exists
+    post_process: Option<PostProcessHook>,
+    fullscreen: bool,
+    always_on_top: bool,
+    fullscreen_key: Option<KeyCode>,
+    diagnostics_key: Option<KeyCode>,
+    layout_debug_key: Option<KeyCode>,
+    icon_path: Option<PathBuf>,
+    state_file: Option<PathBuf>,
+    min_size: Option<PhysicalSize<u32>>,
+    max_size: Option<PhysicalSize<u32>>,
+    auto_size: bool,
+    // Window size last asked for to fit the root
+    auto_sized_to: Option<PhysicalSize<u32>>,
+    #[cfg(not(feature = "threaded-render"))]
+    resize_request: Option<PhysicalSize<u32>>,
+    root: Option<Box<dyn Widget>>,
+    events: EventDispatcher,
+    pending_input: PendingInput,
+    // Window size the root was last laid out for
+    laid_out_size: Option<Size>,
+    // Of the monitor the window is on, kept up to date as it moves
+    scale_factor: f64,
+    cursor_icon: CursorIcon,
+    // Feeds `DrawCtx::time`
+    clock: Clock,
+    unfocused_frame_interval: Option<Duration>,
+    focused: bool,
+    // When the last frame was drawn, to pace them while unfocused
+    last_frame: Option<Instant>,
+    fixed_update: Option<(FixedTimestep, UpdateHook)>,
+    tooltips: Tooltips,
+    toasts: Toasts,
+    profiler: Option<Profiler>,
+    // Shown while set
+    diagnostics: Option<DiagnosticsOverlay>,
+    // Shown while set
+    layout_debug: Option<LayoutDebugOverlay>,
+    // For the dispatchers of modals opened later
+    gestures: GestureConfig,
+    // Bottom to top; input goes to the last one
+    modals: Vec<Modal>,
+    // Above even the modals, taking all input while open
+    context_menu: Option<OpenContextMenu>,
+    shortcuts: Vec<(KeyCombination, ShortcutHook)>,
+    update: Option<MessageHook>,
+    state: State,
+    // Dropped since the last frame, one event each, delivered together
+    dropped_files: Vec<PathBuf>,
+    // Kept here too, since the dispatcher only sees events when there's a root
+    modifiers: ModifiersState,
+    // Last caret rect handed to the platform IME, which is on while this is set
+    ime_area: Option<Rect>,
+    #[cfg(feature = "accesskit")]
+    accessibility: Option<Accessibility>,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+}
+
+// Pointer input held back until the next frame, see `AppState::flush_input`
+#[derive(Default)]
+struct PendingInput {
+    pointer_moved: bool,
+    scroll: Option<Vec2>,
 }
 
 impl AppState {
-    fn new(script_path: Option<String>, use_cpu: bool) -> Self {
+    fn new(app: App) -> Self {
         Self {
             window: None,
+            #[cfg(not(feature = "threaded-render"))]
             render_context: None,
-            script_path,
-            use_cpu,
+            #[cfg(feature = "threaded-render")]
+            render_thread: None,
+            script_path: app.script_path,
+            use_cpu: app.use_cpu,
+            backends: app.backends,
+            srgb: app.srgb,
+            blit_filter: app.blit_filter,
+            render_scale: app.render_scale,
+            frame_latency: app.frame_latency,
+            smooth_resize: app.smooth_resize,
+            direct_surface: app.direct_surface,
+            transparent: app.transparent,
+            theme: app.theme,
+            on_device_lost: app.on_device_lost,
+            on_exit: app.on_exit,
+            on_file_drop: app.on_file_drop,
+            dropped_files: Vec::new(),
+            event_filter: app.event_filter,
+            post_process: app.post_process,
+            fullscreen: app.fullscreen,
+            always_on_top: app.always_on_top,
+            fullscreen_key: app.fullscreen_key,
+            diagnostics_key: app.diagnostics_key,
+            layout_debug_key: app.layout_debug_key,
+            icon_path: app.icon_path,
+            state_file: app.state_file,
+            min_size: app.min_size,
+            max_size: app.max_size,
+            auto_size: app.auto_size,
+            auto_sized_to: None,
+            #[cfg(not(feature = "threaded-render"))]
             resize_request: None,
+            root: app.root,
+            events: EventDispatcher::with_gestures(app.gestures.clone()),
+            gestures: app.gestures,
+            modals: Vec::new(),
+            context_menu: None,
+            pending_input: PendingInput::default(),
+            laid_out_size: None,
+            scale_factor: 1.0,
+            cursor_icon: CursorIcon::Default,
+            clock: {
+                let mut clock = Clock::new();
+                clock.set_max_delta(Some(app.max_frame_delta));
+                clock
+            },
+            unfocused_frame_interval: app.unfocused_frame_interval,
+            focused: true,
+            last_frame: None,
+            fixed_update: app.fixed_update,
+            tooltips: Tooltips::new(app.tooltip_delay),
+            toasts: Toasts::new(),
+            profiler: app.profiler_overlay.then(Profiler::new),
+            diagnostics: None,
+            layout_debug: app.layout_debug.then(|| LayoutDebugOverlay::new(false)),
+            shortcuts: app.shortcuts,
+            update: app.update,
+            state: app.state,
+            modifiers: ModifiersState::empty(),
+            ime_area: None,
+            #[cfg(feature = "accesskit")]
+            accessibility: None,
+            #[cfg(feature = "gamepad")]
+            gamepads: None,
         }
     }
 }
 
-impl ApplicationHandler for AppState {
+impl AppState {
+    // So animations started by input begin at the clock's time now, rather than
+    // at the last frame's. See `animation::now`.
+    fn sync_animation_time(&mut self) {
+        self.clock.catch_up();
+        animation::set_now(self.clock.elapsed());
+    }
+
+    // A fast mouse can deliver several moves and wheel ticks per frame. Rather than
+    // hit-testing each one, the latest position and summed scroll delta go out
+    // once, before the frame is drawn or before any other input that has to see
+    // the pointer where it really is.
+    fn flush_input(&mut self) -> bool {
+        let pointer_moved = std::mem::take(&mut self.pending_input.pointer_moved);
+        let scroll = self.pending_input.scroll.take();
+        if let Some(menu) = &mut self.context_menu {
+            // Nothing under an open menu sees the pointer, and it doesn't scroll
+            let position = self.events.cursor();
+            return pointer_moved && !matches!(menu.handle_event(&UiEvent::PointerMove { position }), MenuAction::Nothing);
+        }
+        let Some((target, events)) = input_target(&mut self.root, &mut self.modals, &mut self.events) else {
+            return false;
+        };
+        let mut consumed = false;
+        if pointer_moved {
+            let position = events.cursor();
+            consumed |= events.handle_event(target, &UiEvent::PointerMove { position });
+            let cursor_icon = events.cursor_icon(target);
+            if cursor_icon != self.cursor_icon
+                && let Some(window) = &self.window
+            {
+                window.set_cursor(cursor_icon);
+                self.cursor_icon = cursor_icon;
+            }
+            self.tooltips.pointer_moved(events.hovered_tooltip(target), position);
+        }
+        if let Some(delta) = scroll {
+            let position = events.cursor();
+            consumed |= events.handle_event(target, &UiEvent::Scroll { position, delta });
+        }
+        consumed
+    }
+
+    // Pops the top modal for an Escape that nothing in it consumed. Returns
+    // whether it did.
+    fn dismiss_modal(&mut self, unconsumed: &UiEvent) -> bool {
+        if self.modals.is_empty() || !matches!(unconsumed, UiEvent::KeyDown { key: Key::Named(NamedKey::Escape), .. }) {
+            return false;
+        }
+        self.modals.pop();
+        true
+    }
+
+    // Held back for `flush_input`, with only the latest position kept
+    fn queue_pointer_move(&mut self, cursor: Point) {
+        // Every dispatcher follows the cursor, so a modal opens or closes
+        // knowing where it is
+        self.events.set_cursor(cursor);
+        for modal in &mut self.modals {
+            modal.events.set_cursor(cursor);
+        }
+        self.pending_input.pointer_moved = true;
+    }
+
+    // Held back for `flush_input`, summed with the rest of the frame's
+    fn queue_scroll(&mut self, delta: Vec2) {
+        *self.pending_input.scroll.get_or_insert(Vec2::ZERO) += delta;
+    }
+
+    // Applies modals pushed or popped since the last call. Returns whether any were.
+    fn apply_modal_requests(&mut self) -> bool {
+        let requests = modal::take_requests();
+        let changed = !requests.is_empty();
+        if changed {
+            self.context_menu = None;
+        }
+        for request in requests {
+            match request {
+                ModalRequest::Push(widget) => {
+                    let cursor = self.events.cursor();
+                    self.modals.push(Modal::new(widget, self.gestures.clone(), cursor, self.modifiers));
+                }
+                ModalRequest::Pop => {
+                    self.modals.pop();
+                }
+            }
+        }
+        changed
+    }
+
+    fn snapshot(&self) -> UiSnapshot {
+        UiSnapshot::capture(&self.state, self.root.as_deref(), self.events.focused())
+    }
+
+    fn restore(&mut self, snapshot: UiSnapshot) {
+        if let Some(root) = &mut self.root {
+            snapshot.restore_tree(root.as_mut());
+        }
+        let (state, focus) = snapshot.into_parts();
+        self.state = state;
+        if let Some(root) = &mut self.root {
+            // Only onto a widget that's still there to take it
+            let focus = focus.filter(|path| {
+                crate::event::widget_ref_at_path(root.as_ref(), path).is_some_and(|widget| widget.is_focusable())
+            });
+            self.events.set_focus(root.as_mut(), focus);
+        }
+        self.laid_out_size = None;
+    }
+
+    // Takes and restores the snapshots asked for since the last call, in order.
+    // Returns whether anything was restored.
+    fn apply_snapshot_requests(&mut self) -> bool {
+        let mut restored = false;
+        for request in snapshot::take_requests() {
+            match request {
+                SnapshotRequest::Take(f) => f(self.snapshot()),
+                SnapshotRequest::Restore(snapshot) => {
+                    self.restore(snapshot);
+                    restored = true;
+                }
+            }
+        }
+        restored
+    }
+
+    // Offers the files dropped since the last call to the widget under the
+    // pointer, then the app's callback. Returns whether there were any.
+    fn apply_file_drops(&mut self) -> bool {
+        if self.dropped_files.is_empty() {
+            return false;
+        }
+        let paths = std::mem::take(&mut self.dropped_files);
+        info!("Dropped {} file(s): {:?}", paths.len(), paths);
+        let event = UiEvent::FileDrop { position: self.events.cursor(), paths: paths.clone() };
+        let consumed = input_target(&mut self.root, &mut self.modals, &mut self.events)
+            .is_some_and(|(target, events)| events.dispatch(target, &event));
+        if !consumed {
+            match &mut self.on_file_drop {
+                Some(on_file_drop) => on_file_drop(&paths, &mut self.state),
+                None => log::debug!("Nothing took the dropped files"),
+            }
+        }
+        true
+    }
+
+    // Runs the update function on messages emitted since the last call.
+    // Returns whether there were any.
+    fn apply_messages(&mut self) -> bool {
+        let messages = message::take_messages();
+        let changed = !messages.is_empty();
+        for (msg, name) in messages {
+            match &mut self.update {
+                Some(update) => update(&mut self.state, msg, name),
+                None => log::warn!("Dropping a {} message; there's no update function", name),
+            }
+        }
+        changed
+    }
+
+    // Opens the context menu of the widget under the pointer, or its nearest
+    // ancestor with one. Returns whether there was one to open.
+    fn open_context_menu(&mut self) -> bool {
+        let Some(window) = self.laid_out_size else {
+            return false;
+        };
+        let modal_depth = self.modals.len();
+        let Some((target, events)) = input_target(&mut self.root, &mut self.modals, &mut self.events) else {
+            return false;
+        };
+        let cursor = events.cursor();
+        let Some(path) = crate::event::hit_test(target, cursor) else {
+            return false;
+        };
+        let found = (0..=path.len()).rev().find_map(|depth| {
+            let menu = crate::event::widget_at_path(target, &path[..depth])?.context_menu()?;
+            (!menu.is_empty()).then(|| (depth, menu.labels()))
+        });
+        let Some((depth, labels)) = found else {
+            return false;
+        };
+        self.context_menu = Some(OpenContextMenu::new(path[..depth].to_vec(), modal_depth, labels, cursor, &self.theme, window));
+        self.tooltips.hide();
+        true
+    }
+
+    // Hands an event to the open context menu, carrying out whatever it
+    // decides. Returns whether anything changed on screen.
+    fn context_menu_event(&mut self, event: &WindowEvent) -> bool {
+        let Some(menu) = &mut self.context_menu else {
+            return false;
+        };
+        let Some(event) = UiEvent::from_window_event(event, self.events.cursor(), self.modifiers) else {
+            return false;
+        };
+        match menu.handle_event(&event) {
+            MenuAction::Nothing => false,
+            MenuAction::Redraw => true,
+            MenuAction::Close => {
+                self.context_menu = None;
+                true
+            }
+            MenuAction::Select(index) => {
+                // Closed before the callback runs, which may open another
+                let Some(menu) = self.context_menu.take() else {
+                    return true;
+                };
+                if menu.modal_depth == self.modals.len()
+                    && let Some((target, _)) = input_target(&mut self.root, &mut self.modals, &mut self.events)
+                    && let Some(items) = crate::event::widget_at_path(target, &menu.path).and_then(|w| w.context_menu())
+                {
+                    items.select(index);
+                }
+                true
+            }
+        }
+    }
+
+    // Fires the shortcut bound to `key`, if any. Returns whether one was bound.
+    fn run_shortcut(&mut self, key: &Key, repeat: bool) -> bool {
+        let Some((_, shortcut)) = self
+            .shortcuts
+            .iter_mut()
+            .find(|(keys, _)| keys.matches(key, self.modifiers))
+        else {
+            return false;
+        };
+        if !repeat {
+            shortcut(&mut self.state);
+        }
+        true
+    }
+
+    // Flashes the taskbar entry, see `request_attention`
+    fn request_attention(&self) {
+        match &self.window {
+            Some(window) => window.request_user_attention(Some(UserAttentionType::Informational)),
+            None => log::warn!("Ignoring attention request made before the window was created"),
+        }
+    }
+
+    // Tears everything down and stops the event loop. Safe to call more than
+    // once, e.g. for close requests that arrive while exiting.
+    fn quit(&mut self, event_loop: &ActiveEventLoop) {
+        // Explicitly drop resources to ensure clean shutdown
+        self.stop_renderer();
+        if let (Some(path), Some(window)) = (&self.state_file, &self.window)
+            && window.fullscreen().is_none()
+        {
+            WindowGeometry::of(window).save(path);
+        }
+        self.window = None;
+        if let Some(on_exit) = self.on_exit.take() {
+            on_exit(&self.state);
+        }
+        event_loop.exit();
+    }
+
+    // The platform may not honour the max size hint, or may be fullscreen past it
+    fn clamp_to_max_size(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        match self.max_size {
+            Some(max) => PhysicalSize::new(size.width.min(max.width), size.height.min(max.height)),
+            None => size,
+        }
+    }
+
+    // Draws the root, if there is one, laying it out over the whole window first
+    // when it has changed or the window has been resized
+    fn draw_root(&mut self) -> Option<Scene> {
+        self.auto_size_window();
+        let size = self.clamp_to_max_size(self.window.as_ref()?.inner_size());
+        self.draw_root_at(size)
+    }
+
+    // Asks for the window to fit the root when auto-sizing, if the root may
+    // have changed size since it last asked
+    fn auto_size_window(&mut self) {
+        let (true, Some(window), Some(root)) = (self.auto_size, &self.window, &mut self.root) else {
+            return;
+        };
+        if window.fullscreen().is_some() || window.is_maximized() {
+            return;
+        }
+        if self.auto_sized_to.is_some() && !root.needs_layout() {
+            return;
+        }
+        let monitor = window.current_monitor().map(|monitor| monitor.size());
+        let available = match monitor {
+            Some(size) => Size::new(size.width as f64, size.height as f64),
+            None => Size::new(f64::INFINITY, f64::INFINITY),
+        };
+        let measured = root.measure(&LayoutCtx { theme: &self.theme }, available);
+        let mut size = PhysicalSize::new(
+            measured.width.min(available.width).ceil().max(1.0) as u32,
+            measured.height.min(available.height).ceil().max(1.0) as u32,
+        );
+        if let Some(min) = self.min_size {
+            size = PhysicalSize::new(size.width.max(min.width), size.height.max(min.height));
+        }
+        size = self.clamp_to_max_size(size);
+        if self.auto_sized_to == Some(size) {
+            return;
+        }
+        self.auto_sized_to = Some(size);
+        // `None` means the resize happens later, with a Resized event
+        if let Some(actual) = window.request_inner_size(size)
+            && actual != size
+        {
+            log::debug!(
+                "Window is {}x{} rather than the {}x{} its content asked for",
+                actual.width, actual.height, size.width, size.height
+            );
+        }
+    }
+
+    // Like `draw_root`, for a window of `size` whether or not there is one
+    fn draw_root_at(&mut self, size: PhysicalSize<u32>) -> Option<Scene> {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.frame_started();
+        }
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.frame_started();
+        }
+        let render_diagnostics = self.diagnostics.as_ref().and_then(|_| self.renderer_diagnostics());
+        let delta = self.clock.tick();
+        animation::set_now(self.clock.elapsed());
+        let interpolation = match &mut self.fixed_update {
+            Some((timestep, update)) => {
+                for _ in 0..timestep.advance(delta) {
+                    update(&mut self.state);
+                }
+                timestep.alpha()
+            }
+            None => 0.0,
+        };
+        let root = self.root.as_mut()?;
+        let size = Size::new(size.width as f64, size.height as f64);
+
+        if self.laid_out_size != Some(size) || root.needs_layout() {
+            let layout_ctx = LayoutCtx { theme: &self.theme };
+            root.measure(&layout_ctx, size);
+            root.layout(&layout_ctx, Rect::from_origin_size((0.0, 0.0), size));
+            self.laid_out_size = Some(size);
+            crate::layout::count_layout_pass();
+        }
+
+        let mut scene = Scene::new();
+        let mut ctx = DrawCtx::new(&mut scene, &self.theme)
+            .with_time(self.clock.elapsed())
+            .with_delta(delta)
+            .with_interpolation(interpolation)
+            .with_scale_factor(self.scale_factor);
+        ctx.draw_child(root.as_mut());
+        if let Some(bounds) = self.events.focused_bounds(root.as_ref()) {
+            ctx.draw_focus_ring(bounds);
+        }
+        ctx.flush_overlay();
+        for modal in &mut self.modals {
+            modal.draw(&mut ctx, &self.theme, size);
+        }
+        if let Some(menu) = &self.context_menu {
+            menu.draw(&mut ctx);
+        }
+        self.toasts.draw(&mut ctx, size);
+        self.tooltips.draw(&mut ctx, size);
+        if let Some(layout_debug) = &self.layout_debug {
+            layout_debug.draw(&mut ctx, root.as_ref());
+            for modal in &self.modals {
+                layout_debug.draw(&mut ctx, modal.widget.as_ref());
+            }
+        }
+        if let Some(profiler) = &self.profiler {
+            profiler.draw(&mut ctx, size);
+        }
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.draw(&mut ctx, render_diagnostics.as_ref());
+        }
+        ctx.finish();
+        Some(scene)
+    }
+
+    // Turns the platform IME on while the focused widget takes text, keeping its
+    // candidate window next to the caret as it moves
+    fn update_ime(&mut self) {
+        let Some(window) = &self.window else {
+            return;
+        };
+        let area = input_target(&mut self.root, &mut self.modals, &mut self.events)
+            .and_then(|(target, events)| events.focused_ime_area(target));
+        if area == self.ime_area {
+            return;
+        }
+        if area.is_some() != self.ime_area.is_some() {
+            window.set_ime_allowed(area.is_some());
+        }
+        if let Some(area) = area {
+            window.set_ime_cursor_area(
+                PhysicalPosition::new(area.x0, area.y0),
+                PhysicalSize::new(area.width(), area.height()),
+            );
+        }
+        self.ime_area = area;
+    }
+
+    // Carries out what assistive technology asked for since the last frame
+    #[cfg(feature = "accesskit")]
+    fn handle_accessibility_actions(&mut self) {
+        let Some(accessibility) = &mut self.accessibility else {
+            return;
+        };
+        let actions = accessibility.take_actions();
+        let Some((target, events)) = input_target(&mut self.root, &mut self.modals, &mut self.events) else {
+            return;
+        };
+        for (action, path) in actions {
+            match action {
+                accesskit::Action::Focus => events.set_focus(target, Some(path)),
+                // Clicked as if by the pointer, which is then put back where it really is
+                accesskit::Action::Click | accesskit::Action::Expand | accesskit::Action::Collapse => {
+                    let Some(position) = crate::event::widget_ref_at_path(target, &path).map(|w| w.bounds().center())
+                    else {
+                        continue;
+                    };
+                    let button = winit::event::MouseButton::Left;
+                    events.handle_event(target, &UiEvent::PointerDown { position, button });
+                    events.handle_event(target, &UiEvent::PointerUp { position, button });
+                    let cursor = events.cursor();
+                    events.handle_event(target, &UiEvent::PointerMove { position: cursor });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Moves focus and activates widgets for whatever the gamepads did since the
+    // last check, as the arrow keys and Enter would. An arrow no widget
+    // consumes moves focus towards it.
+    #[cfg(feature = "gamepad")]
+    fn handle_gamepad_input(&mut self) {
+        let Some(gamepads) = &mut self.gamepads else {
+            return;
+        };
+        let inputs = gamepads.poll();
+        if inputs.is_empty() {
+            return;
+        }
+        let Some((target, events)) = input_target(&mut self.root, &mut self.modals, &mut self.events) else {
+            return;
+        };
+        let modifiers = events.modifiers();
+        let mut handled = false;
+        for input in inputs {
+            let key = Key::Named(input.key());
+            let consumed = events.handle_event(target, &UiEvent::KeyDown { key: key.clone(), modifiers, repeat: false });
+            handled |= consumed;
+            if !consumed && let gamepad::GamepadInput::Navigate(direction) = input {
+                handled |= events.focus_towards(target, direction);
+            }
+            handled |= events.handle_event(target, &UiEvent::KeyUp { key, modifiers });
+        }
+        if handled && let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn gamepad_wake_at(&self) -> Option<Instant> {
+        self.gamepads.as_ref().map(Gamepads::wake_at)
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    fn gamepad_wake_at(&self) -> Option<Instant> {
+        None
+    }
+
+    // When the next animation frame is due while the window is in the
+    // background, if their rate is being capped
+    fn next_unfocused_frame(&self) -> Option<Instant> {
+        if self.focused {
+            return None;
+        }
+        Some(self.last_frame? + self.unfocused_frame_interval?)
+    }
+
+    // Sends assistive technology the tree as it was just drawn
+    #[cfg(feature = "accesskit")]
+    fn update_accessibility(&mut self) {
+        let (Some(accessibility), Some(window)) = (&mut self.accessibility, &self.window) else {
+            return;
+        };
+        if let Some((target, events)) = input_target(&mut self.root, &mut self.modals, &mut self.events) {
+            accessibility.update(window, target, events.focused());
+        }
+    }
+}
+
+// Everything that touches the renderer goes through these, so the event loop
+// doesn't care whether frames are drawn inline or on the render thread.
+#[cfg(not(feature = "threaded-render"))]
+impl AppState {
+    fn start_renderer(&mut self, window: Arc<Window>) {
+        let mut render_context = RenderContext::new(window, self.use_cpu, self.backends, self.srgb).pollster_block_on();
+        render_context.set_background_color(self.theme.background);
+        render_context.set_blit_filter(self.blit_filter);
+        render_context.set_render_scale(self.render_scale);
+        if let Some(frames) = self.frame_latency {
+            render_context.set_desired_maximum_frame_latency(frames);
+        }
+        render_context.set_smooth_resize(self.smooth_resize);
+        render_context.set_direct_surface(self.direct_surface);
+        render_context.set_transparent(self.transparent);
+        render_context.set_max_size(self.max_size);
+        render_context.set_post_process(self.post_process.take());
+        if self.profiler.is_some() {
+            render_context.set_present_mode(wgpu::PresentMode::AutoNoVsync);
+        }
+        self.render_context = Some(render_context);
+    }
+
+    fn stop_renderer(&mut self) {
+        self.render_context = None;
+    }
+
+    fn device_lost(&self) -> Option<String> {
+        self.render_context.as_ref().and_then(|rc| rc.device_lost())
+    }
+
+    fn renderer_diagnostics(&self) -> Option<RenderDiagnostics> {
+        self.render_context.as_ref().map(RenderContext::diagnostics)
+    }
+
+    fn renderer_animating(&self) -> bool {
+        self.render_context.as_ref().is_some_and(RenderContext::is_animating)
+    }
+
+    // When a frame is needed to finish off a resize at full resolution
+    fn renderer_wake_at(&self) -> Option<Instant> {
+        self.render_context.as_ref().and_then(|rc| rc.resize_settles_at())
+    }
+
+    fn recover_renderer(&mut self) {
+        self.render_context = self.render_context.take().map(RenderContext::recover);
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        // Defer resize to RedrawRequested to avoid blocking event loop
+        self.resize_request = Some(size);
+        if self.smooth_resize {
+            // Unless the frame is cheap, in which case the window never shows
+            // one of the wrong size
+            self.render();
+        } else if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+
+    fn render(&mut self) {
+        let content = self.draw_root();
+        if let Some(render_context) = &mut self.render_context {
+            if let Some(size) = self.resize_request.take() {
+                render_context.resize(size);
+            }
+            if content.is_some() {
+                render_context.set_content(content);
+            }
+            render_context.render();
+        }
+    }
+}
+
+#[cfg(feature = "threaded-render")]
+impl AppState {
+    fn start_renderer(&mut self, window: Arc<Window>) {
+        if self.post_process.take().is_some() {
+            log::warn!("Post-process hooks aren't supported with threaded rendering; ignoring it");
+        }
+        let render_thread = RenderThread::spawn(window, self.use_cpu, self.backends, self.srgb);
+        render_thread.set_background_color(self.theme.background);
+        render_thread.set_blit_filter(self.blit_filter);
+        render_thread.set_render_scale(self.render_scale);
+        if let Some(frames) = self.frame_latency {
+            render_thread.set_desired_maximum_frame_latency(frames);
+        }
+        render_thread.set_smooth_resize(self.smooth_resize);
+        render_thread.set_direct_surface(self.direct_surface);
+        render_thread.set_transparent(self.transparent);
+        render_thread.set_max_size(self.max_size);
+        if self.profiler.is_some() {
+            render_thread.set_present_mode(wgpu::PresentMode::AutoNoVsync);
+        }
+        self.render_thread = Some(render_thread);
+    }
+
+    fn stop_renderer(&mut self) {
+        // Joins the thread, so the surface is released before the window
+        self.render_thread = None;
+    }
+
+    fn device_lost(&self) -> Option<String> {
+        self.render_thread.as_ref().and_then(|rt| rt.device_lost())
+    }
+
+    fn renderer_diagnostics(&self) -> Option<RenderDiagnostics> {
+        self.render_thread.as_ref().and_then(RenderThread::diagnostics)
+    }
+
+    // The render thread keeps its own animations going
+    fn renderer_animating(&self) -> bool {
+        false
+    }
+
+    // The render thread finishes off resizes by itself
+    fn renderer_wake_at(&self) -> Option<Instant> {
+        None
+    }
+
+    fn recover_renderer(&mut self) {
+        if let Some(render_thread) = &self.render_thread {
+            render_thread.recover();
+        }
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        // The render thread applies it and draws a frame at the new size
+        if let Some(render_thread) = &self.render_thread {
+            render_thread.resize(size);
+        }
+        // ...with content laid out for that size, rather than waiting for a redraw
+        if self.smooth_resize {
+            self.render();
+        }
+    }
+
+    fn render(&mut self) {
+        let content = self.draw_root();
+        if let Some(render_thread) = &self.render_thread {
+            if content.is_some() {
+                render_thread.set_content(content);
+            }
+            render_thread.request_redraw();
+        }
+    }
+}
+
+// Where input goes: the top modal if there is one, otherwise the root
+fn input_target<'a>(
+    root: &'a mut Option<Box<dyn Widget>>,
+    modals: &'a mut [Modal],
+    events: &'a mut EventDispatcher,
+) -> Option<(&'a mut dyn Widget, &'a mut EventDispatcher)> {
+    let root = root.as_mut()?;
+    Some(match modals.last_mut() {
+        Some(modal) => (modal.widget.as_mut(), &mut modal.events),
+        None => (root.as_mut(), events),
+    })
+}
+
+fn load_icon(path: &Path) -> anyhow::Result<Icon> {
+    let image = image::open(path)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(Icon::from_rgba(image.into_raw(), width, height)?)
+}
+
+impl ApplicationHandler<UserEvent> for AppState {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         if self.window.is_none() {
-            let window_attributes = WindowAttributes::default()
-                .with_title("RasmalaiUI");
+            let mut window_attributes = WindowAttributes::default()
+                .with_title("RasmalaiUI")
+                .with_transparent(self.transparent);
+            if let Some(path) = &self.icon_path {
+                // A broken icon shouldn't stop the app from starting
+                match load_icon(path) {
+                    Ok(icon) => window_attributes = window_attributes.with_window_icon(Some(icon)),
+                    Err(e) => log::warn!("Failed to load window icon {}: {}", path.display(), e),
+                }
+            }
+            if let Some(size) = self.min_size {
+                window_attributes = window_attributes.with_min_inner_size(size);
+            }
+            if let Some(size) = self.max_size {
+                window_attributes = window_attributes.with_max_inner_size(size);
+            }
+            if let Some(geometry) = self.state_file.as_deref().and_then(WindowGeometry::load) {
+                if let Some(size) = geometry.size() {
+                    window_attributes = window_attributes.with_inner_size(self.clamp_to_max_size(size));
+                }
+                if let Some(position) = geometry.position(event_loop) {
+                    window_attributes = window_attributes.with_position(position);
+                }
+            }
+            if self.always_on_top {
+                window_attributes = window_attributes.with_window_level(WindowLevel::AlwaysOnTop);
+            }
+            if self.fullscreen {
+                // `None` picks the monitor the window would have opened on
+                window_attributes = window_attributes.with_fullscreen(Some(Fullscreen::Borderless(None)));
+            }
+            // The adapter has to be set up before the window is first shown
+            #[cfg(feature = "accesskit")]
+            {
+                window_attributes = window_attributes.with_visible(false);
+            }
             
             let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
             self.window = Some(window.clone());
+            monitor::set_window(&window);
+            self.scale_factor = window.scale_factor();
+            // New windows start with the IME off
+            self.ime_area = None;
+            #[cfg(feature = "accesskit")]
+            {
+                self.accessibility = Some(Accessibility::new(event_loop, &window));
+                window.set_visible(true);
+            }
+            #[cfg(feature = "gamepad")]
+            if self.gamepads.is_none() {
+                self.gamepads = Gamepads::new();
+            }
             
-            // Initialize renderer
-            // functionality to be added in RenderContext
-            self.render_context = Some(RenderContext::new(window.clone(), self.use_cpu).pollster_block_on());
+            self.start_renderer(window);
         }
     }
 
@@ -81,43 +1404,271 @@ impl ApplicationHandler for AppState {
         window_id: WindowId,
         event: WindowEvent,
     ) {
-        if let Some(window) = &self.window {
-            if window.id() == window_id {
-                match event {
-                    WindowEvent::CloseRequested => {
-                        info!("Close requested");
-                        // Explicitly drop resources to ensure clean shutdown
-                        self.render_context = None;
-                        self.window = None;
-                        event_loop.exit();
-                    },
-                    WindowEvent::RedrawRequested => {
-                        if let Some(render_context) = &mut self.render_context {
-                            if let Some(size) = self.resize_request.take() {
-                                render_context.resize(size);
+        self.sync_animation_time();
+        if let Some(window) = self.window.clone()
+            && window.id() == window_id
+        {
+            let filtered = self.event_filter.as_mut().is_some_and(|filter| !filter(&event));
+            #[cfg(feature = "accesskit")]
+            if let Some(accessibility) = &mut self.accessibility {
+                // Kept in step even with the event filtered, since the adapter tracks window state
+                accessibility.process_event(&window, &event);
+            }
+            // Skipping a redraw would leave the window without a frame
+            if filtered && !matches!(event, WindowEvent::RedrawRequested) {
+                return;
+            }
+            if let WindowEvent::ModifiersChanged(modifiers) = &event {
+                self.modifiers = modifiers.state();
+                // Dispatchers under a modal don't see the event but still need to know
+                self.events.set_modifiers(self.modifiers);
+                for modal in &mut self.modals {
+                    modal.events.set_modifiers(self.modifiers);
+                }
+            }
+            if let WindowEvent::KeyboardInput { event: key_event, .. } = &event
+                && key_event.state == ElementState::Pressed
+                && self.run_shortcut(&key_event.logical_key, key_event.repeat)
+            {
+                window.request_redraw();
+                return;
+            }
+
+            if self.root.is_some() {
+                match &event {
+                    WindowEvent::CursorMoved { position, .. } => {
+                        self.queue_pointer_move(Point::new(position.x, position.y));
+                        window.request_redraw();
+                    }
+                    WindowEvent::MouseWheel { .. } => {
+                        if let Some(UiEvent::Scroll { delta, .. }) =
+                            UiEvent::from_window_event(&event, self.events.cursor(), self.modifiers)
+                        {
+                            self.queue_scroll(delta);
+                        }
+                        window.request_redraw();
+                    }
+                    // Flushed just before drawing
+                    WindowEvent::RedrawRequested => {}
+                    _ if self.context_menu.is_some() => {
+                        let mut consumed = self.flush_input();
+                        consumed |= self.context_menu_event(&event);
+                        if consumed {
+                            window.request_redraw();
+                        }
+                    }
+                    _ => {
+                        let mut consumed = self.flush_input();
+                        if let Some((target, events)) =
+                            input_target(&mut self.root, &mut self.modals, &mut self.events)
+                        {
+                            consumed |= events.handle_window_event(target, &event);
+                        }
+                        if !consumed
+                            && let Some(ui_event) = UiEvent::from_window_event(&event, self.events.cursor(), self.modifiers)
+                        {
+                            consumed = self.dismiss_modal(&ui_event);
+                        }
+                        if !consumed
+                            && let WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Right, .. } =
+                                event
+                        {
+                            consumed = self.open_context_menu();
+                        }
+                        // Clicking or leaving the window dismisses the tooltip
+                        if matches!(event, WindowEvent::CursorLeft { .. } | WindowEvent::MouseInput { .. }) {
+                            consumed |= self.tooltips.hide();
+                        }
+                        if consumed {
+                            window.request_redraw();
+                        }
+                    }
+                }
+            }
+
+            match event {
+                WindowEvent::CloseRequested => {
+                    info!("Close requested");
+                    self.quit(event_loop);
+                },
+                WindowEvent::RedrawRequested => {
+                    if let Some(reason) = self.device_lost() {
+                        let action = match &mut self.on_device_lost {
+                            Some(on_device_lost) => on_device_lost(&reason),
+                            None => DeviceLostAction::Recover,
+                        };
+                        match action {
+                            DeviceLostAction::Recover => self.recover_renderer(),
+                            DeviceLostAction::Exit => {
+                                info!("Exiting after device loss");
+                                self.quit(event_loop);
+                                return;
                             }
-                            render_context.render();
                         }
                     }
-                    WindowEvent::Resized(size) => {
-                         // Defer resize to RedrawRequested to avoid blocking event loop
-                         self.resize_request = Some(size);
-                         window.request_redraw();
+                    #[cfg(feature = "accesskit")]
+                    self.handle_accessibility_actions();
+                    self.last_frame = Some(Instant::now());
+                    self.flush_input();
+                    self.render();
+                    self.update_ime();
+                    #[cfg(feature = "accesskit")]
+                    self.update_accessibility();
+                }
+                WindowEvent::KeyboardInput { event, .. }
+                    if event.state == ElementState::Pressed
+                        && !event.repeat
+                        && self.fullscreen_key.is_some_and(|key| event.physical_key == PhysicalKey::Code(key)) =>
+                {
+                    // The Resized event that follows recreates the surface and target texture
+                    if window.fullscreen().is_some() {
+                        window.set_fullscreen(None);
+                    } else {
+                        window.set_fullscreen(Some(Fullscreen::Borderless(window.current_monitor())));
+                    }
+                }
+                WindowEvent::KeyboardInput { event, .. }
+                    if event.state == ElementState::Pressed
+                        && !event.repeat
+                        && self.diagnostics_key.is_some_and(|key| event.physical_key == PhysicalKey::Code(key)) =>
+                {
+                    self.diagnostics = match self.diagnostics {
+                        Some(_) => None,
+                        None => Some(DiagnosticsOverlay::new()),
+                    };
+                    window.request_redraw();
+                }
+                WindowEvent::KeyboardInput { event, .. }
+                    if event.state == ElementState::Pressed
+                        && !event.repeat
+                        && self.layout_debug_key.is_some_and(|key| event.physical_key == PhysicalKey::Code(key)) =>
+                {
+                    self.layout_debug = match &self.layout_debug {
+                        None => Some(LayoutDebugOverlay::new(false)),
+                        Some(overlay) if !overlay.shows_padding() => Some(LayoutDebugOverlay::new(true)),
+                        Some(_) => None,
+                    };
+                    window.request_redraw();
+                }
+                WindowEvent::Resized(size) => {
+                    // It was only kept inside the old size
+                    self.context_menu = None;
+                    self.resize(self.clamp_to_max_size(size));
+                }
+                WindowEvent::HoveredFile(path) => {
+                    file_drop::hover(path);
+                    window.request_redraw();
+                }
+                WindowEvent::HoveredFileCancelled => {
+                    file_drop::end_hover();
+                    window.request_redraw();
+                }
+                WindowEvent::DroppedFile(path) => {
+                    file_drop::end_hover();
+                    self.dropped_files.push(path);
+                }
+                WindowEvent::Focused(focused) => {
+                    self.focused = focused;
+                    // Back to the full rate straight away rather than after the slow frame
+                    if focused {
+                        window.request_redraw();
                     }
-                    _ => {}
+                }
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    info!("Scale factor changed to {}", scale_factor);
+                    self.scale_factor = scale_factor;
+                    // Platforms don't always follow up with a Resized when the physical
+                    // size stays put, so rebuild the surface and layout here regardless
+                    self.laid_out_size = None;
+                    self.resize(self.clamp_to_max_size(window.inner_size()));
+                    window.request_redraw();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Sent once an image finishes loading in the background
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+        self.sync_animation_time();
+        match event {
+            UserEvent::Redraw => {
+                if let Some(window) = &self.window {
+                    window.request_redraw();
                 }
             }
+            UserEvent::RequestAttention => self.request_attention(),
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(window) = &self.window {
+    // Also covers the loop stopping some other way than through `quit`
+    fn exiting(&mut self, event_loop: &ActiveEventLoop) {
+        self.quit(event_loop);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        #[cfg(feature = "gamepad")]
+        self.handle_gamepad_input();
+        // Snapshots first, so they're of the state before any messages from the same event
+        if (self.apply_snapshot_requests()
+            | self.apply_file_drops()
+            | self.apply_messages()
+            | self.apply_modal_requests()
+            | self.toasts.apply_requests())
+            && let Some(window) = &self.window
+        {
             window.request_redraw();
         }
+        // The built-in demo scene drawn without a root widget spins forever, the
+        // profiler wants every frame it can get, fixed updates need frames to
+        // run in, toasts may be fading, and the renderer may be fading its background
+        let animating = self.profiler.is_some()
+            || self.fixed_update.is_some()
+            || self.toasts.is_animating()
+            || self.root.as_ref().is_none_or(|root| root.is_animating())
+            || self.renderer_animating();
+        if animating {
+            match self.next_unfocused_frame() {
+                // Held back while in the background
+                Some(next_frame) if next_frame > Instant::now() => {
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(next_frame));
+                }
+                _ => {
+                    event_loop.set_control_flow(ControlFlow::Poll);
+                    if let Some(window) = &self.window {
+                        window.request_redraw();
+                    }
+                }
+            }
+        } else if let Some(wake_at) = self
+            .tooltips
+            .wake_at()
+            .into_iter()
+            .chain(self.toasts.wake_at())
+            .chain(self.renderer_wake_at())
+            .chain(self.gamepad_wake_at())
+            .min()
+        {
+            // Wake up in time to show the tooltip the pointer is resting on, to
+            // fade out a toast, to redraw at full resolution once a resize has settled, or to check
+            // the gamepads
+            if wake_at <= Instant::now() {
+                event_loop.set_control_flow(ControlFlow::Wait);
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            } else {
+                event_loop.set_control_flow(ControlFlow::WaitUntil(wake_at));
+            }
+        } else {
+            // Sleep until input or a resize asks for a redraw
+            event_loop.set_control_flow(ControlFlow::Wait);
+        }
     }
 }
 
 // Placeholder for State to match README usage, though logic is likely elsewhere
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct State {
     pub count: i32,
 }
@@ -127,3 +1678,174 @@ impl State {
         Self { count }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::components::test_widget::Block;
+    use crate::components::{Button, Checkbox, Title};
+    use crate::layout::{HStack, ScrollView, VStack};
+
+    fn child<W: Widget>(app: &mut AppState, index: usize) -> &mut W {
+        let child = &mut app.root.as_mut().unwrap().children_mut()[index];
+        (child.as_mut() as &mut dyn Any).downcast_mut().unwrap()
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn lays_out_again_only_when_text_changes() {
+        let root = VStack::new().child(Title::new("Title")).child(Checkbox::new("Check")).child(Button::new("Button"));
+        let mut app = AppState::new(App::new().with_root(root));
+        let size = PhysicalSize::new(400, 300);
+        app.draw_root_at(size);
+        let passes = crate::layout::layout_passes();
+        app.draw_root_at(size);
+        assert_eq!(crate::layout::layout_passes(), passes);
+
+        child::<Title>(&mut app, 0).set_text("A longer title");
+        app.draw_root_at(size);
+        app.draw_root_at(size);
+        assert_eq!(crate::layout::layout_passes(), passes + 1);
+        child::<Checkbox>(&mut app, 1).set_label("A longer label");
+        app.draw_root_at(size);
+        app.draw_root_at(size);
+        assert_eq!(crate::layout::layout_passes(), passes + 2);
+        child::<Button>(&mut app, 2).set_text("A longer button");
+        app.draw_root_at(size);
+        app.draw_root_at(size);
+        assert_eq!(crate::layout::layout_passes(), passes + 3);
+    }
+
+    #[test]
+    fn pointer_input_is_coalesced_until_flushed() {
+        let block = Block::new(100.0, 100.0);
+        let log = block.events();
+        let mut app = AppState::new(App::new().with_root(block));
+        app.draw_root_at(PhysicalSize::new(100, 100));
+
+        for x in [10.0, 20.0, 30.0] {
+            app.queue_pointer_move(Point::new(x, 50.0));
+            app.queue_scroll(Vec2::new(0.0, -5.0));
+        }
+        assert!(log.borrow().is_empty());
+        assert!(app.flush_input());
+        let position = Point::new(30.0, 50.0);
+        assert_eq!(
+            log.borrow().iter().filter(|event| **event != UiEvent::PointerEnter).collect::<Vec<_>>(),
+            [&UiEvent::PointerMove { position }, &UiEvent::Scroll { position, delta: Vec2::new(0.0, -15.0) }]
+        );
+        assert!(!app.flush_input(), "nothing left to flush");
+    }
+
+    // Like a window event reaching the app, past the shortcuts and context menu
+    fn send(app: &mut AppState, event: UiEvent) -> bool {
+        let (target, events) = input_target(&mut app.root, &mut app.modals, &mut app.events).unwrap();
+        events.handle_event(target, &event) || app.dismiss_modal(&event)
+    }
+
+    fn click(app: &mut AppState, position: Point) {
+        send(app, UiEvent::PointerDown { position, button: MouseButton::Left });
+        send(app, UiEvent::PointerUp { position, button: MouseButton::Left });
+    }
+
+    #[test]
+    fn modal_takes_all_input_until_escape_pops_it() {
+        let root = Block::new(300.0, 300.0);
+        let log = root.events();
+        let mut app = AppState::new(App::new().with_root(root));
+        let size = PhysicalSize::new(300, 300);
+        app.draw_root_at(size);
+
+        let clicks = Rc::new(Cell::new(0));
+        push_modal(Button::new("OK").on_click({
+            let clicks = clicks.clone();
+            move || clicks.set(clicks.get() + 1)
+        }));
+        assert!(app.apply_modal_requests());
+        app.draw_root_at(size);
+        let button = app.modals[0].widget.bounds();
+        assert!(button.width() > 0.0 && button.x0 > 0.0, "centred in the window: {button:?}");
+
+        click(&mut app, button.center());
+        assert_eq!(clicks.get(), 1);
+        // Outside the modal the click is blocked, not handed to the root
+        click(&mut app, Point::new(5.0, 5.0));
+        assert_eq!(clicks.get(), 1);
+        assert!(log.borrow().is_empty(), "{:?}", log.borrow());
+
+        let key = |key| UiEvent::KeyDown { key: Key::Named(key), modifiers: ModifiersState::empty(), repeat: false };
+        assert!(!send(&mut app, key(NamedKey::ArrowDown)));
+        assert_eq!(app.modals.len(), 1);
+        assert!(send(&mut app, key(NamedKey::Escape)));
+        assert!(app.modals.is_empty());
+        click(&mut app, Point::new(5.0, 5.0));
+        assert!(!log.borrow().is_empty(), "the root gets input again");
+    }
+
+    #[test]
+    fn snapshot_restores_state_focus_and_widgets() {
+        let root = VStack::new().child(Checkbox::new("Check")).child(ScrollView::new(Block::new(100.0, 1000.0)));
+        let mut app = AppState::new(App::new().with_root(root).with_state(State::new(1)));
+        let size = PhysicalSize::new(200, 200);
+        app.draw_root_at(size);
+        child::<Checkbox>(&mut app, 0).set_checked(true);
+        child::<ScrollView>(&mut app, 1).set_scroll_offset(50.0);
+        app.events.set_focus(app.root.as_mut().unwrap().as_mut(), Some(vec![0]));
+
+        let taken = Rc::new(RefCell::new(None));
+        take_snapshot({
+            let taken = taken.clone();
+            move |snapshot| *taken.borrow_mut() = Some(snapshot)
+        });
+        app.apply_snapshot_requests();
+        // Through its serialized form, as undo steps or a file would keep it
+        let json = serde_json::to_string(&taken.take().unwrap()).unwrap();
+        let snapshot: UiSnapshot = serde_json::from_str(&json).unwrap();
+
+        child::<Checkbox>(&mut app, 0).set_checked(false);
+        child::<ScrollView>(&mut app, 1).set_scroll_offset(0.0);
+        app.events.set_focus(app.root.as_mut().unwrap().as_mut(), None);
+        app.state.count = 5;
+
+        restore_snapshot(snapshot);
+        assert!(app.apply_snapshot_requests());
+        app.draw_root_at(size);
+        assert_eq!(app.state.count, 1);
+        assert_eq!(app.events.focused(), Some(&[0][..]));
+        assert!(child::<Checkbox>(&mut app, 0).is_checked());
+        assert_eq!(child::<ScrollView>(&mut app, 1).scroll_offset(), 50.0);
+    }
+
+    #[test]
+    fn dropped_files_go_to_the_widget_under_the_pointer() {
+        let (left, right) = (Block::new(50.0, 100.0), Block::new(50.0, 100.0));
+        let (left_events, right_events) = (left.events(), right.events());
+        let unclaimed = Rc::new(RefCell::new(Vec::new()));
+        let app = App::new().with_root(HStack::new().with_spacing(0.0).child(left).child(right)).on_file_drop({
+            let unclaimed = unclaimed.clone();
+            move |paths, _| unclaimed.borrow_mut().push(paths.to_vec())
+        });
+        let mut app = AppState::new(app);
+        app.draw_root_at(PhysicalSize::new(200, 100));
+
+        let paths = vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")];
+        let over_right = Point::new(75.0, 50.0);
+        app.events.set_cursor(over_right);
+        app.dropped_files = paths.clone();
+        assert!(app.apply_file_drops());
+        assert_eq!(*right_events.borrow(), [UiEvent::FileDrop { position: over_right, paths: paths.clone() }]);
+        assert!(left_events.borrow().is_empty());
+        assert!(unclaimed.borrow().is_empty());
+
+        // Past both blocks, where nothing takes them
+        app.events.set_cursor(Point::new(150.0, 50.0));
+        app.dropped_files = paths.clone();
+        assert!(app.apply_file_drops());
+        assert_eq!(*unclaimed.borrow(), [paths]);
+        assert!(!app.apply_file_drops(), "nothing dropped since");
+    }
+}
@@ -0,0 +1,85 @@
+use std::cell::RefCell;
+
+use vello::kurbo::{Affine, Point, Rect, Size};
+use vello::peniko::{Color, Fill};
+use winit::keyboard::ModifiersState;
+
+use crate::components::{DrawCtx, LayoutCtx, Widget};
+use crate::event::{EventDispatcher, GestureConfig};
+use crate::theme::Theme;
+
+// How much the tree under a modal is darkened
+const DIM: f32 = 0.5;
+
+pub(super) enum ModalRequest {
+    Push(Box<dyn Widget>),
+    Pop,
+}
+
+thread_local! {
+    // Widget callbacks have no handle on the app, so requests are queued here
+    // and picked up once the event that made them has been handled
+    static REQUESTS: RefCell<Vec<ModalRequest>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Shows `widget` centred above the rest of the UI, which is dimmed and gets
+/// no input until the modal is popped. Modals stack; input goes to the top
+/// one, and Escape pops it unless the modal consumes the key itself.
+///
+/// Can be called from any callback on the thread running the [`App`](super::App),
+/// and takes effect once the current event has been handled. Modals are only
+/// shown over a root widget, see [`App::with_root`](super::App::with_root).
+pub fn push_modal(widget: impl Widget + 'static) {
+    REQUESTS.with_borrow_mut(|requests| requests.push(ModalRequest::Push(Box::new(widget))));
+}
+
+/// Closes the top modal, if there is one. See [`push_modal`].
+pub fn pop_modal() {
+    REQUESTS.with_borrow_mut(|requests| requests.push(ModalRequest::Pop));
+}
+
+pub(super) fn take_requests() -> Vec<ModalRequest> {
+    REQUESTS.with_borrow_mut(std::mem::take)
+}
+
+/// A modal's widget and the input state that belongs to it.
+pub(super) struct Modal {
+    pub(super) widget: Box<dyn Widget>,
+    // Focus and hover paths are relative to the modal, not the root
+    pub(super) events: EventDispatcher,
+    laid_out_size: Option<Size>,
+}
+
+impl Modal {
+    pub(super) fn new(widget: Box<dyn Widget>, gestures: GestureConfig, cursor: Point, modifiers: ModifiersState) -> Self {
+        let mut events = EventDispatcher::with_gestures(gestures);
+        events.set_cursor(cursor);
+        events.set_modifiers(modifiers);
+        Self {
+            widget,
+            events,
+            laid_out_size: None,
+        }
+    }
+
+    /// Dims everything drawn so far and draws the modal centred in `window`.
+    pub(super) fn draw(&mut self, ctx: &mut DrawCtx, theme: &Theme, window: Size) {
+        if self.laid_out_size != Some(window) || self.widget.needs_layout() {
+            let layout_ctx = LayoutCtx { theme };
+            let size = self.widget.measure(&layout_ctx, window);
+            let size = Size::new(size.width.min(window.width), size.height.min(window.height));
+            let origin = Point::new((window.width - size.width) / 2.0, (window.height - size.height) / 2.0);
+            self.widget.layout(&layout_ctx, Rect::from_origin_size(origin, size));
+            self.laid_out_size = Some(window);
+        }
+
+        let backdrop = Rect::from_origin_size(Point::ZERO, window);
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, Color::BLACK.with_alpha(DIM), None, &backdrop);
+        ctx.draw_child(self.widget.as_mut());
+        if let Some(bounds) = self.events.focused_bounds(self.widget.as_ref()) {
+            ctx.draw_focus_ring(bounds);
+        }
+        // Popups of this modal go under the next one up
+        ctx.flush_overlay();
+    }
+}
@@ -0,0 +1,68 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Weak};
+
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowLevel};
+
+thread_local! {
+    // Widget callbacks have no handle on the app, so it leaves its window here.
+    // Weak, so the window still goes away when the app drops it.
+    static WINDOW: RefCell<Weak<Window>> = const { RefCell::new(Weak::new()) };
+}
+
+pub(super) fn set_window(window: &Arc<Window>) {
+    WINDOW.with_borrow_mut(|current| *current = Arc::downgrade(window));
+}
+
+fn with_window<T>(f: impl FnOnce(&Window) -> T) -> Option<T> {
+    WINDOW.with_borrow(|window| window.upgrade()).map(|window| f(&window))
+}
+
+/// Every monitor connected right now. Empty until the [`App`](super::App)
+/// has created its window, or when called off the thread running it.
+pub fn available_monitors() -> Vec<MonitorHandle> {
+    with_window(|window| window.available_monitors().collect()).unwrap_or_default()
+}
+
+/// The monitor the window is on, if the platform can tell.
+pub fn current_monitor() -> Option<MonitorHandle> {
+    with_window(Window::current_monitor).flatten()
+}
+
+/// Refresh rate of the monitor the window is on in Hz, or of the video mode
+/// in exclusive fullscreen. A sensible default for a frame rate cap. `None`
+/// where the platform doesn't report it.
+pub fn refresh_rate() -> Option<f64> {
+    let millihertz = with_window(|window| match window.fullscreen() {
+        Some(Fullscreen::Exclusive(mode)) => Some(mode.refresh_rate_millihertz()),
+        _ => window.current_monitor()?.refresh_rate_millihertz(),
+    })
+    .flatten()?;
+    Some(f64::from(millihertz) / 1000.0)
+}
+
+/// Puts the window into fullscreen, or back into a window with `None`.
+///
+/// `Fullscreen::Borderless(Some(monitor))` covers a monitor from
+/// [`available_monitors`] at its current resolution, and
+/// `Fullscreen::Exclusive(mode)` switches to one of a monitor's
+/// [`video_modes`](MonitorHandle::video_modes), where the platform allows it.
+/// The window is resized like any other time, redrawing at the new size once
+/// the resize has been handled.
+pub fn set_fullscreen(fullscreen: Option<Fullscreen>) {
+    if let Some(Fullscreen::Exclusive(mode)) = &fullscreen {
+        log::info!("Entering exclusive fullscreen at {}", mode);
+    }
+    if with_window(|window| window.set_fullscreen(fullscreen)).is_none() {
+        log::warn!("Ignoring fullscreen request made before the window was created");
+    }
+}
+
+/// Keeps the window above all others, or lets it go behind them again. See
+/// [`App::with_always_on_top`](super::App::with_always_on_top).
+pub fn set_always_on_top(always_on_top: bool) {
+    let level = if always_on_top { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal };
+    if with_window(|window| window.set_window_level(level)).is_none() {
+        log::warn!("Ignoring always-on-top request made before the window was created");
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use vello::kurbo::{Affine, BezPath, Line, Point, Rect, Size, Stroke, Vec2};
+use vello::peniko::Fill;
+
+use crate::components::DrawCtx;
+use crate::text::{self, TextStyle};
+
+// How many frames the graph spans
+const HISTORY: usize = 120;
+// One frame at 60 Hz, marked on the graph
+const BUDGET_MS: f64 = 1000.0 / 60.0;
+const GRAPH_SIZE: Size = Size::new(240.0, 80.0);
+
+/// Records how long each frame took and draws the recent history as a line
+/// graph in the top-right corner of the window.
+pub(super) struct Profiler {
+    // Oldest first, in milliseconds
+    frame_times: VecDeque<f64>,
+    last_frame: Option<Instant>,
+}
+
+impl Profiler {
+    pub(super) fn new() -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(HISTORY),
+            last_frame: None,
+        }
+    }
+
+    /// Notes that a frame is starting, timing the one before it.
+    pub(super) fn frame_started(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame.replace(now) {
+            if self.frame_times.len() == HISTORY {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(duration_ms(now - last));
+        }
+    }
+
+    /// The latest frame time and the mean over the history, in milliseconds.
+    pub(super) fn stats(&self) -> Option<(f64, f64)> {
+        let latest = *self.frame_times.back()?;
+        let average = self.frame_times.iter().sum::<f64>() / self.frame_times.len() as f64;
+        Some((latest, average))
+    }
+
+    /// Draws the graph into the overlay, in a window of `window` size.
+    pub(super) fn draw(&self, ctx: &mut DrawCtx, window: Size) {
+        let theme = ctx.theme;
+        let margin = theme.spacing;
+        let origin = Point::new(window.width - GRAPH_SIZE.width - margin, margin);
+        let rect = Rect::from_origin_size(origin, GRAPH_SIZE);
+        // Always keeps the budget line in view, with some headroom above the slowest frame
+        let max_ms = self.frame_times.iter().copied().fold(BUDGET_MS, f64::max) * 1.25;
+        let y_for = |ms: f64| rect.y1 - ms / max_ms * rect.height();
+
+        let overlay = ctx.overlay();
+        overlay.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            theme.background.with_alpha(0.85),
+            None,
+            &rect.to_rounded_rect(theme.corner_radius / 2.0),
+        );
+
+        let budget = y_for(BUDGET_MS);
+        overlay.stroke(
+            &Stroke::new(1.0),
+            Affine::IDENTITY,
+            theme.accent,
+            None,
+            &Line::new((rect.x0, budget), (rect.x1, budget)),
+        );
+
+        // Newest frame on the right edge, older ones scrolling off to the left
+        let step = rect.width() / (HISTORY - 1) as f64;
+        let start = rect.x1 - step * self.frame_times.len().saturating_sub(1) as f64;
+        let mut graph = BezPath::new();
+        for (index, &ms) in self.frame_times.iter().enumerate() {
+            let point = Point::new(start + step * index as f64, y_for(ms));
+            if index == 0 {
+                graph.move_to(point);
+            } else {
+                graph.line_to(point);
+            }
+        }
+        overlay.stroke(&Stroke::new(1.5), Affine::IDENTITY, theme.foreground, None, &graph);
+
+        if let Some(&latest) = self.frame_times.back() {
+            let style = TextStyle::new(theme.font_size * 0.75, theme.foreground);
+            let label = format!("{:.1} ms", latest);
+            text::draw_text(overlay, &label, origin + Vec2::new(4.0, 2.0), &style);
+        }
+    }
+}
+
+fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
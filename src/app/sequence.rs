@@ -0,0 +1,100 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use vello::util::RenderContext as VelloRenderContext;
+use vello::{wgpu, AaConfig, RenderParams, Renderer, RendererOptions};
+use winit::dpi::PhysicalSize;
+
+use super::{App, AppState};
+use crate::render::{self, PollsterBlockOn};
+
+// What winit opens a window at on most platforms
+const DEFAULT_SIZE: PhysicalSize<u32> = PhysicalSize::new(800, 600);
+
+impl App {
+    /// Renders `frames` frames of the root widget without opening a window and
+    /// writes them to `out_dir` as `frame_0000.png`, `frame_0001.png` and so
+    /// on, e.g. for turning into a GIF.
+    ///
+    /// Frame `n` is drawn `n / fps` seconds in on the clock behind
+    /// [`DrawCtx::time`](crate::components::DrawCtx::time), however long each
+    /// frame takes to render. Animations timed with `Instant::now()`, like a
+    /// button's press, don't follow it. Frames are the size a window would
+    /// open at: 800x600, within [`Self::with_min_size`] and
+    /// [`Self::with_max_size`].
+    pub fn render_sequence(self, frames: u32, fps: u32, out_dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        if self.logging {
+            let _ = env_logger::try_init();
+        }
+        if fps == 0 {
+            bail!("fps must be at least 1");
+        }
+        let out_dir = out_dir.as_ref();
+        std::fs::create_dir_all(out_dir).with_context(|| format!("failed to create {}", out_dir.display()))?;
+
+        let use_cpu = self.use_cpu;
+        let mut state = AppState::new(self);
+        if state.root.is_none() {
+            bail!("there's nothing to render without a root widget");
+        }
+        let mut size = DEFAULT_SIZE;
+        if let Some(min) = state.min_size {
+            size = PhysicalSize::new(size.width.max(min.width), size.height.max(min.height));
+        }
+        let size = state.clamp_to_max_size(size);
+
+        let mut vello_context = VelloRenderContext::new();
+        let dev_id = vello_context
+            .device(None)
+            .pollster_block_on()
+            .context("no GPU adapter available")?;
+        let device = &vello_context.devices[dev_id].device;
+        let queue = &vello_context.devices[dev_id].queue;
+        let mut renderer = Renderer::new(
+            device,
+            RendererOptions {
+                use_cpu,
+                ..Default::default()
+            },
+        )?;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Sequence Texture"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        for frame in 0..frames {
+            state.apply_modal_requests();
+            state.clock.set(Duration::from_secs_f64(frame as f64 / fps as f64));
+            let scene = state.draw_root_at(size).unwrap_or_default();
+            renderer.render_to_texture(
+                device,
+                queue,
+                &scene,
+                &view,
+                &RenderParams {
+                    base_color: state.theme.background,
+                    width: size.width,
+                    height: size.height,
+                    antialiasing_method: AaConfig::Area,
+                },
+            )?;
+            let image = render::read_texture(device, queue, &texture)?;
+            let path = out_dir.join(format!("frame_{:04}.png", frame));
+            image.save(&path).with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        log::info!("Wrote {} frames to {}", frames, out_dir.display());
+        Ok(())
+    }
+}
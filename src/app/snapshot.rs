@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::State;
+use crate::components::Widget;
+
+/// The app's [`State`] together with what the user has changed about the
+/// widget tree: which widget has focus, and whatever each widget keeps
+/// through [`Widget::save_state`], like scroll positions and ticked boxes.
+/// Serializes with serde, e.g. to keep undo steps or to compare in tests.
+///
+/// Widgets are known by their place in the tree, the path of child indices
+/// from the root, so a snapshot only restores onto a tree of the same shape.
+/// Widgets that have moved or gone are skipped. Modals aren't included.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UiSnapshot {
+    state: State,
+    // Keyed by path, like "0/2" for the root's first child's third child
+    widgets: BTreeMap<String, serde_json::Value>,
+    focus: Option<Vec<usize>>,
+}
+
+impl UiSnapshot {
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub(super) fn capture(state: &State, root: Option<&dyn Widget>, focus: Option<&[usize]>) -> Self {
+        let mut widgets = BTreeMap::new();
+        if let Some(root) = root {
+            save_tree(root, &mut Vec::new(), &mut widgets);
+        }
+        Self {
+            state: state.clone(),
+            widgets,
+            focus: focus.map(<[usize]>::to_vec),
+        }
+    }
+
+    pub(super) fn restore_tree(&self, root: &mut dyn Widget) {
+        for (key, state) in &self.widgets {
+            let path: Option<Vec<usize>> = key.split('/').filter(|index| !index.is_empty()).map(|index| index.parse().ok()).collect();
+            match path.and_then(|path| crate::event::widget_at_path(root, &path)) {
+                Some(widget) => widget.restore_state(state),
+                None => log::debug!("Skipping snapshot state for missing widget {:?}", key),
+            }
+        }
+    }
+
+    pub(super) fn into_parts(self) -> (State, Option<Vec<usize>>) {
+        (self.state, self.focus)
+    }
+}
+
+fn save_tree(widget: &dyn Widget, path: &mut Vec<usize>, widgets: &mut BTreeMap<String, serde_json::Value>) {
+    if let Some(state) = widget.save_state() {
+        let key = path.iter().map(usize::to_string).collect::<Vec<_>>().join("/");
+        widgets.insert(key, state);
+    }
+    for (index, child) in widget.children().iter().enumerate() {
+        path.push(index);
+        save_tree(child.as_ref(), path, widgets);
+        path.pop();
+    }
+}
+
+pub(super) enum SnapshotRequest {
+    Take(Box<dyn FnOnce(UiSnapshot)>),
+    Restore(UiSnapshot),
+}
+
+thread_local! {
+    // Takes and restores, in the order asked for. The app applies them before
+    // the messages emitted by the same event, so a snapshot taken in a click
+    // handler is of the state from before that click's messages ran
+    static REQUESTS: RefCell<Vec<SnapshotRequest>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Calls `f` with a snapshot of the UI, taken once the current event has
+/// been handled and before any messages it emitted are. Can be called from
+/// any callback on the thread running the [`App`](super::App).
+///
+/// ```ignore
+/// Button::new("Save point").on_click(|| take_snapshot(|snapshot| UNDO.with_borrow_mut(|undo| undo.push(snapshot))))
+/// ```
+pub fn take_snapshot(f: impl FnOnce(UiSnapshot) + 'static) {
+    REQUESTS.with_borrow_mut(|requests| requests.push(SnapshotRequest::Take(Box::new(f))));
+}
+
+/// Puts the app's state, focus and widget state back as they were in
+/// `snapshot`, once the current event has been handled. Widget callbacks
+/// aren't called. See [`take_snapshot`].
+pub fn restore_snapshot(snapshot: UiSnapshot) {
+    REQUESTS.with_borrow_mut(|requests| requests.push(SnapshotRequest::Restore(snapshot)));
+}
+
+pub(super) fn take_requests() -> Vec<SnapshotRequest> {
+    REQUESTS.with_borrow_mut(std::mem::take)
+}
@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use vello::kurbo::{Affine, Point, Rect, Size, Vec2};
+use vello::peniko::{Fill, Mix};
+
+use crate::animation::{self, Animation, Easing};
+use crate::components::DrawCtx;
+use crate::text::{self, TextStyle};
+
+// How long a toast takes to fade in, and again to fade out
+const FADE: Duration = Duration::from_millis(200);
+
+thread_local! {
+    // Text and how long to stay up. Each one's timer starts when the app
+    // takes it from here, not when show_toast is called
+    static REQUESTS: RefCell<Vec<(String, Duration)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Shows `text` in a small box in the bottom right corner of the window for
+/// `duration`, fading in and out either side. Toasts shown while others are
+/// up stack above them. They never take input, so whatever is under one
+/// still gets clicked.
+///
+/// Can be called from any callback on the thread running the
+/// [`App`](super::App), and appears once the current event has been handled.
+/// Toasts are only shown over a root widget, see
+/// [`App::with_root`](super::App::with_root).
+pub fn show_toast(text: impl Into<String>, duration: Duration) {
+    REQUESTS.with_borrow_mut(|requests| requests.push((text.into(), duration)));
+}
+
+struct Toast {
+    text: String,
+    // When the fade out starts
+    hide_at: Instant,
+    fade: Animation,
+}
+
+/// The toasts on screen, oldest first.
+pub(super) struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub(super) fn new() -> Self {
+        Self { toasts: Vec::new() }
+    }
+
+    /// Adds the toasts shown since the last call. Returns whether there were any.
+    pub(super) fn apply_requests(&mut self) -> bool {
+        let requests = REQUESTS.with_borrow_mut(std::mem::take);
+        let now = Instant::now();
+        let changed = !requests.is_empty();
+        for (text, duration) in requests {
+            self.toasts.push(Toast {
+                text,
+                hide_at: now + FADE + duration,
+                fade: Animation::new(0.0, 1.0, FADE, Easing::EaseInOut),
+            });
+        }
+        changed
+    }
+
+    /// Whether a toast is fading in or out, which needs every frame.
+    pub(super) fn is_animating(&self) -> bool {
+        let (now, time) = (Instant::now(), animation::now());
+        self.toasts.iter().any(|toast| !toast.fade.is_finished(time) || toast.hide_at <= now)
+    }
+
+    /// When a frame is needed to start fading a toast out.
+    pub(super) fn wake_at(&self) -> Option<Instant> {
+        self.toasts.iter().map(|toast| toast.hide_at).min()
+    }
+
+    /// Draws the toasts into the overlay, stacked up from the bottom right
+    /// corner of a window of `window` size, and drops those that have faded out.
+    pub(super) fn draw(&mut self, ctx: &mut DrawCtx, window: Size) {
+        // How long toasts stay up is real time, but they fade by the app's clock
+        let (now, time) = (Instant::now(), animation::now());
+        for toast in &mut self.toasts {
+            if toast.hide_at <= now && toast.fade.target() > 0.0 {
+                let from = toast.fade.sample(time);
+                toast.fade = Animation::new(from, 0.0, FADE, Easing::EaseInOut);
+            }
+        }
+        self.toasts
+            .retain(|toast| !(toast.fade.target() == 0.0 && toast.fade.is_finished(time)));
+
+        let theme = ctx.theme;
+        let style = TextStyle::new(theme.font_size * 0.875, theme.background);
+        let padding = theme.spacing;
+        let overlay = ctx.overlay();
+        let mut bottom = window.height - theme.spacing;
+        // Newest at the bottom, nearest the corner
+        for toast in self.toasts.iter().rev() {
+            let label = text::text_size(&toast.text, &style);
+            let size = Size::new(label.width + padding * 2.0, label.height + padding);
+            let origin = Point::new((window.width - theme.spacing - size.width).max(0.0), bottom - size.height);
+            let rect = Rect::from_origin_size(origin, size);
+            let opacity = toast.fade.sample(time) as f32;
+
+            overlay.push_layer(Mix::Normal, opacity, Affine::IDENTITY, &rect);
+            overlay.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                theme.foreground,
+                None,
+                &rect.to_rounded_rect(theme.corner_radius),
+            );
+            text::draw_text(overlay, &toast.text, origin + Vec2::new(padding, padding / 2.0), &style);
+            overlay.pop_layer();
+            bottom = rect.y0 - theme.spacing / 2.0;
+        }
+    }
+}
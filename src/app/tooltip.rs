@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use vello::kurbo::{Affine, Point, Rect, Size, Vec2};
+use vello::peniko::Fill;
+
+use crate::components::DrawCtx;
+use crate::text::{self, TextStyle};
+
+// Where the box sits relative to the cursor, clear of the pointer itself
+const CURSOR_OFFSET: Vec2 = Vec2::new(12.0, 18.0);
+
+/// Shows the hovered widget's [`Widget::tooltip`](crate::components::Widget::tooltip)
+/// once the pointer has rested on it for `delay`.
+pub(super) struct Tooltips {
+    delay: Duration,
+    // Tooltip of whatever the pointer last stopped over
+    text: Option<String>,
+    // When and where the pointer last moved
+    since: Instant,
+    anchor: Point,
+    shown: bool,
+}
+
+impl Tooltips {
+    pub(super) fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            text: None,
+            since: Instant::now(),
+            anchor: Point::ZERO,
+            shown: false,
+        }
+    }
+
+    /// Hides any tooltip and restarts the delay for `text`, the tooltip under
+    /// the pointer's new position.
+    pub(super) fn pointer_moved(&mut self, text: Option<&str>, cursor: Point) {
+        self.text = text.map(str::to_string);
+        self.since = Instant::now();
+        self.anchor = cursor;
+        self.shown = false;
+    }
+
+    /// Returns whether a tooltip was on screen.
+    pub(super) fn hide(&mut self) -> bool {
+        self.text = None;
+        std::mem::take(&mut self.shown)
+    }
+
+    /// When a frame is needed to show a pending tooltip.
+    pub(super) fn wake_at(&self) -> Option<Instant> {
+        (self.text.is_some() && !self.shown).then(|| self.since + self.delay)
+    }
+
+    /// Draws the tooltip into the overlay if the delay is up, kept inside a
+    /// window of `window` size.
+    pub(super) fn draw(&mut self, ctx: &mut DrawCtx, window: Size) {
+        let Some(text) = &self.text else {
+            return;
+        };
+        if self.since.elapsed() < self.delay {
+            return;
+        }
+        self.shown = true;
+
+        let theme = ctx.theme;
+        // Inverted colours stand out from whatever is underneath
+        let style = TextStyle::new(theme.font_size * 0.875, theme.background);
+        let label = text::text_size(text, &style);
+        let padding = theme.spacing / 2.0;
+        let size = Size::new(label.width + padding * 2.0, label.height + padding * 2.0);
+
+        let mut origin = self.anchor + CURSOR_OFFSET;
+        if origin.y + size.height > window.height {
+            // No room below, so go above the cursor instead
+            origin.y = self.anchor.y - CURSOR_OFFSET.y - size.height;
+        }
+        origin.x = origin.x.min(window.width - size.width).max(0.0);
+        origin.y = origin.y.min(window.height - size.height).max(0.0);
+
+        let rect = Rect::from_origin_size(origin, size);
+        let overlay = ctx.overlay();
+        overlay.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            theme.foreground,
+            None,
+            &rect.to_rounded_rect(theme.corner_radius / 2.0),
+        );
+        text::draw_text(overlay, text, origin + Vec2::new(padding, padding), &style);
+    }
+}
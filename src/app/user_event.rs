@@ -0,0 +1,38 @@
+use std::sync::Mutex;
+
+use winit::event_loop::EventLoopProxy;
+
+/// What other threads ask of the event loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UserEvent {
+    /// Something changed that needs drawing, e.g. an image finished loading.
+    Redraw,
+    RequestAttention,
+}
+
+// Kept globally since work can start on other threads before the event loop
+// exists, and they have no handle on the app
+static PROXY: Mutex<Option<EventLoopProxy<UserEvent>>> = Mutex::new(None);
+
+pub(super) fn set_proxy(proxy: EventLoopProxy<UserEvent>) {
+    *PROXY.lock().unwrap() = Some(proxy);
+}
+
+/// Sends `event` to the event loop, from any thread. Returns whether it was
+/// sent, which it isn't before the loop has started or once it has stopped.
+pub(crate) fn send_user_event(event: UserEvent) -> bool {
+    PROXY
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|proxy| proxy.send_event(event).is_ok())
+}
+
+/// Flashes the window's taskbar entry or bounces its dock icon, to say a
+/// background task is done. Does nothing while the window has focus, and
+/// stops once it's focused. Can be called from any thread.
+pub fn request_attention() {
+    if !send_user_event(UserEvent::RequestAttention) {
+        log::warn!("Ignoring attention request made while the app isn't running");
+    }
+}
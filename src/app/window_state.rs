@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event_loop::ActiveEventLoop;
+use winit::window::Window;
+
+// How much of the window has to overlap a monitor, each way, for a saved
+// position to be used: enough to grab it by the title bar
+const MIN_VISIBLE: i64 = 64;
+
+/// Where the window was and how big, in physical pixels, as saved by
+/// [`App::with_state_file`](super::App::with_state_file).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(super) struct WindowGeometry {
+    // Outer position, missing on platforms that don't report one, like Wayland
+    position: Option<[i32; 2]>,
+    // Inner size
+    size: [u32; 2],
+}
+
+impl WindowGeometry {
+    pub(super) fn of(window: &Window) -> Self {
+        let size = window.inner_size();
+        Self {
+            position: window.outer_position().ok().map(|position| [position.x, position.y]),
+            size: [size.width, size.height],
+        }
+    }
+
+    /// `None` if there's no file yet or it can't be read, which is logged.
+    pub(super) fn load(path: &Path) -> Option<Self> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                log::warn!("Failed to read window state {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match serde_json::from_str(&json) {
+            Ok(geometry) => Some(geometry),
+            Err(e) => {
+                log::warn!("Ignoring malformed window state {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub(super) fn save(&self, path: &Path) {
+        let result = serde_json::to_string(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(path, json)?));
+        if let Err(e) = result {
+            log::warn!("Failed to save window state {}: {}", path.display(), e);
+        }
+    }
+
+    pub(super) fn size(&self) -> Option<PhysicalSize<u32>> {
+        let [width, height] = self.size;
+        (width > 0 && height > 0).then(|| PhysicalSize::new(width, height))
+    }
+
+    /// The saved position, unless it would leave the window mostly off every
+    /// monitor, e.g. because the one it was on has been unplugged.
+    pub(super) fn position(&self, event_loop: &ActiveEventLoop) -> Option<PhysicalPosition<i32>> {
+        let [x, y] = self.position?;
+        let [width, height] = self.size.map(i64::from);
+        let (x, y) = (i64::from(x), i64::from(y));
+        let visible = event_loop.available_monitors().any(|monitor| {
+            let origin = monitor.position();
+            let size = monitor.size();
+            let (left, top) = (i64::from(origin.x), i64::from(origin.y));
+            let (right, bottom) = (left + i64::from(size.width), top + i64::from(size.height));
+            let overlap_x = (x + width).min(right) - x.max(left);
+            let overlap_y = (y + height).min(bottom) - y.max(top);
+            // The top edge has to be on the monitor too, or the title bar is out of reach
+            overlap_x >= MIN_VISIBLE.min(width) && overlap_y >= MIN_VISIBLE.min(height) && (top..bottom).contains(&y)
+        });
+        if !visible {
+            log::info!("Saved window position ({}, {}) is off screen; letting the platform place it", x, y);
+        }
+        visible.then(|| PhysicalPosition::new(x as i32, y as i32))
+    }
+}
@@ -0,0 +1,32 @@
+use vello::kurbo::Point;
+use vello::peniko::{Brush, Color, ColorStopsSource, Gradient};
+
+/// The stops behind the demo triangle: red through violet and back to red,
+/// so it wraps seamlessly around a sweep gradient.
+pub const RAINBOW: [(f32, Color); 8] = [
+    (0.0, Color::from_rgb8(255, 0, 0)),
+    (0.14, Color::from_rgb8(255, 165, 0)),
+    (0.28, Color::from_rgb8(255, 255, 0)),
+    (0.42, Color::from_rgb8(0, 128, 0)),
+    (0.57, Color::from_rgb8(0, 0, 255)),
+    (0.71, Color::from_rgb8(75, 0, 130)),
+    (0.85, Color::from_rgb8(238, 130, 238)),
+    (1.0, Color::from_rgb8(255, 0, 0)),
+];
+
+pub fn solid(color: Color) -> Brush {
+    Brush::Solid(color)
+}
+
+pub fn linear_gradient(start: Point, end: Point, stops: impl ColorStopsSource) -> Gradient {
+    Gradient::new_linear(start, end).with_stops(stops)
+}
+
+pub fn radial_gradient(center: Point, radius: f32, stops: impl ColorStopsSource) -> Gradient {
+    Gradient::new_radial(center, radius).with_stops(stops)
+}
+
+/// Angles are in radians, clockwise from the positive x axis.
+pub fn sweep_gradient(center: Point, start_angle: f32, end_angle: f32, stops: impl ColorStopsSource) -> Gradient {
+    Gradient::new_sweep(center, start_angle, end_angle).with_stops(stops)
+}
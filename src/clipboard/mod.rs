@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+
+use arboard::Clipboard;
+
+thread_local! {
+    // On X11 and Wayland copied text only stays available while a clipboard
+    // handle is alive, so one is kept around rather than opened per call
+    static CLIPBOARD: RefCell<Option<Clipboard>> = const { RefCell::new(None) };
+}
+
+// Runs `f` with the clipboard, opening it on first use. Errors are logged; a
+// missing clipboard shouldn't take the app down with it.
+fn with_clipboard<T>(f: impl FnOnce(&mut Clipboard) -> Result<T, arboard::Error>) -> Option<T> {
+    CLIPBOARD.with_borrow_mut(|clipboard| {
+        if clipboard.is_none() {
+            match Clipboard::new() {
+                Ok(opened) => *clipboard = Some(opened),
+                Err(e) => {
+                    log::warn!("Failed to open the clipboard: {}", e);
+                    return None;
+                }
+            }
+        }
+        match f(clipboard.as_mut()?) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                log::warn!("Clipboard error: {}", e);
+                None
+            }
+        }
+    })
+}
+
+/// The text on the system clipboard, or `None` if it's empty, holds something
+/// other than text, or can't be read.
+pub fn get_text() -> Option<String> {
+    with_clipboard(|clipboard| match clipboard.get_text() {
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        result => result.map(Some),
+    })
+    .flatten()
+}
+
+/// Puts `text` on the system clipboard. Failures are logged and otherwise ignored.
+pub fn set_text(text: &str) {
+    with_clipboard(|clipboard| clipboard.set_text(text));
+}
+
+/// Prepares clipboard text for pasting into a text field. A single-line field
+/// gets each line break turned into a space, with trailing ones dropped; a
+/// multi-line one keeps them, normalised to `\n`.
+pub fn paste_text(text: &str, single_line: bool) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    if single_line {
+        text.trim_end_matches('\n').replace('\n', " ")
+    } else {
+        text
+    }
+}
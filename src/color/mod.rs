@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use vello::peniko::Color;
+
+/// A colour as hue, saturation and value, which is how colour pickers let
+/// people choose one.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hsv {
+    /// In degrees, from 0 (red) up to but not including 360.
+    pub hue: f32,
+    /// From 0 (grey) to 1 (fully saturated).
+    pub saturation: f32,
+    /// From 0 (black) to 1 (full brightness).
+    pub value: f32,
+    pub alpha: f32,
+}
+
+impl Hsv {
+    /// Opaque. Hues wrap around and the other components are clamped to `[0, 1]`.
+    pub fn new(hue: f32, saturation: f32, value: f32) -> Self {
+        Self {
+            hue,
+            saturation,
+            value,
+            alpha: 1.0,
+        }
+        .clamped()
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The hue wrapped into `[0, 360)` and everything else into `[0, 1]`.
+    pub fn clamped(self) -> Self {
+        let hue = self.hue.rem_euclid(360.0);
+        Self {
+            // rem_euclid can round up to exactly 360 for tiny negative hues
+            hue: if hue >= 360.0 { 0.0 } else { hue },
+            saturation: self.saturation.clamp(0.0, 1.0),
+            value: self.value.clamp(0.0, 1.0),
+            alpha: self.alpha.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn to_color(self) -> Color {
+        let [r, g, b] = hsv_to_rgb(self.hue, self.saturation, self.value);
+        Color::new([r, g, b, self.alpha])
+    }
+
+    /// Greys and black have no hue of their own, so they come back with a hue of 0.
+    pub fn from_color(color: Color) -> Self {
+        let [r, g, b, alpha] = color.components;
+        let [hue, saturation, value] = rgb_to_hsv(r, g, b);
+        Self {
+            hue,
+            saturation,
+            value,
+            alpha,
+        }
+        .clamped()
+    }
+}
+
+impl From<Color> for Hsv {
+    fn from(color: Color) -> Self {
+        Self::from_color(color)
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        hsv.to_color()
+    }
+}
+
+/// Converts a hue in degrees and saturation and value in `[0, 1]` to sRGB
+/// components in `[0, 1]`. Out-of-range input is wrapped or clamped first.
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let saturation = saturation.clamp(0.0, 1.0);
+    let value = value.clamp(0.0, 1.0);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let [r, g, b] = match hue as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    let min = value - chroma;
+    [r + min, g + min, b + min]
+}
+
+/// Converts sRGB components in `[0, 1]` to a hue in degrees and saturation
+/// and value in `[0, 1]`.
+pub fn rgb_to_hsv(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let [r, g, b] = [r, g, b].map(|c| c.clamp(0.0, 1.0));
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+    [hue, saturation, max]
+}
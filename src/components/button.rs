@@ -0,0 +1,295 @@
+use std::time::Duration;
+
+use vello::kurbo::{Affine, Point, Rect, Size};
+use vello::peniko::{BlendMode, Color, Compose, Fill, Mix};
+use winit::event::MouseButton;
+use winit::keyboard::{Key, NamedKey};
+use winit::window::CursorIcon;
+
+use super::{ContextMenu, DrawCtx, LayoutCtx, Widget};
+use crate::animation::{self, Animation, Easing};
+use crate::event::UiEvent;
+use crate::render::{self, NinePatch, Shadow};
+use crate::text::{self, FontWeight, TextStyle};
+use crate::theme::Theme;
+#[cfg(feature = "accesskit")]
+use accesskit::{Action, Node, Role};
+
+pub struct Button {
+    text: String,
+    on_click: Option<Box<dyn FnMut()>>,
+    background: Option<Color>,
+    background_image: Option<NinePatch>,
+    text_color: Option<Color>,
+    font: Option<String>,
+    weight: FontWeight,
+    italic: bool,
+    pressed: bool,
+    focused: bool,
+    hovered: bool,
+    // 0 when released, 1 when fully pressed
+    press: Animation,
+    z_index: i32,
+    shadow: Option<Shadow>,
+    tooltip: Option<String>,
+    context_menu: Option<ContextMenu>,
+    // Measured for the current text
+    label: Option<Size>,
+    bounds: Rect,
+}
+
+const PRESS_DURATION: Duration = Duration::from_millis(100);
+
+impl Button {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            on_click: None,
+            background: None,
+            background_image: None,
+            text_color: None,
+            font: None,
+            weight: FontWeight::NORMAL,
+            italic: false,
+            pressed: false,
+            focused: false,
+            hovered: false,
+            press: Animation::new(0.0, 0.0, Duration::ZERO, Easing::Linear),
+            z_index: 0,
+            shadow: None,
+            tooltip: None,
+            context_menu: None,
+            label: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.label = None;
+    }
+
+    pub fn on_click(mut self, f: impl FnMut() + 'static) -> Self {
+        self.on_click = Some(Box::new(f));
+        self
+    }
+
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Draws `image` stretched over the button instead of the rounded
+    /// background colour.
+    pub fn with_background_image(mut self, image: NinePatch) -> Self {
+        self.background_image = Some(image);
+        self
+    }
+
+    pub fn with_text_color(mut self, color: Color) -> Self {
+        self.text_color = Some(color);
+        self
+    }
+
+    /// Sets the label in a registered or system font, by name.
+    pub fn with_font(mut self, name: &str) -> Self {
+        self.font = Some(name.to_string());
+        self
+    }
+
+    pub fn with_weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_tooltip(mut self, text: &str) -> Self {
+        self.tooltip = Some(text.to_string());
+        self
+    }
+
+    /// Shown when the button is right-clicked.
+    pub fn with_context_menu(mut self, menu: ContextMenu) -> Self {
+        self.context_menu = Some(menu);
+        self
+    }
+
+    /// Casts `shadow` under the button.
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    fn text_style(&self, theme: &Theme) -> TextStyle {
+        TextStyle::new(theme.font_size, self.text_color.unwrap_or(Color::WHITE))
+            .with_weight(self.weight)
+            .with_italic(self.italic)
+            .with_font(self.font.as_deref())
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+        // Start from wherever the previous animation got to so quick taps don't jump
+        let current = self.press.sample(animation::now());
+        let target = if pressed { 1.0 } else { 0.0 };
+        self.press = Animation::new(current, target, PRESS_DURATION, Easing::EaseInOut);
+    }
+}
+
+impl Widget for Button {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        let label = text::measure_text(&self.text, &self.text_style(ctx.theme), None).size;
+        let padding = ctx.theme.spacing;
+        Size::new(label.width + padding * 4.0, label.height + padding * 2.0)
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.label = Some(text::measure_text(&self.text, &self.text_style(ctx.theme), None).size);
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let press = self.press.sample(animation::now()) as f32;
+        // Lighter under the pointer, darker while pressed
+        let hover = if self.hovered { 0.08 } else { 0.0 };
+        let background = self
+            .background
+            .unwrap_or(theme.accent)
+            .map_lightness(|l| (l + hover).min(1.0) * (1.0 - 0.2 * press));
+        if let Some(shadow) = &self.shadow {
+            render::draw_shadow(ctx.scene, self.bounds, theme.corner_radius, shadow);
+        }
+        let shape = self.bounds.to_rounded_rect(theme.corner_radius);
+        match &self.background_image {
+            Some(image) => {
+                // The same hover and press feedback, as a tint only where the image is opaque
+                let tint = if press > 0.0 {
+                    Color::BLACK.with_alpha(0.2 * press)
+                } else {
+                    Color::WHITE.with_alpha(hover)
+                };
+                ctx.scene.push_layer(Mix::Normal, 1.0, Affine::IDENTITY, &self.bounds);
+                image.draw(ctx.scene, self.bounds);
+                ctx.scene.push_layer(BlendMode::new(Mix::Normal, Compose::SrcAtop), 1.0, Affine::IDENTITY, &self.bounds);
+                ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, tint, None, &self.bounds);
+                ctx.scene.pop_layer();
+                ctx.scene.pop_layer();
+            }
+            None => ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, background, None, &shape),
+        }
+
+        // Center the label within the button
+        let style = self.text_style(theme);
+        // Not measured since the text changed
+        let label = self.label.unwrap_or_else(|| text::measure_text(&self.text, &style, None).size);
+        let origin = Point::new(
+            self.bounds.center().x - label.width / 2.0,
+            self.bounds.center().y - label.height / 2.0,
+        );
+        text::draw_text(ctx.scene, &self.text, origin, &style);
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Pointer)
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.label.is_none()
+    }
+
+    fn tooltip(&self) -> Option<&str> {
+        self.tooltip.as_deref()
+    }
+
+    fn context_menu(&mut self) -> Option<&mut ContextMenu> {
+        self.context_menu.as_mut()
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            UiEvent::PointerDown { button: MouseButton::Left, .. } => {
+                self.set_pressed(true);
+                true
+            }
+            UiEvent::PointerUp { position, button: MouseButton::Left } if self.pressed => {
+                self.set_pressed(false);
+                // Only a release over the button counts as a click
+                if self.bounds.contains(*position)
+                    && let Some(on_click) = &mut self.on_click
+                {
+                    on_click();
+                }
+                true
+            }
+            // Consumed so the app redraws with the new background
+            UiEvent::PointerEnter => {
+                self.hovered = true;
+                true
+            }
+            UiEvent::PointerLeave => {
+                self.hovered = false;
+                true
+            }
+            UiEvent::FocusGained => {
+                self.focused = true;
+                false
+            }
+            UiEvent::FocusLost => {
+                self.focused = false;
+                false
+            }
+            // Unfocused buttons also see keys when nothing has focus, which mustn't click them
+            UiEvent::KeyDown { key: Key::Named(NamedKey::Enter | NamedKey::Space), repeat: false, .. }
+                if self.focused =>
+            {
+                if let Some(on_click) = &mut self.on_click {
+                    on_click();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::Button);
+        node.set_label(self.text.as_str());
+        node.add_action(Action::Click);
+        if let Some(tooltip) = &self.tooltip {
+            node.set_description(tooltip.as_str());
+        }
+        Some(node)
+    }
+
+    fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    fn is_animating(&self) -> bool {
+        !self.press.is_finished(animation::now())
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use vello::Scene;
+use vello::kurbo::{Rect, Size};
+
+use super::{DrawCtx, LayoutCtx, Widget};
+
+type DrawFn = Box<dyn FnMut(&mut Scene, Rect, Duration)>;
+
+/// A rectangle you paint yourself.
+///
+/// The callback runs every frame with the canvas's bounds and
+/// [`DrawCtx::time`], and anything it draws outside the bounds is clipped.
+pub struct Canvas {
+    draw: DrawFn,
+    size: Size,
+    animated: bool,
+    bounds: Rect,
+}
+
+impl Canvas {
+    pub fn new(draw: impl FnMut(&mut Scene, Rect, Duration) + 'static) -> Self {
+        Self {
+            draw: Box::new(draw),
+            size: Size::ZERO,
+            animated: false,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// Size asked for when measured. Containers that stretch their children
+    /// may still hand it more.
+    pub fn with_size(mut self, width: f64, height: f64) -> Self {
+        self.size = Size::new(width, height);
+        self
+    }
+
+    /// Keeps the app redrawing every frame, for drawings that move with time.
+    pub fn with_animated(mut self, animated: bool) -> Self {
+        self.animated = animated;
+        self
+    }
+}
+
+impl Widget for Canvas {
+    fn measure(&mut self, _ctx: &LayoutCtx, _available: Size) -> Size {
+        self.size
+    }
+
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let bounds = self.bounds;
+        let time = ctx.time;
+        let draw = &mut self.draw;
+        ctx.with_clip(&bounds, |ctx| draw(ctx.scene, bounds, time));
+    }
+
+    fn is_animating(&self) -> bool {
+        self.animated
+    }
+}
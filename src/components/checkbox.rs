@@ -0,0 +1,201 @@
+use vello::kurbo::{Affine, BezPath, Point, Rect, Size, Stroke};
+use vello::peniko::{Color, Fill};
+use winit::event::MouseButton;
+use winit::keyboard::{Key, NamedKey};
+use winit::window::CursorIcon;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+use crate::text::{self, TextStyle};
+#[cfg(feature = "accesskit")]
+use accesskit::{Action, Node, Role};
+
+pub struct Checkbox {
+    label: String,
+    checked: bool,
+    on_toggle: Option<Box<dyn FnMut(bool)>>,
+    accent: Option<Color>,
+    focused: bool,
+    tooltip: Option<String>,
+    // Measured for the current label
+    label_size: Option<Size>,
+    bounds: Rect,
+}
+
+impl Checkbox {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            checked: false,
+            on_toggle: None,
+            accent: None,
+            focused: false,
+            tooltip: None,
+            label_size: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+
+    pub fn with_tooltip(mut self, text: &str) -> Self {
+        self.tooltip = Some(text.to_string());
+        self
+    }
+
+    pub fn with_accent(mut self, color: Color) -> Self {
+        self.accent = Some(color);
+        self
+    }
+
+    pub fn on_toggle(mut self, f: impl FnMut(bool) + 'static) -> Self {
+        self.on_toggle = Some(Box::new(f));
+        self
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Changes the state without calling the toggle callback.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_string();
+        self.label_size = None;
+    }
+
+    // The box is a square as tall as one line of text
+    fn box_size(font_size: f32) -> f64 {
+        font_size as f64 * 1.25
+    }
+}
+
+impl Widget for Checkbox {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        let box_size = Self::box_size(ctx.theme.font_size);
+        let style = TextStyle::new(ctx.theme.font_size, ctx.theme.foreground);
+        let label = text::text_size(&self.label, &style);
+        Size::new(box_size + ctx.theme.spacing + label.width, box_size.max(label.height))
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        let style = TextStyle::new(ctx.theme.font_size, ctx.theme.foreground);
+        self.label_size = Some(text::text_size(&self.label, &style));
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let box_size = Self::box_size(theme.font_size);
+        let top = self.bounds.center().y - box_size / 2.0;
+        let check_box = Rect::new(self.bounds.x0, top, self.bounds.x0 + box_size, top + box_size)
+            .to_rounded_rect(theme.corner_radius / 2.0);
+        let accent = self.accent.unwrap_or(theme.accent);
+
+        if self.checked {
+            ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, accent, None, &check_box);
+            let rect = check_box.rect();
+            let mut tick = BezPath::new();
+            tick.move_to(Point::new(rect.x0 + box_size * 0.25, rect.y0 + box_size * 0.5));
+            tick.line_to(Point::new(rect.x0 + box_size * 0.45, rect.y0 + box_size * 0.7));
+            tick.line_to(Point::new(rect.x0 + box_size * 0.75, rect.y0 + box_size * 0.3));
+            ctx.scene.stroke(&Stroke::new(2.0), Affine::IDENTITY, Color::WHITE, None, &tick);
+        } else {
+            ctx.scene.stroke(&Stroke::new(1.5), Affine::IDENTITY, theme.foreground, None, &check_box);
+        }
+
+        let style = TextStyle::new(theme.font_size, theme.foreground);
+        // Not measured since the label changed
+        let label = self.label_size.unwrap_or_else(|| text::text_size(&self.label, &style));
+        let origin = Point::new(
+            self.bounds.x0 + box_size + theme.spacing,
+            self.bounds.center().y - label.height / 2.0,
+        );
+        text::draw_text(ctx.scene, &self.label, origin, &style);
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.label_size.is_none()
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Pointer)
+    }
+
+    fn tooltip(&self) -> Option<&str> {
+        self.tooltip.as_deref()
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            UiEvent::PointerDown { button: MouseButton::Left, .. } => true,
+            UiEvent::PointerUp { position, button: MouseButton::Left } if self.bounds.contains(*position) => {
+                self.toggle();
+                true
+            }
+            UiEvent::FocusGained => {
+                self.focused = true;
+                false
+            }
+            UiEvent::FocusLost => {
+                self.focused = false;
+                false
+            }
+            UiEvent::KeyDown { key: Key::Named(NamedKey::Space), repeat: false, .. } if self.focused => {
+                self.toggle();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::CheckBox);
+        node.set_label(self.label.as_str());
+        node.set_toggled(self.checked.into());
+        node.add_action(Action::Click);
+        if let Some(tooltip) = &self.tooltip {
+            node.set_description(tooltip.as_str());
+        }
+        Some(node)
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.checked.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        if let Some(checked) = state.as_bool() {
+            self.checked = checked;
+        }
+    }
+}
+
+impl Checkbox {
+    fn toggle(&mut self) {
+        self.checked = !self.checked;
+        if let Some(on_toggle) = &mut self.on_toggle {
+            on_toggle(self.checked);
+        }
+    }
+}
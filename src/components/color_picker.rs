@@ -0,0 +1,240 @@
+use vello::kurbo::{Affine, Circle, Point, Rect, Size, Stroke};
+use vello::peniko::{Color, Fill};
+use winit::event::MouseButton;
+use winit::window::CursorIcon;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::brush;
+use crate::color::Hsv;
+use crate::event::UiEvent;
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+// Which part of the picker a drag started on, and so which components it changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    SaturationValue,
+    Hue,
+}
+
+/// Picks a colour with a saturation/value square and a hue bar, next to a
+/// swatch of the current colour. Clicking or dragging in either changes it.
+pub struct ColorPicker {
+    color: Hsv,
+    on_change: Option<Box<dyn FnMut(Color)>>,
+    dragging: Option<Part>,
+    // From the last layout
+    square: Rect,
+    hue_bar: Rect,
+    swatch: Rect,
+    bounds: Rect,
+}
+
+impl ColorPicker {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color: Hsv::from_color(color),
+            on_change: None,
+            dragging: None,
+            square: Rect::ZERO,
+            hue_bar: Rect::ZERO,
+            swatch: Rect::ZERO,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// Called with the new colour whenever the user changes it.
+    pub fn on_change(mut self, f: impl FnMut(Color) + 'static) -> Self {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+
+    pub fn color(&self) -> Color {
+        self.color.to_color()
+    }
+
+    pub fn hsv(&self) -> Hsv {
+        self.color
+    }
+
+    /// Changes the colour without calling `on_change`.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Hsv::from_color(color);
+    }
+
+    // The square's side; the bar and swatch are sized off the same font size
+    fn square_size(font_size: f32) -> f64 {
+        font_size as f64 * 10.0
+    }
+
+    fn bar_width(font_size: f32) -> f64 {
+        font_size as f64 * 1.25
+    }
+
+    fn part_at(&self, position: Point) -> Option<Part> {
+        if self.square.contains(position) {
+            Some(Part::SaturationValue)
+        } else if self.hue_bar.contains(position) {
+            Some(Part::Hue)
+        } else {
+            None
+        }
+    }
+
+    // Positions past the edges pin to them, so a drag can overshoot
+    fn pick(&mut self, part: Part, position: Point) {
+        let fraction = |value: f64, start: f64, length: f64| {
+            if length > 0.0 { ((value - start) / length).clamp(0.0, 1.0) as f32 } else { 0.0 }
+        };
+        let mut color = self.color;
+        match part {
+            Part::SaturationValue => {
+                color.saturation = fraction(position.x, self.square.x0, self.square.width());
+                color.value = 1.0 - fraction(position.y, self.square.y0, self.square.height());
+            }
+            Part::Hue => {
+                // The bottom edge stays just short of 360 rather than wrapping to red at the top
+                color.hue = (fraction(position.y, self.hue_bar.y0, self.hue_bar.height()) * 360.0).min(359.9);
+            }
+        }
+        if color == self.color {
+            return;
+        }
+        self.color = color;
+        if let Some(on_change) = &mut self.on_change {
+            on_change(color.to_color());
+        }
+    }
+}
+
+impl Widget for ColorPicker {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        let square = Self::square_size(ctx.theme.font_size);
+        let bar = Self::bar_width(ctx.theme.font_size);
+        // Square, hue bar, then a swatch twice as wide as the bar
+        Size::new(square + ctx.theme.spacing * 2.0 + bar * 3.0, square)
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        let spacing = ctx.theme.spacing;
+        let bar = Self::bar_width(ctx.theme.font_size);
+        let side = Self::square_size(ctx.theme.font_size).min(bounds.height());
+        self.square = Rect::new(bounds.x0, bounds.y0, bounds.x0 + side, bounds.y0 + side);
+        let bar_left = self.square.x1 + spacing;
+        self.hue_bar = Rect::new(bar_left, bounds.y0, bar_left + bar, bounds.y0 + side);
+        let swatch_left = self.hue_bar.x1 + spacing;
+        self.swatch = Rect::new(swatch_left, bounds.y0, swatch_left + bar * 2.0, bounds.y0 + bar * 2.0);
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let radius = theme.corner_radius / 2.0;
+        let outline = theme.foreground.with_alpha(0.3);
+
+        // Pure hue, whitened towards the left and darkened towards the bottom
+        let square = self.square.to_rounded_rect(radius);
+        let hue = Hsv::new(self.color.hue, 1.0, 1.0).to_color();
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, hue, None, &square);
+        let whiten = brush::linear_gradient(
+            Point::new(self.square.x0, self.square.y0),
+            Point::new(self.square.x1, self.square.y0),
+            [Color::WHITE, Color::WHITE.with_alpha(0.0)].as_slice(),
+        );
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, &whiten, None, &square);
+        let darken = brush::linear_gradient(
+            Point::new(self.square.x0, self.square.y0),
+            Point::new(self.square.x0, self.square.y1),
+            [Color::BLACK.with_alpha(0.0), Color::BLACK].as_slice(),
+        );
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, &darken, None, &square);
+        ctx.scene.stroke(&Stroke::new(1.0), Affine::IDENTITY, outline, None, &square);
+
+        // Every sixth of the way round the wheel, top to bottom
+        let hue_stops: [(f32, Color); 7] =
+            std::array::from_fn(|i| (i as f32 / 6.0, Hsv::new(i as f32 * 60.0, 1.0, 1.0).to_color()));
+        let hues = brush::linear_gradient(
+            Point::new(self.hue_bar.x0, self.hue_bar.y0),
+            Point::new(self.hue_bar.x0, self.hue_bar.y1),
+            hue_stops,
+        );
+        let hue_bar = self.hue_bar.to_rounded_rect(radius);
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, &hues, None, &hue_bar);
+        ctx.scene.stroke(&Stroke::new(1.0), Affine::IDENTITY, outline, None, &hue_bar);
+
+        // Markers are ringed in black and white so they show on any colour
+        let marker = Point::new(
+            self.square.x0 + self.square.width() * self.color.saturation as f64,
+            self.square.y1 - self.square.height() * self.color.value as f64,
+        );
+        let ring = Circle::new(marker, Self::bar_width(theme.font_size) * 0.3);
+        ctx.scene.stroke(&Stroke::new(3.0), Affine::IDENTITY, Color::BLACK, None, &ring);
+        ctx.scene.stroke(&Stroke::new(1.5), Affine::IDENTITY, Color::WHITE, None, &ring);
+        let hue_y = self.hue_bar.y0 + self.hue_bar.height() * (self.color.hue / 360.0) as f64;
+        let handle = Rect::new(self.hue_bar.x0 - 2.0, hue_y - 2.0, self.hue_bar.x1 + 2.0, hue_y + 2.0)
+            .to_rounded_rect(2.0);
+        ctx.scene.stroke(&Stroke::new(3.0), Affine::IDENTITY, Color::BLACK, None, &handle);
+        ctx.scene.stroke(&Stroke::new(1.5), Affine::IDENTITY, Color::WHITE, None, &handle);
+
+        let swatch = self.swatch.to_rounded_rect(radius);
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, self.color.to_color(), None, &swatch);
+        ctx.scene.stroke(&Stroke::new(1.0), Affine::IDENTITY, outline, None, &swatch);
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Crosshair)
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::ColorWell);
+        let [r, g, b, _] = self.color.to_color().to_rgba8().to_u8_array();
+        node.set_value(format!("#{:02x}{:02x}{:02x}", r, g, b));
+        Some(node)
+    }
+
+    // So a drag keeps picking with the pointer outside the square or bar
+    fn captures_pointer(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            UiEvent::PointerDown { position, button: MouseButton::Left } => {
+                self.dragging = self.part_at(*position);
+                match self.dragging {
+                    Some(part) => {
+                        self.pick(part, *position);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            UiEvent::DragMove { position, .. } => match self.dragging {
+                Some(part) => {
+                    self.pick(part, *position);
+                    true
+                }
+                None => false,
+            },
+            UiEvent::PointerUp { button: MouseButton::Left, .. } | UiEvent::DragEnd { .. } => {
+                self.dragging.take().is_some()
+            }
+            _ => false,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self.color).ok()
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        if let Ok(color) = serde_json::from_value(state.clone()) {
+            self.color = color;
+        }
+    }
+}
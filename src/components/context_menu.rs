@@ -0,0 +1,52 @@
+/// Entries shown at the pointer when a widget is right-clicked, see
+/// [`Widget::context_menu`](super::Widget::context_menu).
+#[derive(Default)]
+pub struct ContextMenu {
+    items: Vec<MenuItem>,
+}
+
+enum MenuItem {
+    Action { label: String, on_select: Box<dyn FnMut()> },
+    Separator,
+}
+
+impl ContextMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn item(mut self, label: &str, on_select: impl FnMut() + 'static) -> Self {
+        self.items.push(MenuItem::Action {
+            label: label.to_string(),
+            on_select: Box::new(on_select),
+        });
+        self
+    }
+
+    /// A line between groups of items.
+    pub fn separator(mut self) -> Self {
+        self.items.push(MenuItem::Separator);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // One per entry, `None` for separators
+    pub(crate) fn labels(&self) -> Vec<Option<String>> {
+        self.items
+            .iter()
+            .map(|item| match item {
+                MenuItem::Action { label, .. } => Some(label.clone()),
+                MenuItem::Separator => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn select(&mut self, index: usize) {
+        if let Some(MenuItem::Action { on_select, .. }) = self.items.get_mut(index) {
+            on_select();
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use vello::kurbo::{Affine, BezPath, Point, Rect, Size, Stroke};
+use vello::peniko::Fill;
+use winit::event::MouseButton;
+use winit::window::CursorIcon;
+use winit::keyboard::{Key, NamedKey};
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+use crate::text::{self, TextStyle};
+#[cfg(feature = "accesskit")]
+use accesskit::{Action, Node, Role};
+
+// While open the list should sit above neighbouring widgets
+const OPEN_Z_INDEX: i32 = 1000;
+
+/// A combo box: shows the selected option and, when clicked, a list to pick from.
+pub struct Dropdown<T> {
+    options: Vec<T>,
+    selected: usize,
+    // Row under the pointer or keyboard cursor while open
+    highlighted: usize,
+    open: bool,
+    focused: bool,
+    on_select: Option<Box<dyn FnMut(usize)>>,
+    bounds: Rect,
+}
+
+impl<T: ToString> Dropdown<T> {
+    pub fn new(options: Vec<T>) -> Self {
+        Self {
+            options,
+            selected: 0,
+            highlighted: 0,
+            open: false,
+            focused: false,
+            on_select: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.selected = index.min(self.options.len().saturating_sub(1));
+        self
+    }
+
+    pub fn on_select(mut self, f: impl FnMut(usize) + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_option(&self) -> Option<&T> {
+        self.options.get(self.selected)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn set_open(&mut self, open: bool) {
+        self.open = open && !self.options.is_empty();
+        self.highlighted = self.selected;
+    }
+
+    fn select(&mut self, index: usize) {
+        self.open = false;
+        if index == self.selected {
+            return;
+        }
+        self.selected = index;
+        if let Some(on_select) = &mut self.on_select {
+            on_select(index);
+        }
+    }
+
+    // Each row in the list is as tall as the closed dropdown
+    fn row_height(&self) -> f64 {
+        self.bounds.height()
+    }
+
+    fn list_rect(&self) -> Rect {
+        let height = self.row_height() * self.options.len() as f64;
+        Rect::new(self.bounds.x0, self.bounds.y1, self.bounds.x1, self.bounds.y1 + height)
+    }
+
+    fn row_at(&self, point: Point) -> Option<usize> {
+        let list = self.list_rect();
+        if !list.contains(point) {
+            return None;
+        }
+        let row = ((point.y - list.y0) / self.row_height()) as usize;
+        (row < self.options.len()).then_some(row)
+    }
+}
+
+impl<T: ToString + 'static> Widget for Dropdown<T> {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        let font_size = ctx.theme.font_size;
+        let style = TextStyle::new(font_size, ctx.theme.foreground);
+        let line_height = text::text_size("", &style).height;
+        let widest = self
+            .options
+            .iter()
+            .map(|option| text::text_size(&option.to_string(), &style).width)
+            .fold(0.0, f64::max);
+        let padding = ctx.theme.spacing;
+        // Room on the right for the chevron
+        let chevron = font_size as f64;
+        Size::new(widest + chevron + padding * 3.0, line_height + padding * 2.0)
+    }
+
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let padding = theme.spacing;
+        let style = TextStyle::new(theme.font_size, theme.foreground);
+        let header = self.bounds.to_rounded_rect(theme.corner_radius);
+        let border = if self.focused { theme.accent } else { theme.foreground.with_alpha(0.3) };
+        ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, theme.foreground.with_alpha(0.08), None, &header);
+        ctx.scene.stroke(&Stroke::new(1.0), Affine::IDENTITY, border, None, &header);
+
+        if let Some(option) = self.selected_option() {
+            let label = option.to_string();
+            let size = text::text_size(&label, &style);
+            let origin = Point::new(self.bounds.x0 + padding, self.bounds.center().y - size.height / 2.0);
+            text::draw_text(ctx.scene, &label, origin, &style);
+        }
+
+        let chevron_size = theme.font_size as f64 * 0.4;
+        let center = Point::new(self.bounds.x1 - padding - chevron_size, self.bounds.center().y);
+        let mut chevron = BezPath::new();
+        chevron.move_to(Point::new(center.x - chevron_size, center.y - chevron_size / 2.0));
+        chevron.line_to(Point::new(center.x, center.y + chevron_size / 2.0));
+        chevron.line_to(Point::new(center.x + chevron_size, center.y - chevron_size / 2.0));
+        ctx.scene.stroke(&Stroke::new(1.5), Affine::IDENTITY, theme.foreground, None, &chevron);
+
+        if !self.open {
+            return;
+        }
+        let list = self.list_rect();
+        let row_height = self.row_height();
+        let (x0, x1, y0) = (list.x0, list.x1, list.y0);
+        let labels: Vec<String> = self.options.iter().map(ToString::to_string).collect();
+        let highlighted = self.highlighted;
+        let overlay = ctx.overlay();
+        let list_shape = list.to_rounded_rect(theme.corner_radius);
+        overlay.fill(Fill::NonZero, Affine::IDENTITY, theme.background, None, &list_shape);
+        overlay.push_clip_layer(Affine::IDENTITY, &list_shape);
+        for (index, label) in labels.iter().enumerate() {
+            let top = y0 + row_height * index as f64;
+            let row = Rect::new(x0, top, x1, top + row_height);
+            if index == highlighted {
+                overlay.fill(Fill::NonZero, Affine::IDENTITY, theme.accent.with_alpha(0.3), None, &row);
+            }
+            let size = text::text_size(label, &style);
+            let origin = Point::new(x0 + padding, row.center().y - size.height / 2.0);
+            text::draw_text(overlay, label, origin, &style);
+        }
+        overlay.pop_layer();
+        overlay.stroke(&Stroke::new(1.0), Affine::IDENTITY, theme.foreground.with_alpha(0.3), None, &list_shape);
+    }
+
+    fn z_index(&self) -> i32 {
+        if self.open { OPEN_Z_INDEX } else { 0 }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn overlay_bounds(&self) -> Option<Rect> {
+        self.open.then(|| self.list_rect())
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::ComboBox);
+        if let Some(option) = self.options.get(self.selected) {
+            node.set_value(option.to_string());
+        }
+        node.set_expanded(self.open);
+        node.add_action(Action::Click);
+        node.add_action(if self.open { Action::Collapse } else { Action::Expand });
+        Some(node)
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Pointer)
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            UiEvent::PointerDown { position, button: MouseButton::Left } => {
+                match self.row_at(*position).filter(|_| self.open) {
+                    Some(row) => self.select(row),
+                    None => self.set_open(!self.open),
+                }
+                true
+            }
+            UiEvent::PointerMove { position } if self.open => {
+                if let Some(row) = self.row_at(*position) {
+                    self.highlighted = row;
+                }
+                true
+            }
+            // Clicking anywhere else takes focus away, which closes the list
+            UiEvent::FocusGained => {
+                self.focused = true;
+                false
+            }
+            UiEvent::FocusLost => {
+                self.focused = false;
+                self.open = false;
+                false
+            }
+            UiEvent::KeyDown { key, .. } if self.focused => self.handle_key(key),
+            _ => false,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.selected.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        if let Some(selected) = state.as_u64().map(|index| index as usize)
+            && selected < self.options.len()
+        {
+            self.selected = selected;
+        }
+    }
+}
+
+impl<T: ToString> Dropdown<T> {
+    fn handle_key(&mut self, key: &Key) -> bool {
+        let last = self.options.len().saturating_sub(1);
+        match key {
+            Key::Named(NamedKey::Escape) if self.open => self.open = false,
+            Key::Named(NamedKey::ArrowDown) if self.open => self.highlighted = (self.highlighted + 1).min(last),
+            Key::Named(NamedKey::ArrowUp) if self.open => self.highlighted = self.highlighted.saturating_sub(1),
+            Key::Named(NamedKey::Enter | NamedKey::Space) if self.open => self.select(self.highlighted),
+            // Closed: arrows change the selection in place, like a native select
+            Key::Named(NamedKey::ArrowDown) if self.selected < last => self.select(self.selected + 1),
+            Key::Named(NamedKey::ArrowUp) if self.selected > 0 => self.select(self.selected - 1),
+            Key::Named(NamedKey::Enter | NamedKey::Space) => self.set_open(true),
+            _ => return false,
+        }
+        true
+    }
+}
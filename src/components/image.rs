@@ -0,0 +1,205 @@
+use std::path::Path;
+
+use vello::kurbo::{Affine, Line, Rect, Size, Vec2};
+use vello::peniko::{ImageBrush, ImageData};
+
+use super::{DrawCtx, LayoutCtx, Spinner, Widget};
+use crate::render::{self, PendingImage};
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+enum State {
+    Loading(PendingImage),
+    Ready(ImageBrush),
+    Failed(String),
+}
+
+/// Draws a bitmap image, scaled to fit its bounds with the aspect ratio kept.
+///
+/// [`Image::load`] decodes on a pool of background threads so a large file
+/// doesn't stall the frame. Until it's done a placeholder is shown in its
+/// place, a spinner by default, and the app redraws once the image arrives.
+/// If it fails to load the reason is logged and an error placeholder is shown
+/// instead.
+pub struct Image {
+    state: State,
+    size: Option<Size>,
+    placeholder: Box<dyn Widget>,
+    // A crossed-out box when unset
+    error_placeholder: Option<Box<dyn Widget>>,
+    bounds: Rect,
+}
+
+impl Image {
+    /// An image that's already decoded, shown straight away.
+    pub fn new(image: impl Into<ImageBrush>) -> Self {
+        Self::with_state(State::Ready(image.into()))
+    }
+
+    /// Starts loading the PNG at `path` in the background.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        Self::with_state(State::Loading(render::load_image(path.as_ref().to_path_buf())))
+    }
+
+    fn with_state(state: State) -> Self {
+        Self {
+            state,
+            size: None,
+            placeholder: Box::new(Spinner::new()),
+            error_placeholder: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// Asks for this size instead of the image's own. Also the size the
+    /// placeholders are given, so the layout doesn't jump once it loads.
+    pub fn with_size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Shown while the image is loading, instead of a spinner.
+    pub fn with_placeholder(mut self, placeholder: impl Widget + 'static) -> Self {
+        self.placeholder = Box::new(placeholder);
+        self
+    }
+
+    /// Shown if the image fails to load, instead of a crossed-out box.
+    pub fn with_error_placeholder(mut self, placeholder: impl Widget + 'static) -> Self {
+        self.error_placeholder = Some(Box::new(placeholder));
+        self
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, State::Loading(_))
+    }
+
+    /// Why the image failed to load, if it did.
+    pub fn error(&self) -> Option<&str> {
+        match &self.state {
+            State::Failed(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// The decoded image, once it's ready.
+    pub fn image(&self) -> Option<&ImageData> {
+        match &self.state {
+            State::Ready(brush) => Some(&brush.image),
+            _ => None,
+        }
+    }
+
+    // Moves on from loading if the loader is done
+    fn poll(&mut self) {
+        if let State::Loading(pending) = &self.state
+            && let Some(result) = pending.take()
+        {
+            self.state = match result {
+                Ok(image) => State::Ready(image.into()),
+                Err(err) => State::Failed(err),
+            };
+        }
+    }
+
+    // Whichever placeholder stands in for the image right now
+    fn placeholder_mut(&mut self) -> Option<&mut dyn Widget> {
+        match &self.state {
+            State::Loading(_) => Some(self.placeholder.as_mut()),
+            State::Failed(_) => self.error_placeholder.as_deref_mut(),
+            State::Ready(_) => None,
+        }
+    }
+
+    fn placeholder(&self) -> Option<&dyn Widget> {
+        match &self.state {
+            State::Loading(_) => Some(self.placeholder.as_ref()),
+            State::Failed(_) => self.error_placeholder.as_deref(),
+            State::Ready(_) => None,
+        }
+    }
+}
+
+impl Widget for Image {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        self.poll();
+        let size = self.size;
+        if let State::Ready(brush) = &self.state {
+            return size.unwrap_or(Size::new(brush.image.width as f64, brush.image.height as f64));
+        }
+        let measured = match self.placeholder_mut() {
+            Some(placeholder) => placeholder.measure(ctx, size.unwrap_or(available)),
+            None => {
+                let side = ctx.theme.font_size as f64 * 1.5;
+                Size::new(side, side)
+            }
+        };
+        size.unwrap_or(measured)
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        if let Some(placeholder) = self.placeholder_mut() {
+            placeholder.layout(ctx, bounds);
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        if self.bounds.is_zero_area() {
+            return;
+        }
+        match &self.state {
+            State::Ready(brush) => {
+                let natural = Size::new(brush.image.width as f64, brush.image.height as f64);
+                if natural.is_zero_area() {
+                    return;
+                }
+                // As large as fits, centred along whichever axis has room to spare
+                let scale = (self.bounds.width() / natural.width).min(self.bounds.height() / natural.height);
+                let fitted = natural * scale;
+                let offset = Vec2::new(
+                    self.bounds.x0 + (self.bounds.width() - fitted.width) / 2.0,
+                    self.bounds.y0 + (self.bounds.height() - fitted.height) / 2.0,
+                );
+                ctx.scene.draw_image(brush, Affine::translate(offset) * Affine::scale(scale));
+            }
+            State::Failed(_) if self.error_placeholder.is_none() => {
+                let color = ctx.theme.foreground.with_alpha(0.4);
+                let rect = self.bounds.inset(-0.5);
+                ctx.stroke_hairline(Affine::IDENTITY, color, &rect);
+                for line in [
+                    Line::new((rect.x0, rect.y0), (rect.x1, rect.y1)),
+                    Line::new((rect.x1, rect.y0), (rect.x0, rect.y1)),
+                ] {
+                    ctx.stroke_hairline(Affine::IDENTITY, color, &line);
+                }
+            }
+            _ => {
+                if let Some(placeholder) = self.placeholder_mut() {
+                    ctx.draw_child(placeholder);
+                }
+            }
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        self.placeholder().is_some_and(|placeholder| placeholder.is_animating())
+    }
+
+    // Picked up by the next layout, which swaps the placeholder for the image
+    fn needs_layout(&self) -> bool {
+        match &self.state {
+            State::Loading(pending) => pending.is_ready() || self.placeholder.needs_layout(),
+            _ => self.placeholder().is_some_and(|placeholder| placeholder.needs_layout()),
+        }
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        Some(Node::new(Role::Image))
+    }
+}
@@ -0,0 +1,133 @@
+use vello::kurbo::{Rect, Size};
+use vello::peniko::Color;
+
+use super::{ContextMenu, DrawCtx, LayoutCtx, Widget};
+use crate::text::{self, FontWeight, Paragraph, TextStyle};
+use crate::theme::Theme;
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+/// Body text that wraps to the width it's given.
+pub struct Label {
+    text: String,
+    font_size: Option<f32>,
+    color: Option<Color>,
+    font: Option<String>,
+    weight: FontWeight,
+    italic: bool,
+    context_menu: Option<ContextMenu>,
+    // Laid out for the current bounds
+    paragraph: Option<Paragraph>,
+    bounds: Rect,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            font_size: None,
+            color: None,
+            font: None,
+            weight: FontWeight::NORMAL,
+            italic: false,
+            context_menu: None,
+            paragraph: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the label in a registered or system font, by name.
+    pub fn with_font(mut self, name: &str) -> Self {
+        self.font = Some(name.to_string());
+        self
+    }
+
+    pub fn with_weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    /// Shown when the label is right-clicked, e.g. with a "Copy" item.
+    pub fn with_context_menu(mut self, menu: ContextMenu) -> Self {
+        self.context_menu = Some(menu);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.paragraph = None;
+    }
+
+    fn text_style(&self, theme: &Theme) -> TextStyle {
+        TextStyle::new(self.font_size.unwrap_or(theme.font_size), self.color.unwrap_or(theme.foreground))
+            .with_weight(self.weight)
+            .with_italic(self.italic)
+            .with_font(self.font.as_deref())
+    }
+}
+
+// An unbounded width means nothing to wrap against
+fn wrap_width(width: f64) -> Option<f64> {
+    width.is_finite().then_some(width)
+}
+
+impl Widget for Label {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        // The height depends on how many lines the available width leaves us with
+        text::measure_text(&self.text, &self.text_style(ctx.theme), wrap_width(available.width)).size
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.paragraph = Some(Paragraph::new(&self.text, &self.text_style(ctx.theme), wrap_width(bounds.width())));
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let paragraph = match self.paragraph.take() {
+            Some(paragraph) => paragraph,
+            // Not laid out since the text changed
+            None => Paragraph::new(&self.text, &self.text_style(ctx.theme), wrap_width(self.bounds.width())),
+        };
+        paragraph.draw(ctx.scene, self.bounds.origin());
+        self.paragraph = Some(paragraph);
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.paragraph.is_none()
+    }
+
+    fn context_menu(&mut self) -> Option<&mut ContextMenu> {
+        self.context_menu.as_mut()
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::Label);
+        node.set_value(self.text.as_str());
+        Some(node)
+    }
+}
@@ -1,8 +1,333 @@
+use std::any::Any;
+use std::time::Duration;
+
+use vello::Scene;
+use vello::peniko::{BlendMode, BrushRef, Mix};
+use vello::kurbo::{Affine, Rect, Shape, Size, Stroke};
+use winit::window::CursorIcon;
+
+use crate::event::UiEvent;
+use crate::render;
+use crate::theme::Theme;
+
+mod button;
+mod canvas;
+mod checkbox;
+mod color_picker;
+mod context_menu;
+mod dropdown;
+mod image;
+mod label;
+mod opacity;
+mod panel;
+mod progress;
+mod radio_group;
+#[cfg(feature = "svg")]
+mod svg;
+mod tab_view;
+#[cfg(test)]
+pub(crate) mod test_widget;
+mod title;
+
 // Re-export components
-pub struct Title;
-pub struct Button;
+pub use button::Button;
+pub use canvas::Canvas;
+pub use checkbox::Checkbox;
+pub use color_picker::ColorPicker;
+pub use context_menu::ContextMenu;
+pub use dropdown::Dropdown;
+pub use image::Image;
+pub use label::Label;
+pub use opacity::Opacity;
+pub use panel::Panel;
+pub use progress::{ProgressBar, Spinner};
+pub use radio_group::RadioGroup;
+#[cfg(feature = "svg")]
+pub use svg::Svg;
+pub use tab_view::TabView;
+pub use title::Title;
+
+/// What a widget can see while measuring and laying out.
+pub struct LayoutCtx<'a> {
+    pub theme: &'a Theme,
+}
+
+// How far outside the focused widget its ring is drawn, and how thick
+const FOCUS_RING_OFFSET: f64 = 2.0;
+const FOCUS_RING_WIDTH: f64 = 2.0;
+
+/// What a widget draws into.
+pub struct DrawCtx<'a> {
+    pub scene: &'a mut Scene,
+    pub theme: &'a Theme,
+    /// How long the app has been running, for widgets that animate continuously.
+    pub time: Duration,
+    /// How far `time` moved since the previous frame, for widgets that step
+    /// motion along themselves. Capped after a stall, see
+    /// [`App::with_max_frame_delta`](crate::app::App::with_max_frame_delta).
+    pub delta: Duration,
+    /// How far the frame is from the last fixed update to the next, from 0 to
+    /// 1, for drawing state in between them. See
+    /// [`App::with_fixed_update`](crate::app::App::with_fixed_update).
+    pub interpolation: f64,
+    /// Physical pixels per logical pixel on the window's current monitor.
+    /// Bounds are already physical; this is for sizes meant to look the same
+    /// on every display, like hairlines.
+    pub scale_factor: f64,
+    overlay: Scene,
+}
+
+impl<'a> DrawCtx<'a> {
+    pub fn new(scene: &'a mut Scene, theme: &'a Theme) -> Self {
+        Self {
+            scene,
+            theme,
+            time: Duration::ZERO,
+            delta: Duration::ZERO,
+            interpolation: 0.0,
+            scale_factor: 1.0,
+            overlay: Scene::new(),
+        }
+    }
+
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn with_delta(mut self, delta: Duration) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    pub fn with_interpolation(mut self, interpolation: f64) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn with_scale_factor(mut self, scale_factor: f64) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// A scene drawn above the whole tree and outside every clip, for popups
+    /// that must escape their parent. Pair it with [`Widget::overlay_bounds`].
+    pub fn overlay(&mut self) -> &mut Scene {
+        &mut self.overlay
+    }
+
+    /// Composites the overlays on top. Call once the whole tree is drawn.
+    pub fn finish(mut self) {
+        self.flush_overlay();
+    }
+
+    /// Composites the overlays drawn so far, so whatever is drawn next ends up
+    /// above them, e.g. a second tree stacked over the first.
+    pub fn flush_overlay(&mut self) {
+        self.scene.append(&self.overlay, None);
+        self.overlay.reset();
+    }
+
+    /// Draws a child, blended at its [`Widget::opacity`]. Containers should draw
+    /// their children through this rather than calling `draw` directly.
+    ///
+    /// Translucent children are drawn into a layer clipped to their bounds, so
+    /// nested opacities multiply. Fully transparent ones aren't drawn at all.
+    pub fn draw_child(&mut self, child: &mut dyn Widget) {
+        let opacity = child.opacity().clamp(0.0, 1.0);
+        if opacity <= 0.0 {
+            return;
+        }
+        if opacity >= 1.0 {
+            child.draw(self);
+            return;
+        }
+        self.scene.push_layer(Mix::Normal, opacity, Affine::IDENTITY, &child.bounds());
+        child.draw(self);
+        self.scene.pop_layer();
+    }
+
+    /// Strokes `shape` one pixel wide, snapped to the pixel grid, for crisp
+    /// borders and rules. See [`render::stroke_hairline`].
+    pub fn stroke_hairline<'b>(&mut self, transform: Affine, brush: impl Into<BrushRef<'b>>, shape: &impl Shape) {
+        render::stroke_hairline(self.scene, transform, brush, shape);
+    }
+
+    /// Outlines `bounds` in the theme's accent colour, above everything else,
+    /// to show which widget has keyboard focus.
+    pub fn draw_focus_ring(&mut self, bounds: Rect) {
+        let ring = bounds
+            .inflate(FOCUS_RING_OFFSET, FOCUS_RING_OFFSET)
+            .to_rounded_rect(self.theme.corner_radius + FOCUS_RING_OFFSET);
+        let stroke = Stroke::new(FOCUS_RING_WIDTH);
+        self.overlay.stroke(&stroke, Affine::IDENTITY, self.theme.accent, None, &ring);
+    }
+
+    /// Like [`crate::render::with_clip`], for drawing through a context.
+    pub fn with_clip(&mut self, clip: &impl Shape, draw: impl FnOnce(&mut Self)) {
+        self.scene.push_clip_layer(Affine::IDENTITY, clip);
+        let layer = PopLayer(self);
+        draw(&mut *layer.0);
+    }
+
+    /// Like [`crate::render::with_blend`], for drawing through a context, e.g.
+    /// a highlight overlay screened onto a widget.
+    pub fn with_blend(&mut self, mode: impl Into<BlendMode>, opacity: f32, clip: &impl Shape, draw: impl FnOnce(&mut Self)) {
+        self.scene.push_layer(mode, opacity.clamp(0.0, 1.0), Affine::IDENTITY, clip);
+        let layer = PopLayer(self);
+        draw(&mut *layer.0);
+    }
+}
+
+// Pops the context's layer when dropped, including while unwinding
+struct PopLayer<'c, 'a>(&'c mut DrawCtx<'a>);
+
+impl Drop for PopLayer<'_, '_> {
+    fn drop(&mut self) {
+        self.0.scene.pop_layer();
+    }
+}
+
+/// Anything that can be placed in the UI tree.
+///
+/// A frame runs `measure` (how big would you like to be within `available`),
+/// then `layout` (this is where you are), then `draw`.
+///
+/// Widgets are `Any` so code holding a `dyn Widget` can get the concrete type
+/// back, as [`Ui`](crate::ui::Ui) does to reuse widgets between frames.
+pub trait Widget: Any {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size;
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect);
+    fn bounds(&self) -> Rect;
+    fn draw(&mut self, ctx: &mut DrawCtx);
+
+    /// Siblings are drawn in increasing z-index and hit-tested top-most first.
+    /// Equal values keep tree order.
+    fn z_index(&self) -> i32 {
+        0
+    }
+
+    /// How opaque the widget and everything below it is drawn, from 0 to 1.
+    /// Layout is unaffected, so a transparent widget still takes up space.
+    fn opacity(&self) -> f32 {
+        1.0
+    }
+
+    /// Whether clicking this widget, or tabbing to it, gives it keyboard focus.
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Area this widget currently covers in [`DrawCtx::overlay`], which
+    /// hit-testing checks before the regular tree.
+    fn overlay_bounds(&self) -> Option<Rect> {
+        None
+    }
+
+    /// What's left of [`bounds`](Widget::bounds) once the widget's padding is
+    /// taken off, for widgets that have some. Shown by
+    /// [`App::with_layout_debug`](crate::app::App::with_layout_debug).
+    fn content_bounds(&self) -> Option<Rect> {
+        None
+    }
+
+    /// Cursor to show while the pointer is over this widget. `None` defers to
+    /// the parent, and the default arrow is used if nobody claims one.
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        None
+    }
+
+    /// Text shown in a small box near the cursor once the pointer has rested on
+    /// this widget for a moment. `None` defers to the parent.
+    fn tooltip(&self) -> Option<&str> {
+        None
+    }
+
+    /// Menu shown at the pointer when this widget is right-clicked and nothing
+    /// consumes the press. `None` defers to the parent.
+    fn context_menu(&mut self) -> Option<&mut ContextMenu> {
+        None
+    }
+
+    /// Where text typed through an input method goes, usually the caret, for
+    /// widgets that take text. While the widget is focused and returns `Some`,
+    /// the platform IME is turned on and its candidate window kept next to
+    /// this rect. It then receives [`UiEvent::ImePreedit`] and
+    /// [`UiEvent::ImeCommit`], and should drop any preedit on `FocusLost`.
+    fn ime_cursor_area(&self) -> Option<Rect> {
+        None
+    }
+
+    /// How assistive technology should see this widget: its role, label and
+    /// state. Bounds, children and the focus action are filled in by the app.
+    /// `None` leaves the widget out, with its children attached to the nearest
+    /// ancestor that has a node.
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<accesskit::Node> {
+        None
+    }
+
+    /// Containers return their children so events and tree walks can reach them.
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &[]
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut []
+    }
+
+    /// Whether this widget or anything below it needs another frame soon.
+    /// The app keeps redrawing while this is `true` and goes idle otherwise.
+    fn is_animating(&self) -> bool {
+        self.children().iter().any(|child| child.is_animating())
+    }
+
+    /// Whether something changed that affects the size or placement of this
+    /// widget or anything below it, such as a child added or new text. The tree
+    /// is only measured and laid out again when this is `true` or the window
+    /// changed size. Containers with their own flag should `||` it with this.
+    fn needs_layout(&self) -> bool {
+        self.children().iter().any(|child| child.needs_layout())
+    }
+
+    /// What the user has changed about this widget, like a scroll position
+    /// or a ticked box, for a [`UiSnapshot`](crate::app::UiSnapshot). Not
+    /// what the app set up, and never callbacks. `None` keeps nothing.
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Puts back what [`Self::save_state`] returned, without calling any
+    /// callbacks. A value the widget can't use is ignored.
+    fn restore_state(&mut self, _state: &serde_json::Value) {}
+
+    /// Whether this widget wants every pointer event until the button just
+    /// pressed is released, wherever the pointer goes, for drags that carry on
+    /// past its bounds. Asked right after a press has been dispatched; the
+    /// deepest widget under the press that returns `true` captures the
+    /// pointer. See [`EventDispatcher`](crate::event::EventDispatcher).
+    fn captures_pointer(&self) -> bool {
+        false
+    }
+
+    /// Sees pointer events on their way down to the target, before any
+    /// descendant. Returning `true` intercepts the event.
+    fn capture_event(&mut self, _event: &UiEvent) -> bool {
+        false
+    }
+
+    /// Handles an event at the target and then at each ancestor in turn.
+    /// Returning `true` consumes the event and stops propagation.
+    fn handle_event(&mut self, _event: &UiEvent) -> bool {
+        false
+    }
+}
 
-impl Button {
-    pub fn new(_text: &str) -> Self { Self }
-    pub fn on_click<F>(&self, _f: F) where F: Fn(crate::prelude::State) {}
+/// Child indices in the order they should be drawn: by z-index, with ties kept
+/// in tree order. Hit-testing walks the same order in reverse.
+pub fn paint_order(children: &[Box<dyn Widget>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..children.len()).collect();
+    // sort_by_key is stable, which is what keeps equal z-indices in tree order
+    order.sort_by_key(|&index| children[index].z_index());
+    order
 }
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use vello::kurbo::{Rect, Size};
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::animation::{self, Animation, Easing};
+
+/// Draws its child at a given opacity, which can be animated for fades.
+pub struct Opacity {
+    child: Box<dyn Widget>,
+    opacity: Animation,
+}
+
+impl Opacity {
+    pub fn new(child: impl Widget + 'static, opacity: f32) -> Self {
+        Self {
+            child: Box::new(child),
+            opacity: Animation::new(opacity as f64, opacity as f64, Duration::ZERO, Easing::Linear),
+        }
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.fade_to(opacity, Duration::ZERO);
+    }
+
+    /// Animates from the current opacity to `opacity`.
+    pub fn fade_to(&mut self, opacity: f32, duration: Duration) {
+        let current = self.opacity.sample(animation::now());
+        self.opacity = Animation::new(current, opacity as f64, duration, Easing::EaseInOut);
+    }
+}
+
+impl Widget for Opacity {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        self.child.measure(ctx, available)
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.child.layout(ctx, bounds);
+    }
+
+    fn bounds(&self) -> Rect {
+        self.child.bounds()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        // Our parent has already applied our opacity
+        ctx.draw_child(self.child.as_mut());
+    }
+
+    fn opacity(&self) -> f32 {
+        self.opacity.sample(animation::now()) as f32
+    }
+
+    fn z_index(&self) -> i32 {
+        self.child.z_index()
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        std::slice::from_ref(&self.child)
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        std::slice::from_mut(&mut self.child)
+    }
+
+    fn is_animating(&self) -> bool {
+        !self.opacity.is_finished(animation::now()) || self.child.is_animating()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vello::Scene;
+
+    use super::*;
+    use crate::components::test_widget::Block;
+    use crate::layout::VStack;
+    use crate::theme::Theme;
+
+    #[test]
+    fn transparent_children_are_laid_out_but_not_drawn() {
+        let theme = Theme::default();
+        let hidden = Block::new(100.0, 40.0);
+        let hidden_draws = hidden.draws();
+        let below = Block::new(100.0, 20.0);
+        let below_draws = below.draws();
+        let mut stack = VStack::new().with_spacing(0.0).child(Opacity::new(hidden, 0.0)).child(below);
+        stack.layout(&LayoutCtx { theme: &theme }, Rect::new(0.0, 0.0, 100.0, 100.0));
+
+        assert_eq!(stack.children()[0].bounds(), Rect::new(0.0, 0.0, 100.0, 40.0));
+        assert_eq!(stack.children()[1].bounds().y0, 40.0, "still takes its space");
+
+        let mut scene = Scene::new();
+        stack.draw(&mut DrawCtx::new(&mut scene, &theme));
+        assert_eq!(hidden_draws.get(), 0);
+        assert_eq!(below_draws.get(), 1);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn nested_opacities_multiply() {
+        use vello::peniko::Color;
+
+        let Some(mut renderer) = crate::testing::headless() else {
+            return;
+        };
+        let theme = Theme::default();
+        let block = Block::new(32.0, 32.0).with_fill(Color::WHITE);
+        let mut outer = Opacity::new(Opacity::new(block, 0.5), 0.5);
+        outer.layout(&LayoutCtx { theme: &theme }, Rect::new(0.0, 0.0, 32.0, 32.0));
+        let mut scene = Scene::new();
+        DrawCtx::new(&mut scene, &theme).draw_child(&mut outer);
+
+        let frame = renderer.render(&scene, 32, 32, Color::BLACK).unwrap();
+        let pixel = frame.get_pixel(16, 16);
+        // A quarter of the way from black to white
+        assert!(pixel[0].abs_diff(64) <= 2, "{pixel:?}");
+        assert_eq!(pixel[0], pixel[1]);
+        assert_eq!(pixel[1], pixel[2]);
+    }
+}
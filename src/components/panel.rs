@@ -0,0 +1,152 @@
+use vello::kurbo::{Affine, Insets, Rect, Size, Stroke};
+use vello::peniko::{Color, Fill};
+
+use super::{ContextMenu, DrawCtx, LayoutCtx, Widget};
+use crate::render::NinePatch;
+
+/// A box around a single child, with a rounded background, an optional
+/// border and padding between the edge and the child. The building block for
+/// cards and dialogs.
+pub struct Panel {
+    child: Box<dyn Widget>,
+    // Each falls back to the theme when unset
+    background: Option<Color>,
+    background_image: Option<NinePatch>,
+    border: Option<(Color, Stroke)>,
+    corner_radius: Option<f64>,
+    padding: Option<Insets>,
+    context_menu: Option<ContextMenu>,
+    bounds: Rect,
+    // Inside the padding, where the child was laid out
+    content: Rect,
+}
+
+impl Panel {
+    pub fn new(child: impl Widget + 'static) -> Self {
+        Self {
+            child: Box::new(child),
+            background: None,
+            background_image: None,
+            border: None,
+            corner_radius: None,
+            padding: None,
+            context_menu: None,
+            bounds: Rect::ZERO,
+            content: Rect::ZERO,
+        }
+    }
+
+    /// Defaults to a shade lighter than the theme's background.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Draws `image` stretched over the panel instead of the background colour.
+    pub fn with_background_image(mut self, image: NinePatch) -> Self {
+        self.background_image = Some(image);
+        self
+    }
+
+    /// Outlines the panel, inside its bounds. There's no border by default.
+    pub fn with_border(mut self, color: Color, width: f64) -> Self {
+        self.border = Some((color, Stroke::new(width.max(0.0))));
+        self
+    }
+
+    /// Like [`Self::with_border`], with a stroke of any style, e.g.
+    /// [`shapes::dashed`](crate::shapes::dashed).
+    pub fn with_border_stroke(mut self, color: Color, stroke: Stroke) -> Self {
+        self.border = Some((color, stroke));
+        self
+    }
+
+    /// Defaults to the theme's corner radius.
+    pub fn with_corner_radius(mut self, radius: f64) -> Self {
+        self.corner_radius = Some(radius.max(0.0));
+        self
+    }
+
+    /// Space between the panel's edge and its child, either one value for
+    /// every side or an [`Insets`]. Defaults to the theme's spacing.
+    pub fn with_padding(mut self, padding: impl Into<Insets>) -> Self {
+        self.padding = Some(padding.into());
+        self
+    }
+
+    /// Shown when the panel is right-clicked anywhere its child doesn't show
+    /// a menu of its own. Wrapping a widget in a panel gives it a menu.
+    pub fn with_context_menu(mut self, menu: ContextMenu) -> Self {
+        self.context_menu = Some(menu);
+        self
+    }
+
+    fn padding(&self, ctx: &LayoutCtx) -> Insets {
+        self.padding.unwrap_or_else(|| Insets::uniform(ctx.theme.spacing))
+    }
+}
+
+impl Widget for Panel {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        let padding = self.padding(ctx);
+        let inner = Size::new(
+            (available.width - padding.x_value()).max(0.0),
+            (available.height - padding.y_value()).max(0.0),
+        );
+        let child = self.child.measure(ctx, inner);
+        Size::new(child.width + padding.x_value(), child.height + padding.y_value())
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        let inner = bounds - self.padding(ctx);
+        // Padding wider than the panel leaves the child an empty rect in the middle
+        let inner = Rect::from_center_size(inner.center(), Size::new(inner.width().max(0.0), inner.height().max(0.0)));
+        self.content = inner;
+        self.child.layout(ctx, inner);
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn content_bounds(&self) -> Option<Rect> {
+        Some(self.content)
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let radius = self.corner_radius.unwrap_or(theme.corner_radius);
+        match &self.background_image {
+            Some(image) => image.draw(ctx.scene, self.bounds),
+            None => {
+                let background = self
+                    .background
+                    .unwrap_or_else(|| theme.background.map_lightness(|l| (l + 0.05).min(1.0)));
+                let shape = self.bounds.to_rounded_rect(radius);
+                ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, background, None, &shape);
+            }
+        }
+        if let Some((color, stroke)) = &self.border
+            && stroke.width > 0.0
+        {
+            // Stroked along a path inset by half the width so all of it stays inside
+            let half = stroke.width / 2.0;
+            let shape = self.bounds.inset(-half).to_rounded_rect((radius - half).max(0.0));
+            ctx.scene.stroke(stroke, Affine::IDENTITY, *color, None, &shape);
+        }
+        ctx.draw_child(self.child.as_mut());
+    }
+
+    fn context_menu(&mut self) -> Option<&mut ContextMenu> {
+        self.context_menu.as_mut()
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        std::slice::from_ref(&self.child)
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        std::slice::from_mut(&mut self.child)
+    }
+}
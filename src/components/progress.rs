@@ -0,0 +1,195 @@
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use vello::kurbo::{Affine, Arc, Cap, Rect, Size, Stroke, Vec2};
+use vello::peniko::{Color, Fill};
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::animation::{self, Animation, Easing};
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+// How long the bar takes to slide to a new value
+const PROGRESS_SLIDE: Duration = Duration::from_millis(150);
+// One full turn of the spinner
+const SPINNER_PERIOD: Duration = Duration::from_secs(1);
+// How much of the circle the spinner's arc covers
+const SPINNER_SWEEP: f64 = TAU * 0.75;
+
+/// A horizontal bar filled in proportion to a value from 0 to 1.
+pub struct ProgressBar {
+    value: Animation,
+    accent: Option<Color>,
+    bounds: Rect,
+}
+
+impl ProgressBar {
+    pub fn new(value: f64) -> Self {
+        let value = value.clamp(0.0, 1.0);
+        Self {
+            value: Animation::new(value, value, Duration::ZERO, Easing::Linear),
+            accent: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn with_accent(mut self, color: Color) -> Self {
+        self.accent = Some(color);
+        self
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value.target()
+    }
+
+    /// Slides the bar to `value`, clamped to `[0, 1]`. The slide is an
+    /// animation, so it keeps frames coming until the bar has caught up.
+    pub fn set_value(&mut self, value: f64) {
+        let current = self.value.sample(animation::now());
+        self.value = Animation::new(current, value.clamp(0.0, 1.0), PROGRESS_SLIDE, Easing::EaseInOut);
+    }
+
+    fn thickness(font_size: f32) -> f64 {
+        font_size as f64 * 0.5
+    }
+}
+
+impl Widget for ProgressBar {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        // Fills the row it's in, or a sensible default when nothing bounds it
+        let width = if available.width.is_finite() {
+            available.width
+        } else {
+            ctx.theme.font_size as f64 * 12.0
+        };
+        Size::new(width, Self::thickness(ctx.theme.font_size))
+    }
+
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let thickness = Self::thickness(theme.font_size).min(self.bounds.height());
+        let top = self.bounds.center().y - thickness / 2.0;
+        let track = Rect::new(self.bounds.x0, top, self.bounds.x1, top + thickness);
+        let radius = thickness / 2.0;
+        ctx.scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            theme.foreground.with_alpha(0.2),
+            None,
+            &track.to_rounded_rect(radius),
+        );
+
+        let value = self.value.sample(animation::now());
+        if value > 0.0 {
+            let filled = track.with_size(Size::new(track.width() * value, thickness));
+            let accent = self.accent.unwrap_or(theme.accent);
+            ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, accent, None, &filled.to_rounded_rect(radius));
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        !self.value.is_finished(animation::now())
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::ProgressIndicator);
+        node.set_min_numeric_value(0.0);
+        node.set_max_numeric_value(1.0);
+        node.set_numeric_value(self.value());
+        Some(node)
+    }
+}
+
+/// A spinning arc for work of unknown length.
+///
+/// Turns with [`DrawCtx::time`], so it keeps the app redrawing for as long
+/// as it's laid out somewhere visible.
+pub struct Spinner {
+    size: Option<f64>,
+    accent: Option<Color>,
+    bounds: Rect,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Spinner {
+    pub fn new() -> Self {
+        Self {
+            size: None,
+            accent: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// Diameter in pixels. Defaults to one and a half lines of text.
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn with_accent(mut self, color: Color) -> Self {
+        self.accent = Some(color);
+        self
+    }
+
+    fn diameter(&self, font_size: f32) -> f64 {
+        self.size.unwrap_or(font_size as f64 * 1.5)
+    }
+}
+
+impl Widget for Spinner {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        let diameter = self.diameter(ctx.theme.font_size);
+        Size::new(diameter, diameter)
+    }
+
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let diameter = self
+            .diameter(ctx.theme.font_size)
+            .min(self.bounds.width())
+            .min(self.bounds.height());
+        let width = (diameter / 8.0).max(1.0);
+        // Inset by half the stroke so the arc stays inside our bounds
+        let radius = (diameter - width) / 2.0;
+        if radius <= 0.0 {
+            return;
+        }
+        let start = animation::phase(ctx.time, SPINNER_PERIOD) * TAU;
+        let arc = Arc::new(self.bounds.center(), Vec2::new(radius, radius), start, SPINNER_SWEEP, 0.0);
+        let stroke = Stroke::new(width).with_caps(Cap::Round);
+        let accent = self.accent.unwrap_or(ctx.theme.accent);
+        ctx.scene.stroke(&stroke, Affine::IDENTITY, accent, None, &arc);
+    }
+
+    fn is_animating(&self) -> bool {
+        // Laid out with no area, e.g. in a collapsed container, nobody can see it turn
+        self.bounds.area() > 0.0
+    }
+
+    // No numeric value, which is how an indeterminate indicator is told apart
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        Some(Node::new(Role::ProgressIndicator))
+    }
+}
@@ -0,0 +1,202 @@
+use vello::kurbo::{Affine, Circle, Point, Rect, Size, Stroke};
+use vello::peniko::{Color, Fill};
+use winit::event::MouseButton;
+use winit::keyboard::{Key, NamedKey};
+use winit::window::CursorIcon;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+use crate::text::{self, TextStyle};
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+/// A column of mutually exclusive options, exactly one of which is selected.
+pub struct RadioGroup {
+    options: Vec<String>,
+    selected: usize,
+    on_select: Option<Box<dyn FnMut(usize)>>,
+    accent: Option<Color>,
+    focused: bool,
+    // One per option, from the last layout
+    rows: Vec<Rect>,
+    bounds: Rect,
+}
+
+impl RadioGroup {
+    pub fn new(options: &[&str]) -> Self {
+        Self {
+            options: options.iter().map(|option| option.to_string()).collect(),
+            selected: 0,
+            on_select: None,
+            accent: None,
+            focused: false,
+            rows: Vec::new(),
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.selected = index.min(self.options.len().saturating_sub(1));
+        self
+    }
+
+    pub fn with_accent(mut self, color: Color) -> Self {
+        self.accent = Some(color);
+        self
+    }
+
+    /// Called with the new index whenever the selection changes.
+    pub fn on_select(mut self, f: impl FnMut(usize) + 'static) -> Self {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_option(&self) -> Option<&str> {
+        self.options.get(self.selected).map(String::as_str)
+    }
+
+    fn select(&mut self, index: usize) {
+        // Clicking the selected option leaves it selected
+        if index == self.selected || index >= self.options.len() {
+            return;
+        }
+        self.selected = index;
+        if let Some(on_select) = &mut self.on_select {
+            on_select(index);
+        }
+    }
+
+    // The circle is as tall as one line of text, like a checkbox's box
+    fn circle_size(font_size: f32) -> f64 {
+        font_size as f64 * 1.25
+    }
+
+    // Every row is as tall as the tallest, so they're evenly spaced
+    fn row_height(&self, ctx: &LayoutCtx, style: &TextStyle) -> f64 {
+        self.options
+            .iter()
+            .map(|option| text::text_size(option, style).height)
+            .fold(Self::circle_size(ctx.theme.font_size), f64::max)
+    }
+}
+
+impl Widget for RadioGroup {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        let style = TextStyle::new(ctx.theme.font_size, ctx.theme.foreground);
+        let label_width = self
+            .options
+            .iter()
+            .map(|option| text::text_size(option, &style).width)
+            .fold(0.0, f64::max);
+        let rows = self.options.len() as f64;
+        Size::new(
+            Self::circle_size(ctx.theme.font_size) + ctx.theme.spacing + label_width,
+            self.row_height(ctx, &style) * rows + ctx.theme.spacing * (rows - 1.0).max(0.0),
+        )
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        let style = TextStyle::new(ctx.theme.font_size, ctx.theme.foreground);
+        let row_height = self.row_height(ctx, &style);
+        self.rows = (0..self.options.len())
+            .map(|index| {
+                let top = bounds.y0 + index as f64 * (row_height + ctx.theme.spacing);
+                Rect::new(bounds.x0, top, bounds.x1, top + row_height)
+            })
+            .collect();
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let radius = Self::circle_size(theme.font_size) / 2.0;
+        let accent = self.accent.unwrap_or(theme.accent);
+        let style = TextStyle::new(theme.font_size, theme.foreground);
+
+        for (index, (option, row)) in self.options.iter().zip(&self.rows).enumerate() {
+            let center = Point::new(row.x0 + radius, row.center().y);
+            let selected = index == self.selected;
+            // Inset by half the stroke so the ring stays inside its row
+            let ring = Circle::new(center, radius - 0.75);
+            let ring_color = if selected { accent } else { theme.foreground };
+            ctx.scene.stroke(&Stroke::new(1.5), Affine::IDENTITY, ring_color, None, &ring);
+            if selected {
+                ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, accent, None, &Circle::new(center, radius * 0.5));
+            }
+
+            let label = text::text_size(option, &style);
+            let origin = Point::new(row.x0 + radius * 2.0 + theme.spacing, row.center().y - label.height / 2.0);
+            text::draw_text(ctx.scene, option, origin, &style);
+        }
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        Some(CursorIcon::Pointer)
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            UiEvent::PointerDown { button: MouseButton::Left, .. } => true,
+            UiEvent::PointerUp { position, button: MouseButton::Left } => {
+                // The gaps between rows don't pick anything
+                match self.rows.iter().position(|row| row.contains(*position)) {
+                    Some(index) => {
+                        self.select(index);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            UiEvent::FocusGained => {
+                self.focused = true;
+                false
+            }
+            UiEvent::FocusLost => {
+                self.focused = false;
+                false
+            }
+            UiEvent::KeyDown { key: Key::Named(NamedKey::ArrowUp), .. } if self.focused => {
+                self.select(self.selected.saturating_sub(1));
+                true
+            }
+            UiEvent::KeyDown { key: Key::Named(NamedKey::ArrowDown), .. } if self.focused => {
+                self.select(self.selected + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        !self.options.is_empty()
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::RadioGroup);
+        if let Some(option) = self.options.get(self.selected) {
+            node.set_value(option.as_str());
+        }
+        Some(node)
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.selected.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        if let Some(selected) = state.as_u64().map(|index| index as usize)
+            && selected < self.options.len()
+        {
+            self.selected = selected;
+        }
+    }
+}
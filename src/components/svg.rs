@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use anyhow::Context;
+use vello::Scene;
+use vello::kurbo::{Affine, Rect, Size, Vec2};
+use vello_svg::usvg;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+
+/// Draws an SVG image, scaled to fit its bounds with the aspect ratio kept.
+///
+/// The file is parsed once, into a scene fragment that every draw appends.
+/// Elements vello can't draw, like filters and patterns, are left out with a
+/// warning rather than failing the load. Text is only drawn if it has been
+/// converted to paths.
+pub struct Svg {
+    fragment: Scene,
+    // The image's own size, from its width and height or viewBox
+    natural_size: Size,
+    size: Option<Size>,
+    bounds: Rect,
+}
+
+impl Svg {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let tree = usvg::Tree::from_data(bytes, &usvg::Options::default()).context("failed to parse SVG")?;
+        let mut fragment = Scene::new();
+        let mut skipped = 0;
+        vello_svg::append_tree_with(&mut fragment, &tree, &mut |_, node| {
+            log::debug!("Skipping unsupported SVG element {:?}", node.id());
+            skipped += 1;
+        });
+        if skipped > 0 {
+            log::warn!("Left out {} SVG elements that can't be drawn", skipped);
+        }
+        let size = tree.size();
+        Ok(Self {
+            fragment,
+            natural_size: Size::new(size.width() as f64, size.height() as f64),
+            size: None,
+            bounds: Rect::ZERO,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("failed to read SVG {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("failed to load SVG {}", path.display()))
+    }
+
+    /// Asks for this size instead of the image's own, e.g. to draw an icon at
+    /// the size of the text next to it.
+    pub fn with_size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// The image's own size, from its `width` and `height` or its `viewBox`.
+    pub fn natural_size(&self) -> Size {
+        self.natural_size
+    }
+}
+
+impl Widget for Svg {
+    fn measure(&mut self, _ctx: &LayoutCtx, _available: Size) -> Size {
+        self.size.unwrap_or(self.natural_size)
+    }
+
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let natural = self.natural_size;
+        if natural.is_zero_area() || self.bounds.is_zero_area() {
+            return;
+        }
+        // As large as fits, centred along whichever axis has room to spare
+        let scale = (self.bounds.width() / natural.width).min(self.bounds.height() / natural.height);
+        let fitted = natural * scale;
+        let offset = Vec2::new(
+            self.bounds.x0 + (self.bounds.width() - fitted.width) / 2.0,
+            self.bounds.y0 + (self.bounds.height() - fitted.height) / 2.0,
+        );
+        let transform = Affine::translate(offset) * Affine::scale(scale);
+        // Drawing can spill past the viewBox, which shouldn't show
+        let clip = Rect::from_origin_size(offset.to_point(), fitted);
+        let fragment = &self.fragment;
+        ctx.with_clip(&clip, |ctx| ctx.scene.append(fragment, Some(transform)));
+    }
+}
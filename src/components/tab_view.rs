@@ -0,0 +1,254 @@
+use vello::kurbo::{Affine, Line, Point, Rect, Size};
+use vello::peniko::Fill;
+use winit::event::MouseButton;
+use winit::keyboard::{Key, NamedKey};
+use winit::window::CursorIcon;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+use crate::text::{self, TextStyle};
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+// Thickness of the line under the selected header
+const INDICATOR_WIDTH: f64 = 2.0;
+
+/// Named pages with a row of clickable headers above whichever one is
+/// selected. Ctrl+Tab moves to the next tab and Ctrl+Shift+Tab back.
+///
+/// Only the selected tab is part of the tree: the others aren't laid out,
+/// drawn or sent events until they're selected.
+pub struct TabView {
+    names: Vec<String>,
+    tabs: Vec<Box<dyn Widget>>,
+    selected: usize,
+    on_tab_change: Option<Box<dyn FnMut(usize)>>,
+    hovered: Option<usize>,
+    // Set when another tab is selected, which then has to be laid out
+    dirty: bool,
+    // One per tab, from the last layout
+    headers: Vec<Rect>,
+    bounds: Rect,
+}
+
+impl Default for TabView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TabView {
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            tabs: Vec::new(),
+            selected: 0,
+            on_tab_change: None,
+            hovered: None,
+            dirty: false,
+            headers: Vec::new(),
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn tab(mut self, name: &str, content: impl Widget + 'static) -> Self {
+        self.push_tab(name, content);
+        self
+    }
+
+    pub fn push_tab(&mut self, name: &str, content: impl Widget + 'static) {
+        self.names.push(name.to_string());
+        self.tabs.push(Box::new(content));
+        self.dirty = true;
+    }
+
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.set_selected(index);
+        self
+    }
+
+    /// Called with the new index whenever the user switches tabs.
+    pub fn on_tab_change(mut self, f: impl FnMut(usize) + 'static) -> Self {
+        self.on_tab_change = Some(Box::new(f));
+        self
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Switches tabs without calling `on_tab_change`.
+    pub fn set_selected(&mut self, index: usize) {
+        let index = index.min(self.tabs.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.dirty = true;
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        if index == self.selected || index >= self.tabs.len() {
+            return;
+        }
+        self.set_selected(index);
+        if let Some(on_tab_change) = &mut self.on_tab_change {
+            on_tab_change(index);
+        }
+    }
+
+    fn header_size(ctx: &LayoutCtx, name: &str) -> Size {
+        let label = text::text_size(name, &TextStyle::new(ctx.theme.font_size, ctx.theme.foreground));
+        let padding = ctx.theme.spacing;
+        Size::new(label.width + padding * 3.0, label.height + padding * 1.5)
+    }
+
+    fn header_at(&self, position: Point) -> Option<usize> {
+        self.headers.iter().position(|header| header.contains(position))
+    }
+}
+
+impl Widget for TabView {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        let headers: Vec<Size> = self.names.iter().map(|name| Self::header_size(ctx, name)).collect();
+        let header_width: f64 = headers.iter().map(|size| size.width).sum();
+        let header_height = headers.iter().map(|size| size.height).fold(0.0, f64::max);
+        let content = match self.tabs.get_mut(self.selected) {
+            Some(tab) => tab.measure(ctx, Size::new(available.width, (available.height - header_height).max(0.0))),
+            None => Size::ZERO,
+        };
+        Size::new(header_width.max(content.width), header_height + content.height)
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.dirty = false;
+        let mut left = bounds.x0;
+        let mut header_height: f64 = 0.0;
+        self.headers = self
+            .names
+            .iter()
+            .map(|name| {
+                let size = Self::header_size(ctx, name);
+                let header = Rect::from_origin_size((left, bounds.y0), size);
+                left = header.x1;
+                header_height = header_height.max(size.height);
+                header
+            })
+            .collect();
+        // Headers share a height so their indicators line up
+        for header in &mut self.headers {
+            header.y1 = bounds.y0 + header_height;
+        }
+        if let Some(tab) = self.tabs.get_mut(self.selected) {
+            let top = (bounds.y0 + header_height).min(bounds.y1);
+            tab.layout(ctx, Rect::new(bounds.x0, top, bounds.x1, bounds.y1));
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let theme = ctx.theme;
+        let header_bottom = self.headers.first().map_or(self.bounds.y0, |header| header.y1);
+        // Along the last row of pixels above the content
+        let rule = Line::new((self.bounds.x0, header_bottom - 0.5), (self.bounds.x1, header_bottom - 0.5));
+        ctx.stroke_hairline(Affine::IDENTITY, theme.foreground.with_alpha(0.2), &rule);
+
+        for (index, (name, header)) in self.names.iter().zip(&self.headers).enumerate() {
+            let selected = index == self.selected;
+            if self.hovered == Some(index) && !selected {
+                let highlight = header.to_rounded_rect((theme.corner_radius, theme.corner_radius, 0.0, 0.0));
+                ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, theme.foreground.with_alpha(0.08), None, &highlight);
+            }
+            // Unselected tabs are dimmed rather than a different colour
+            let color = if selected { theme.foreground } else { theme.foreground.with_alpha(0.6) };
+            let style = TextStyle::new(theme.font_size, color);
+            let label = text::text_size(name, &style);
+            let origin = Point::new(
+                header.center().x - label.width / 2.0,
+                header.center().y - label.height / 2.0,
+            );
+            text::draw_text(ctx.scene, name, origin, &style);
+            if selected {
+                let indicator = Rect::new(header.x0, header.y1 - INDICATOR_WIDTH, header.x1, header.y1);
+                ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, theme.accent, None, &indicator);
+            }
+        }
+
+        if let Some(tab) = self.tabs.get_mut(self.selected) {
+            ctx.draw_child(tab.as_mut());
+        }
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        // Only over the headers; the content falls back to its own
+        self.hovered.map(|_| CursorIcon::Pointer)
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::TabPanel);
+        if let Some(name) = self.names.get(self.selected) {
+            node.set_label(name.as_str());
+        }
+        Some(node)
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        self.tabs.get(self.selected..=self.selected).unwrap_or(&[])
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        self.tabs.get_mut(self.selected..=self.selected).unwrap_or(&mut [])
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.dirty || self.children().iter().any(|child| child.needs_layout())
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            // Moves over the content bubble up here too, unless the content consumes them
+            UiEvent::PointerMove { position } => {
+                let hovered = self.header_at(*position);
+                // Consumed only when the highlight changes, so the app redraws
+                std::mem::replace(&mut self.hovered, hovered) != hovered
+            }
+            UiEvent::PointerDown { position, button: MouseButton::Left } => self.header_at(*position).is_some(),
+            UiEvent::PointerUp { position, button: MouseButton::Left } => match self.header_at(*position) {
+                Some(index) => {
+                    self.select(index);
+                    true
+                }
+                None => false,
+            },
+            // Consumed so the app redraws without the highlight
+            UiEvent::PointerLeave => self.hovered.take().is_some(),
+            UiEvent::KeyDown { key: Key::Named(NamedKey::Tab), modifiers, .. }
+                if modifiers.control_key() && !self.tabs.is_empty() =>
+            {
+                let count = self.tabs.len();
+                let next = if modifiers.shift_key() {
+                    (self.selected + count - 1) % count
+                } else {
+                    (self.selected + 1) % count
+                };
+                self.select(next);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.selected.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        if let Some(selected) = state.as_u64() {
+            self.set_selected(selected as usize);
+        }
+    }
+}
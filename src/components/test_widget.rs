@@ -0,0 +1,117 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use vello::kurbo::{Affine, Rect, Size};
+use vello::peniko::{Color, Fill};
+use winit::window::CursorIcon;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+
+/// A fixed-size block for tests, which consumes every event it's sent and
+/// keeps a log of them. It can have a child, laid out over the whole block,
+/// and a fill, and counts how often it's drawn.
+pub(crate) struct Block {
+    size: Size,
+    child: Option<Box<dyn Widget>>,
+    cursor: Option<CursorIcon>,
+    fill: Option<Color>,
+    captures: bool,
+    // Shared, so tests can still read it once the block is in a tree
+    events: Rc<RefCell<Vec<UiEvent>>>,
+    draws: Rc<Cell<usize>>,
+    bounds: Rect,
+}
+
+impl Block {
+    pub(crate) fn new(width: f64, height: f64) -> Self {
+        Self {
+            size: Size::new(width, height),
+            child: None,
+            cursor: None,
+            fill: None,
+            captures: false,
+            events: Rc::default(),
+            draws: Rc::default(),
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub(crate) fn with_child(mut self, child: impl Widget + 'static) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+
+    pub(crate) fn with_cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    // Only drawn by the GPU tests
+    #[cfg(feature = "testing")]
+    pub(crate) fn with_fill(mut self, fill: Color) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    pub(crate) fn capturing(mut self) -> Self {
+        self.captures = true;
+        self
+    }
+
+    pub(crate) fn events(&self) -> Rc<RefCell<Vec<UiEvent>>> {
+        self.events.clone()
+    }
+
+    pub(crate) fn draws(&self) -> Rc<Cell<usize>> {
+        self.draws.clone()
+    }
+}
+
+impl Widget for Block {
+    fn measure(&mut self, _ctx: &LayoutCtx, _available: Size) -> Size {
+        self.size
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        if let Some(child) = &mut self.child {
+            child.layout(ctx, bounds);
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        self.draws.set(self.draws.get() + 1);
+        if let Some(fill) = self.fill {
+            ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, fill, None, &self.bounds);
+        }
+        if let Some(child) = &mut self.child {
+            ctx.draw_child(child.as_mut());
+        }
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        self.events.borrow_mut().push(event.clone());
+        true
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        self.cursor
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        self.child.as_slice()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        self.child.as_mut_slice()
+    }
+
+    fn captures_pointer(&self) -> bool {
+        self.captures
+    }
+}
@@ -0,0 +1,111 @@
+use vello::kurbo::{Rect, Size};
+use vello::peniko::Color;
+
+use super::{DrawCtx, LayoutCtx, Widget};
+use crate::text::{self, FontWeight, TextStyle};
+use crate::theme::Theme;
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+// Titles are set this much larger than body text
+const TITLE_SCALE: f32 = 1.5;
+
+pub struct Title {
+    text: String,
+    font_size: Option<f32>,
+    color: Option<Color>,
+    font: Option<String>,
+    weight: FontWeight,
+    italic: bool,
+    needs_layout: bool,
+    bounds: Rect,
+}
+
+impl Title {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            font_size: None,
+            color: None,
+            font: None,
+            weight: FontWeight::NORMAL,
+            italic: false,
+            needs_layout: true,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.needs_layout = true;
+    }
+
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the title in a registered or system font, by name.
+    pub fn with_font(mut self, name: &str) -> Self {
+        self.font = Some(name.to_string());
+        self
+    }
+
+    pub fn with_weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    fn text_style(&self, theme: &Theme) -> TextStyle {
+        let font_size = self.font_size.unwrap_or(theme.font_size * TITLE_SCALE);
+        TextStyle::new(font_size, self.color.unwrap_or(theme.foreground))
+            .with_weight(self.weight)
+            .with_italic(self.italic)
+            .with_font(self.font.as_deref())
+    }
+}
+
+impl Widget for Title {
+    fn measure(&mut self, ctx: &LayoutCtx, _available: Size) -> Size {
+        text::text_size(&self.text, &self.text_style(ctx.theme))
+    }
+
+    fn layout(&mut self, _ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.needs_layout = false;
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        text::draw_text(ctx.scene, &self.text, self.bounds.origin(), &self.text_style(ctx.theme));
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.needs_layout
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::Heading);
+        node.set_label(self.text.as_str());
+        Some(node)
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use vello::kurbo::Point;
+use winit::event::MouseButton;
+
+use super::UiEvent;
+
+/// Thresholds for turning raw presses and moves into gestures.
+#[derive(Clone, Debug)]
+pub struct GestureConfig {
+    /// Longest gap between presses that still counts as a multi-click.
+    pub double_click_interval: Duration,
+    /// How far the pointer may wander between presses of a multi-click.
+    pub double_click_distance: f64,
+    /// How far a held pointer must move before it counts as a drag.
+    pub drag_threshold: f64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            double_click_interval: Duration::from_millis(500),
+            double_click_distance: 4.0,
+            drag_threshold: 5.0,
+        }
+    }
+}
+
+/// Watches the left button's pointer events and reports the gestures they make.
+///
+/// Presses in quick succession count up: the second emits `DoubleClick`, the
+/// third `TripleClick`, and a fourth starts a fresh sequence rather than
+/// emitting another double click. Starting a drag also resets the count.
+#[derive(Default)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    last_press: Option<(Instant, Point)>,
+    click_count: u32,
+    // Where the button went down, while it's held
+    press_origin: Option<Point>,
+    // Last position reported by a drag event, while dragging
+    drag_position: Option<Point>,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    /// Feeds one raw event in and returns the gesture events it completes, in order.
+    pub fn process(&mut self, event: &UiEvent, now: Instant) -> Vec<UiEvent> {
+        match *event {
+            UiEvent::PointerDown { position, button: MouseButton::Left } => self.press(position, now),
+            UiEvent::PointerMove { position } => self.moved(position),
+            UiEvent::PointerUp { position, button: MouseButton::Left } => self.release(position),
+            _ => Vec::new(),
+        }
+    }
+
+    fn press(&mut self, position: Point, now: Instant) -> Vec<UiEvent> {
+        let continues_sequence = self.last_press.is_some_and(|(time, last)| {
+            now.saturating_duration_since(time) <= self.config.double_click_interval
+                && last.distance(position) <= self.config.double_click_distance
+        });
+        self.click_count = if continues_sequence && self.click_count < 3 { self.click_count + 1 } else { 1 };
+        self.last_press = Some((now, position));
+        self.press_origin = Some(position);
+
+        match self.click_count {
+            2 => vec![UiEvent::DoubleClick { position }],
+            3 => vec![UiEvent::TripleClick { position }],
+            _ => Vec::new(),
+        }
+    }
+
+    fn moved(&mut self, position: Point) -> Vec<UiEvent> {
+        let Some(origin) = self.press_origin else {
+            return Vec::new();
+        };
+        match self.drag_position {
+            Some(last) => {
+                self.drag_position = Some(position);
+                vec![UiEvent::DragMove { position, delta: position - last }]
+            }
+            None if origin.distance(position) >= self.config.drag_threshold => {
+                self.drag_position = Some(position);
+                // A drag is never also part of a click sequence
+                self.click_count = 0;
+                self.last_press = None;
+                vec![
+                    UiEvent::DragStart { position: origin },
+                    UiEvent::DragMove { position, delta: position - origin },
+                ]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn release(&mut self, position: Point) -> Vec<UiEvent> {
+        self.press_origin = None;
+        match self.drag_position.take() {
+            Some(_) => vec![UiEvent::DragEnd { position }],
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,680 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use vello::kurbo::{Point, Rect, Vec2};
+use winit::event::{ElementState, Ime, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::CursorIcon;
+
+use crate::components::{paint_order, Widget};
+
+mod gesture;
+mod shortcut;
+
+pub use gesture::{GestureConfig, GestureRecognizer};
+pub use shortcut::KeyCombination;
+
+// How far one wheel "line" scrolls, in pixels
+const LINE_HEIGHT: f64 = 40.0;
+
+/// Input as seen by widgets, in the same pixel space as their bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiEvent {
+    /// [`App`](crate::app::App) coalesces moves, so a widget sees at most one per
+    /// frame, at the latest position. Nothing needs every raw sample yet.
+    PointerMove { position: Point },
+    PointerDown { position: Point, button: MouseButton },
+    PointerUp { position: Point, button: MouseButton },
+    /// `delta` follows winit's convention: positive values move content right and down.
+    /// Like moves, scrolls are coalesced per frame with their deltas summed.
+    Scroll { position: Point, delta: Vec2 },
+    /// Synthesized by [`GestureRecognizer`] right after the second press of a sequence.
+    DoubleClick { position: Point },
+    TripleClick { position: Point },
+    /// Sent with the position the button went down at, once the pointer has moved
+    /// far enough while held.
+    DragStart { position: Point },
+    /// `delta` is the movement since the previous drag event.
+    DragMove { position: Point, delta: Vec2 },
+    DragEnd { position: Point },
+    /// Sent again with `repeat` set while the key is held, at the user's
+    /// repeat delay and rate, so a widget that edits text or moves a caret
+    /// should act on repeats too; ones that toggle, like buttons, ignore them.
+    ///
+    /// Repeats come from the platform and are passed through as they arrive,
+    /// with no timer of our own: Windows, macOS, X11 and browsers repeat
+    /// natively, and winit repeats itself on Wayland at the compositor's
+    /// rate, which may be off. Shortcuts and the app's own toggle keys only
+    /// fire on the first press.
+    KeyDown { key: Key, modifiers: ModifiersState, repeat: bool },
+    KeyUp { key: Key, modifiers: ModifiersState },
+    /// Text an input method is still composing, to show at the caret until
+    /// it's committed or replaced. Empty when composition stops without a
+    /// commit. `cursor` is the IME's own cursor, as a byte range into `text`.
+    ImePreedit { text: String, cursor: Option<(usize, usize)> },
+    /// Finished text from an input method, to insert at the caret.
+    ImeCommit { text: String },
+    FocusGained,
+    FocusLost,
+    /// Sent straight to a widget, without routing, when the pointer moves onto
+    /// it or any of its descendants. See [`EventDispatcher`].
+    PointerEnter,
+    /// Sent when the pointer is no longer over a widget or any of its
+    /// descendants, or has left the window.
+    PointerLeave,
+    /// Files dropped onto the window, all those of one drop together. Sent to
+    /// the widget under the pointer and bubbling up from there, so a widget
+    /// becomes a drop target by consuming it; drops nobody consumes go to
+    /// [`App::on_file_drop`](crate::app::App::on_file_drop). `position` is
+    /// where the pointer was last seen, since not every platform reports it
+    /// during a drag. See [`hovered_files`](crate::app::hovered_files) for
+    /// highlighting while files are dragged over.
+    FileDrop { position: Point, paths: Vec<PathBuf> },
+}
+
+impl UiEvent {
+    /// Translates a raw window event, given where the cursor last was and which
+    /// modifiers are held.
+    pub fn from_window_event(event: &WindowEvent, cursor: Point, modifiers: ModifiersState) -> Option<Self> {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => Some(UiEvent::PointerMove {
+                position: Point::new(position.x, position.y),
+            }),
+            WindowEvent::MouseInput { state, button, .. } => Some(match state {
+                ElementState::Pressed => UiEvent::PointerDown { position: cursor, button: *button },
+                ElementState::Released => UiEvent::PointerUp { position: cursor, button: *button },
+            }),
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Vec2::new(*x as f64, *y as f64) * LINE_HEIGHT,
+                    MouseScrollDelta::PixelDelta(pos) => Vec2::new(pos.x, pos.y),
+                };
+                Some(UiEvent::Scroll { position: cursor, delta })
+            }
+            WindowEvent::KeyboardInput { event, .. } => Some(match event.state {
+                ElementState::Pressed => UiEvent::KeyDown {
+                    key: event.logical_key.clone(),
+                    modifiers,
+                    repeat: event.repeat,
+                },
+                ElementState::Released => UiEvent::KeyUp { key: event.logical_key.clone(), modifiers },
+            }),
+            WindowEvent::Ime(Ime::Preedit(text, cursor)) => Some(UiEvent::ImePreedit {
+                text: text.clone(),
+                cursor: *cursor,
+            }),
+            WindowEvent::Ime(Ime::Commit(text)) => Some(UiEvent::ImeCommit { text: text.clone() }),
+            // Whatever was being composed is abandoned
+            WindowEvent::Ime(Ime::Disabled) => Some(UiEvent::ImePreedit {
+                text: String::new(),
+                cursor: None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Where the pointer was, for events that are routed by hit-testing.
+    pub fn position(&self) -> Option<Point> {
+        match self {
+            UiEvent::PointerMove { position }
+            | UiEvent::PointerDown { position, .. }
+            | UiEvent::PointerUp { position, .. }
+            | UiEvent::Scroll { position, .. }
+            | UiEvent::DoubleClick { position }
+            | UiEvent::TripleClick { position }
+            | UiEvent::DragStart { position }
+            | UiEvent::DragMove { position, .. }
+            | UiEvent::DragEnd { position }
+            | UiEvent::FileDrop { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the child indices leading from `root` to the deepest widget under
+/// `point`, or `None` if nothing is there.
+///
+/// Overlays (see [`Widget::overlay_bounds`]) are drawn above the whole tree, so
+/// they're checked first. Otherwise children are only searched while the point
+/// lies inside their parent, so anything a container clips away (e.g.
+/// scrolled-out ScrollView content) can't be hit. Siblings are tried top-most
+/// first: highest z-index, then latest in tree order, matching how they're drawn.
+pub fn hit_test(root: &dyn Widget, point: Point) -> Option<Vec<usize>> {
+    if let Some(path) = overlay_hit_test(root, point, &mut Vec::new()) {
+        return Some(path);
+    }
+    if !root.bounds().contains(point) {
+        return None;
+    }
+    let mut path = Vec::new();
+    let mut current = root;
+    'descend: loop {
+        let children = current.children();
+        for index in paint_order(children).into_iter().rev() {
+            if children[index].bounds().contains(point) {
+                path.push(index);
+                current = children[index].as_ref();
+                continue 'descend;
+            }
+        }
+        return Some(path);
+    }
+}
+
+fn overlay_hit_test(widget: &dyn Widget, point: Point, path: &mut Vec<usize>) -> Option<Vec<usize>> {
+    // Deeper overlays are drawn later, so they win
+    let children = widget.children();
+    for index in paint_order(children).into_iter().rev() {
+        path.push(index);
+        if let Some(hit) = overlay_hit_test(children[index].as_ref(), point, path) {
+            return Some(hit);
+        }
+        path.pop();
+    }
+    widget
+        .overlay_bounds()
+        .filter(|bounds| bounds.contains(point))
+        .map(|_| path.clone())
+}
+
+/// Paths to every focusable widget, in tree order. This is the order Tab
+/// moves focus in.
+pub fn focus_order(root: &dyn Widget) -> Vec<Vec<usize>> {
+    fn collect(widget: &dyn Widget, path: &mut Vec<usize>, order: &mut Vec<Vec<usize>>) {
+        if widget.is_focusable() {
+            order.push(path.clone());
+        }
+        for (index, child) in widget.children().iter().enumerate() {
+            path.push(index);
+            collect(child.as_ref(), path, order);
+            path.pop();
+        }
+    }
+    let mut order = Vec::new();
+    collect(root, &mut Vec::new(), &mut order);
+    order
+}
+
+pub(crate) fn widget_ref_at_path<'a>(root: &'a dyn Widget, path: &[usize]) -> Option<&'a dyn Widget> {
+    let mut widget = root;
+    for &index in path {
+        widget = widget.children().get(index)?.as_ref();
+    }
+    Some(widget)
+}
+
+// `None` if the tree changed shape since the path was taken
+pub(crate) fn widget_at_path<'a>(root: &'a mut dyn Widget, path: &[usize]) -> Option<&'a mut dyn Widget> {
+    let mut widget = root;
+    for &index in path {
+        widget = widget.children_mut().get_mut(index)?.as_mut();
+    }
+    Some(widget)
+}
+
+/// Delivers `event` along `path` in two phases:
+///
+/// 1. capture: `capture_event` runs from the root down to the target's parent,
+///    letting a container intercept before its descendants see anything;
+/// 2. bubble: `handle_event` runs on the target and then on every ancestor
+///    back up to the root.
+///
+/// Either phase stops as soon as a widget returns `true`.
+fn route(root: &mut dyn Widget, path: &[usize], event: &UiEvent) -> bool {
+    for depth in 0..path.len() {
+        if widget_at_path(root, &path[..depth]).is_some_and(|widget| widget.capture_event(event)) {
+            return true;
+        }
+    }
+    (0..=path.len())
+        .rev()
+        .any(|depth| widget_at_path(root, &path[..depth]).is_some_and(|widget| widget.handle_event(event)))
+}
+
+fn broadcast(widget: &mut dyn Widget, event: &UiEvent) -> bool {
+    widget.handle_event(event)
+        || widget.children_mut().iter_mut().any(|child| broadcast(child.as_mut(), event))
+}
+
+/// Which way [`EventDispatcher::focus_towards`] looks for the next widget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Turns window events into [`UiEvent`]s and routes them through a widget tree,
+/// remembering what it needs between events (cursor, modifiers, focus).
+///
+/// Pointer events go to the widget under the pointer (see [`hit_test`]) and a
+/// press moves keyboard focus to the deepest focusable widget under it, or
+/// clears it. Keyboard events go to the focused widget and bubble up from
+/// there; with nothing focused they're offered to every widget depth-first
+/// until one consumes them. A Tab that nobody consumes moves focus to the next
+/// focusable widget (Shift-Tab to the previous one), wrapping around.
+///
+/// Gestures recognised from the pointer events are dispatched right after the
+/// event that completed them.
+///
+/// The dispatcher also tracks the path under the pointer. When it changes,
+/// every widget that's no longer on it gets [`UiEvent::PointerLeave`], deepest
+/// first, and then every widget newly on it gets [`UiEvent::PointerEnter`],
+/// outermost first. Moving straight from one widget onto its neighbour leaves
+/// the first before entering the second, while their shared ancestors hear
+/// nothing.
+///
+/// A press can capture the pointer, see [`Widget::captures_pointer`]. Until
+/// that button is released, every pointer event and gesture goes to the
+/// capturing widget wherever the pointer is, and it stays hovered. Capture
+/// only starts and ends in [`Self::handle_event`].
+#[derive(Default)]
+pub struct EventDispatcher {
+    cursor: Point,
+    modifiers: ModifiersState,
+    focused: Option<Vec<usize>>,
+    hovered: Option<Vec<usize>>,
+    // Widget that captured the pointer, and the button it's held for
+    captured: Option<(Vec<usize>, MouseButton)>,
+    gestures: GestureRecognizer,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_gestures(config: GestureConfig) -> Self {
+        Self {
+            gestures: GestureRecognizer::new(config),
+            ..Self::default()
+        }
+    }
+
+    pub fn cursor(&self) -> Point {
+        self.cursor
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Returns whether the tree consumed the event.
+    pub fn handle_window_event(&mut self, root: &mut dyn Widget, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => self.cursor = Point::new(position.x, position.y),
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            // A captured pointer stays on its widget even outside the window
+            WindowEvent::CursorLeft { .. } if self.captured.is_none() => return self.set_hover(root, None),
+            _ => {}
+        }
+        match UiEvent::from_window_event(event, self.cursor, self.modifiers) {
+            Some(ui_event) => self.handle_event(root, &ui_event),
+            None => false,
+        }
+    }
+
+    /// Dispatches `event` followed by any gestures it completes. Returns whether
+    /// the tree consumed any of them.
+    pub fn handle_event(&mut self, root: &mut dyn Widget, event: &UiEvent) -> bool {
+        let mut consumed = self.dispatch(root, event);
+        for gesture in self.gestures.process(event, Instant::now()) {
+            consumed |= self.dispatch(root, &gesture);
+        }
+        // After the gestures, so a drag's end still reaches the captured widget
+        match event {
+            UiEvent::PointerDown { position, button } if self.captured.is_none() => {
+                self.captured = hit_test(root, *position).and_then(|path| {
+                    (0..=path.len())
+                        .rev()
+                        .find(|&depth| widget_ref_at_path(root, &path[..depth]).is_some_and(|w| w.captures_pointer()))
+                        .map(|depth| (path[..depth].to_vec(), *button))
+                });
+            }
+            UiEvent::PointerUp { position, button } if self.captured.as_ref().is_some_and(|(_, held)| held == button) => {
+                self.captured = None;
+                // Hover catches up with wherever the pointer ended up
+                let path = hit_test(root, *position);
+                consumed |= self.set_hover(root, path);
+            }
+            _ => {}
+        }
+        consumed
+    }
+
+    /// Moves the pointer without dispatching anything, for callers that
+    /// deliver the matching `PointerMove` themselves later.
+    pub fn set_cursor(&mut self, cursor: Point) {
+        self.cursor = cursor;
+    }
+
+    /// Like [`Self::set_cursor`], for modifiers tracked elsewhere.
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    /// Routes an already translated event. Returns whether anything consumed it.
+    pub fn dispatch(&mut self, root: &mut dyn Widget, event: &UiEvent) -> bool {
+        if let Some(point) = event.position() {
+            if let UiEvent::PointerDown { button, .. } = event
+                && self.captured.as_ref().is_some_and(|(_, held)| held == button)
+            {
+                // The release never arrived, e.g. because a modal took it
+                self.captured = None;
+            }
+            let path = match &self.captured {
+                Some((path, _)) => Some(path.clone()),
+                None => hit_test(root, point),
+            };
+            // Gestures report where they started, not where the pointer is now
+            let tracks_pointer = matches!(
+                event,
+                UiEvent::PointerMove { .. }
+                    | UiEvent::PointerDown { .. }
+                    | UiEvent::PointerUp { .. }
+                    | UiEvent::Scroll { .. }
+            );
+            let hover_consumed = tracks_pointer && self.set_hover(root, path.clone());
+            if matches!(event, UiEvent::PointerDown { .. }) {
+                let focus = path.as_deref().and_then(|path| deepest_focusable(root, path));
+                self.set_focus(root, focus);
+            }
+            return path.is_some_and(|path| route(root, &path, event)) || hover_consumed;
+        }
+
+        let consumed = match self.focused.clone() {
+            Some(path) => route(root, &path, event),
+            None => broadcast(root, event),
+        };
+        if !consumed
+            && let UiEvent::KeyDown { key: Key::Named(NamedKey::Tab), modifiers, .. } = event
+        {
+            self.focus_next(root, modifiers.shift_key());
+            return true;
+        }
+        consumed
+    }
+
+    pub fn focused(&self) -> Option<&[usize]> {
+        self.focused.as_deref()
+    }
+
+    /// Path to the deepest widget under the pointer, as of the last pointer event.
+    pub fn hovered(&self) -> Option<&[usize]> {
+        self.hovered.as_deref()
+    }
+
+    /// Path to the widget that has captured the pointer, if one has.
+    pub fn captured(&self) -> Option<&[usize]> {
+        self.captured.as_ref().map(|(path, _)| path.as_slice())
+    }
+
+    /// Bounds of the focused widget, for drawing a focus ring.
+    pub fn focused_bounds(&self, root: &dyn Widget) -> Option<Rect> {
+        widget_ref_at_path(root, self.focused.as_deref()?).map(|widget| widget.bounds())
+    }
+
+    /// Where the focused widget wants input method text, if it takes any. See
+    /// [`Widget::ime_cursor_area`].
+    pub fn focused_ime_area(&self, root: &dyn Widget) -> Option<Rect> {
+        widget_ref_at_path(root, self.focused.as_deref()?).and_then(|widget| widget.ime_cursor_area())
+    }
+
+    /// Moves focus to the next focusable widget in tree order, or the previous
+    /// one when `reverse`, wrapping around at either end.
+    pub fn focus_next(&mut self, root: &mut dyn Widget, reverse: bool) {
+        let order = focus_order(root);
+        if order.is_empty() {
+            return;
+        }
+        let current = self.focused.as_ref().and_then(|focused| order.iter().position(|path| path == focused));
+        let next = match (current, reverse) {
+            (Some(index), false) => (index + 1) % order.len(),
+            (Some(index), true) => (index + order.len() - 1) % order.len(),
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+        };
+        self.set_focus(root, Some(order[next].clone()));
+    }
+
+    /// Moves focus to the nearest focusable widget in `direction` from the
+    /// focused one, as with arrow keys or a d-pad, comparing the centres of
+    /// their bounds. Widgets in line count as nearer than ones off to the side.
+    /// With nothing focused, focuses the first widget. Returns whether focus
+    /// moved.
+    pub fn focus_towards(&mut self, root: &mut dyn Widget, direction: FocusDirection) -> bool {
+        let Some(from) = self.focused_bounds(root).map(|bounds| bounds.center()) else {
+            let had_focus = self.focused.is_some();
+            self.focus_next(root, false);
+            return self.focused.is_some() != had_focus;
+        };
+        let nearest = focus_order(root)
+            .into_iter()
+            .filter(|path| Some(path) != self.focused.as_ref())
+            .filter_map(|path| {
+                let delta = widget_ref_at_path(root, &path)?.bounds().center() - from;
+                let (along, across) = match direction {
+                    FocusDirection::Up => (-delta.y, delta.x),
+                    FocusDirection::Down => (delta.y, delta.x),
+                    FocusDirection::Left => (-delta.x, delta.y),
+                    FocusDirection::Right => (delta.x, delta.y),
+                };
+                // Only widgets that are actually that way
+                (along > 0.0).then(|| (along + across.abs() * 2.0, path))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        match nearest {
+            Some((_, path)) => {
+                self.set_focus(root, Some(path));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The cursor the widget under the pointer asks for, falling back through
+    /// its ancestors to the default arrow.
+    pub fn cursor_icon(&self, root: &dyn Widget) -> CursorIcon {
+        let Some(path) = hit_test(root, self.cursor) else {
+            return CursorIcon::Default;
+        };
+        let mut widgets = vec![root];
+        for &index in &path {
+            let parent = widgets[widgets.len() - 1];
+            widgets.push(parent.children()[index].as_ref());
+        }
+        widgets
+            .iter()
+            .rev()
+            .find_map(|widget| widget.cursor_icon())
+            .unwrap_or_default()
+    }
+
+    /// The tooltip of the hovered widget, falling back through its ancestors.
+    pub fn hovered_tooltip<'a>(&self, root: &'a dyn Widget) -> Option<&'a str> {
+        let path = self.hovered.as_deref()?;
+        (0..=path.len())
+            .rev()
+            .find_map(|depth| widget_ref_at_path(root, &path[..depth]).and_then(|widget| widget.tooltip()))
+    }
+
+    /// Moves focus to the widget at `path`, telling the old and new widgets.
+    pub fn set_focus(&mut self, root: &mut dyn Widget, path: Option<Vec<usize>>) {
+        if self.focused == path {
+            return;
+        }
+        if let Some(old) = self.focused.take()
+            && let Some(widget) = widget_at_path(root, &old)
+        {
+            widget.handle_event(&UiEvent::FocusLost);
+        }
+        if let Some(new) = &path
+            && let Some(widget) = widget_at_path(root, new)
+        {
+            widget.handle_event(&UiEvent::FocusGained);
+        }
+        self.focused = path;
+    }
+
+    // Sends leave and enter events for whatever differs between the old and new
+    // hovered paths. Returns whether any widget asked for a redraw.
+    fn set_hover(&mut self, root: &mut dyn Widget, path: Option<Vec<usize>>) -> bool {
+        if self.hovered == path {
+            return false;
+        }
+        let old = self.hovered.take();
+        // Widgets on both paths stay hovered. Depth 0 is the root itself.
+        let shared = match (&old, &path) {
+            (Some(old), Some(new)) => 1 + old.iter().zip(new).take_while(|(a, b)| a == b).count(),
+            _ => 0,
+        };
+        let mut consumed = false;
+        if let Some(old) = &old {
+            for depth in (shared..=old.len()).rev() {
+                if let Some(widget) = widget_at_path(root, &old[..depth]) {
+                    consumed |= widget.handle_event(&UiEvent::PointerLeave);
+                }
+            }
+        }
+        if let Some(new) = &path {
+            for depth in shared..=new.len() {
+                if let Some(widget) = widget_at_path(root, &new[..depth]) {
+                    consumed |= widget.handle_event(&UiEvent::PointerEnter);
+                }
+            }
+        }
+        self.hovered = path;
+        consumed
+    }
+}
+
+fn deepest_focusable(root: &mut dyn Widget, path: &[usize]) -> Option<Vec<usize>> {
+    (0..=path.len())
+        .rev()
+        .find(|&depth| widget_at_path(root, &path[..depth]).is_some_and(|widget| widget.is_focusable()))
+        .map(|depth| path[..depth].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::components::test_widget::Block;
+    use crate::components::{Button, LayoutCtx};
+    use crate::layout::{HStack, ScrollView, VStack, ZStack};
+    use crate::theme::Theme;
+
+    fn click(events: &mut EventDispatcher, root: &mut dyn Widget, position: Point) {
+        events.handle_event(root, &UiEvent::PointerDown { position, button: MouseButton::Left });
+        events.handle_event(root, &UiEvent::PointerUp { position, button: MouseButton::Left });
+    }
+
+    #[test]
+    fn hit_test_follows_scrolled_content() {
+        let theme = Theme::default();
+        let ctx = LayoutCtx { theme: &theme };
+        let rows = (0..10).fold(VStack::new().with_spacing(0.0), |rows, row| rows.child(Button::new(&format!("Row {row}"))));
+        let mut root = ScrollView::new(rows);
+        let view = Rect::new(0.0, 0.0, 200.0, 100.0);
+        root.layout(&ctx, view);
+        let row_height = root.children()[0].children()[0].bounds().height();
+        let point = Point::new(50.0, row_height / 2.0);
+        assert_eq!(hit_test(&root, point), Some(vec![0, 0]));
+
+        let mut events = EventDispatcher::new();
+        let scroll = UiEvent::Scroll { position: point, delta: Vec2::new(0.0, -2.0 * row_height) };
+        assert!(events.handle_event(&mut root, &scroll));
+        root.layout(&ctx, view);
+        assert_eq!(hit_test(&root, point), Some(vec![0, 2]));
+        // Rows pushed below the view are clipped away
+        assert_eq!(hit_test(&root, Point::new(50.0, view.y1 + row_height / 2.0)), None);
+    }
+
+    #[test]
+    fn click_reaches_the_top_most_of_overlapping_widgets() {
+        let theme = Theme::default();
+        let ctx = LayoutCtx { theme: &theme };
+        let (top_clicks, bottom_clicks) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+        let top = Button::new("Top").with_z_index(1).on_click({
+            let clicks = top_clicks.clone();
+            move || clicks.set(clicks.get() + 1)
+        });
+        // Later in tree order, which would put it on top at the same z-index
+        let bottom = Button::new("Bottom").on_click({
+            let clicks = bottom_clicks.clone();
+            move || clicks.set(clicks.get() + 1)
+        });
+        let mut root = ZStack::new().child(top).child(bottom);
+        root.layout(&ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+
+        let point = Point::new(50.0, 50.0);
+        assert_eq!(hit_test(&root, point), Some(vec![0]));
+        click(&mut EventDispatcher::new(), &mut root, point);
+        assert_eq!((top_clicks.get(), bottom_clicks.get()), (1, 0));
+    }
+
+    #[test]
+    fn captured_pointer_keeps_its_widget_outside_its_bounds() {
+        let theme = Theme::default();
+        let ctx = LayoutCtx { theme: &theme };
+        let (dragged, other) = (Block::new(50.0, 100.0).capturing(), Block::new(50.0, 100.0));
+        let (dragged_events, other_events) = (dragged.events(), other.events());
+        let mut root = HStack::new().with_spacing(0.0).child(dragged).child(other);
+        root.layout(&ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+
+        let mut events = EventDispatcher::new();
+        let (inside, outside) = (Point::new(25.0, 50.0), Point::new(75.0, 150.0));
+        events.handle_event(&mut root, &UiEvent::PointerDown { position: inside, button: MouseButton::Left });
+        assert_eq!(events.captured(), Some(&[0][..]));
+        other_events.borrow_mut().clear();
+        dragged_events.borrow_mut().clear();
+
+        // Over the other block, and then outside the window altogether
+        let moves = [Point::new(75.0, 50.0), outside];
+        for position in moves {
+            events.handle_event(&mut root, &UiEvent::PointerMove { position });
+        }
+        events.handle_event(&mut root, &UiEvent::PointerUp { position: outside, button: MouseButton::Left });
+        assert_eq!(events.captured(), None);
+        // Along with the drag gestures the moves make
+        let pointer_events: Vec<_> = dragged_events
+            .borrow()
+            .iter()
+            .filter(|event| matches!(event, UiEvent::PointerMove { .. } | UiEvent::PointerUp { .. } | UiEvent::DragEnd { .. }))
+            .cloned()
+            .collect();
+        assert_eq!(
+            pointer_events,
+            [
+                UiEvent::PointerMove { position: moves[0] },
+                UiEvent::PointerMove { position: moves[1] },
+                UiEvent::PointerUp { position: outside, button: MouseButton::Left },
+                UiEvent::DragEnd { position: outside },
+            ]
+        );
+        assert!(other_events.borrow().is_empty(), "{:?}", other_events.borrow());
+    }
+
+    #[test]
+    fn cursor_icon_falls_back_through_ancestors() {
+        let theme = Theme::default();
+        let ctx = LayoutCtx { theme: &theme };
+        let row = HStack::new().with_spacing(0.0).child(Button::new("Button")).child(Block::new(50.0, 100.0));
+        let mut root = Block::new(200.0, 100.0).with_cursor(CursorIcon::Crosshair).with_child(row);
+        root.layout(&ctx, Rect::new(0.0, 0.0, 200.0, 100.0));
+        let button = root.children()[0].children()[0].bounds();
+
+        let mut events = EventDispatcher::new();
+        let cursor_at = |events: &mut EventDispatcher, point| {
+            events.set_cursor(point);
+            events.cursor_icon(&root)
+        };
+        assert_eq!(cursor_at(&mut events, button.center()), CursorIcon::Pointer);
+        // Neither the block nor the row ask for one, but the root does
+        assert_eq!(cursor_at(&mut events, Point::new(button.x1 + 10.0, 50.0)), CursorIcon::Crosshair);
+        assert_eq!(cursor_at(&mut events, Point::new(250.0, 50.0)), CursorIcon::Default);
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+use std::str::FromStr;
+
+use winit::keyboard::{Key, ModifiersState, NamedKey, SmolStr};
+
+/// A key together with the exact modifiers that must be held with it, e.g.
+/// Ctrl+Shift+P.
+///
+/// Character keys match the character typed, ignoring case, so Ctrl+Shift+P
+/// still matches when Shift turns the key into `P`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyCombination {
+    key: Key,
+    modifiers: ModifiersState,
+}
+
+impl KeyCombination {
+    pub fn new(key: Key, modifiers: ModifiersState) -> Self {
+        Self {
+            key: normalize(&key),
+            modifiers,
+        }
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Whether pressing `key` with `modifiers` held triggers this combination.
+    pub fn matches(&self, key: &Key, modifiers: ModifiersState) -> bool {
+        self.modifiers == modifiers && self.key == normalize(key)
+    }
+}
+
+// Character keys are stored and compared lowercase
+fn normalize(key: &Key) -> Key {
+    match key {
+        Key::Character(text) => Key::Character(SmolStr::new(text.to_lowercase())),
+        other => other.clone(),
+    }
+}
+
+impl FromStr for KeyCombination {
+    type Err = anyhow::Error;
+
+    /// Parses `+`-separated modifiers followed by a key, like `"Ctrl+S"`,
+    /// `"Alt+F4"` or `"Ctrl+Shift+ArrowUp"`. Names are case-insensitive.
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        let mut modifiers = ModifiersState::empty();
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        // "Ctrl++" binds the plus key itself
+        if text.trim_end().ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let Some((key, modifier_names)) = parts.split_last().filter(|(key, _)| !key.is_empty()) else {
+            anyhow::bail!("no key in {:?}", text);
+        };
+        for name in modifier_names {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "shift" => ModifiersState::SHIFT,
+                "alt" | "option" => ModifiersState::ALT,
+                "super" | "cmd" | "command" | "meta" | "win" => ModifiersState::SUPER,
+                _ => anyhow::bail!("unknown modifier {:?} in {:?}", name, text),
+            };
+        }
+        let key = parse_key(key).ok_or_else(|| anyhow::anyhow!("unknown key {:?} in {:?}", key, text))?;
+        Ok(Self::new(key, modifiers))
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(_), None) = (chars.next(), chars.next()) {
+        return Some(Key::Character(SmolStr::new(name)));
+    }
+    let lower = name.to_ascii_lowercase();
+    if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return function_key(number).map(Key::Named);
+    }
+    let named = match lower.as_str() {
+        "enter" | "return" => NamedKey::Enter,
+        "escape" | "esc" => NamedKey::Escape,
+        "tab" => NamedKey::Tab,
+        "space" => NamedKey::Space,
+        "backspace" => NamedKey::Backspace,
+        "delete" | "del" => NamedKey::Delete,
+        "insert" => NamedKey::Insert,
+        "home" => NamedKey::Home,
+        "end" => NamedKey::End,
+        "pageup" => NamedKey::PageUp,
+        "pagedown" => NamedKey::PageDown,
+        "arrowup" | "up" => NamedKey::ArrowUp,
+        "arrowdown" | "down" => NamedKey::ArrowDown,
+        "arrowleft" | "left" => NamedKey::ArrowLeft,
+        "arrowright" | "right" => NamedKey::ArrowRight,
+        _ => return None,
+    };
+    Some(Key::Named(named))
+}
+
+fn function_key(number: u8) -> Option<NamedKey> {
+    const KEYS: [NamedKey; 12] = [
+        NamedKey::F1,
+        NamedKey::F2,
+        NamedKey::F3,
+        NamedKey::F4,
+        NamedKey::F5,
+        NamedKey::F6,
+        NamedKey::F7,
+        NamedKey::F8,
+        NamedKey::F9,
+        NamedKey::F10,
+        NamedKey::F11,
+        NamedKey::F12,
+    ];
+    KEYS.get(usize::from(number).checked_sub(1)?).copied()
+}
+
+impl fmt::Display for KeyCombination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (ModifiersState::CONTROL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::SUPER, "Super"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match &self.key {
+            Key::Character(text) => write!(f, "{}", text.to_uppercase()),
+            Key::Named(named) => write!(f, "{:?}", named),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use vello::kurbo::{Rect, Size};
+
+use crate::components::{paint_order, DrawCtx, LayoutCtx, Widget};
+
+/// Size of one grid column or row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Track {
+    /// Exactly this many pixels.
+    Fixed(f64),
+    /// A share of whatever the fixed tracks and gaps leave over, like CSS `fr`.
+    Fr(f64),
+}
+
+// Which tracks a child covers
+#[derive(Clone, Copy)]
+struct Cell {
+    col: usize,
+    row: usize,
+    col_span: usize,
+    row_span: usize,
+}
+
+/// Places children into the cells of a grid of column and row tracks.
+///
+/// Children fill their cell, which may span several tracks. When laid out,
+/// `Fr` tracks share the space left in the parent's rect; when measured, each
+/// `Fr` track is as big as the largest single-track child asks it to be.
+pub struct Grid {
+    columns: Vec<Track>,
+    rows: Vec<Track>,
+    // Falls back to the theme's spacing
+    gap: Option<f64>,
+    children: Vec<Box<dyn Widget>>,
+    cells: Vec<Cell>,
+    needs_layout: bool,
+    bounds: Rect,
+}
+
+impl Grid {
+    pub fn new(columns: impl Into<Vec<Track>>, rows: impl Into<Vec<Track>>) -> Self {
+        Self {
+            columns: columns.into(),
+            rows: rows.into(),
+            gap: None,
+            children: Vec::new(),
+            cells: Vec::new(),
+            needs_layout: true,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// Space between adjacent columns and rows.
+    pub fn with_gap(mut self, gap: f64) -> Self {
+        self.gap = Some(gap);
+        self
+    }
+
+    pub fn child_at(self, col: usize, row: usize, child: impl Widget + 'static) -> Self {
+        self.child_spanning(col, row, 1, 1, child)
+    }
+
+    /// Places `child` over `col_span` columns and `row_span` rows starting at
+    /// (`col`, `row`). Spans running past the last track are cut short.
+    pub fn child_spanning(
+        mut self,
+        col: usize,
+        row: usize,
+        col_span: usize,
+        row_span: usize,
+        child: impl Widget + 'static,
+    ) -> Self {
+        if col >= self.columns.len() || row >= self.rows.len() {
+            log::warn!(
+                "Grid cell ({}, {}) is outside the {}x{} grid; the child won't be shown",
+                col,
+                row,
+                self.columns.len(),
+                self.rows.len()
+            );
+        }
+        self.cells.push(Cell {
+            col,
+            row,
+            col_span: col_span.max(1),
+            row_span: row_span.max(1),
+        });
+        self.children.push(Box::new(child));
+        self.needs_layout = true;
+        self
+    }
+}
+
+// Track sizes along one axis. `fr_unit` is the size of `Fr(1.0)`.
+fn track_sizes(tracks: &[Track], fr_unit: f64) -> Vec<f64> {
+    tracks
+        .iter()
+        .map(|track| match *track {
+            Track::Fixed(size) => size,
+            Track::Fr(fr) => fr * fr_unit,
+        })
+        .collect()
+}
+
+// Size of `Fr(1.0)` when the tracks have to fill `extent`
+fn fr_unit_within(tracks: &[Track], gap: f64, extent: f64) -> f64 {
+    let mut fixed = gap * tracks.len().saturating_sub(1) as f64;
+    let mut total_fr = 0.0;
+    for track in tracks {
+        match *track {
+            Track::Fixed(size) => fixed += size,
+            Track::Fr(fr) => total_fr += fr,
+        }
+    }
+    if total_fr > 0.0 {
+        (extent - fixed).max(0.0) / total_fr
+    } else {
+        0.0
+    }
+}
+
+// Where each track starts
+fn track_starts(sizes: &[f64], gap: f64, start: f64) -> Vec<f64> {
+    let mut cursor = start;
+    sizes
+        .iter()
+        .map(|size| {
+            let track_start = cursor;
+            cursor += size + gap;
+            track_start
+        })
+        .collect()
+}
+
+fn total(sizes: &[f64], gap: f64) -> f64 {
+    sizes.iter().sum::<f64>() + gap * sizes.len().saturating_sub(1) as f64
+}
+
+impl Widget for Grid {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        let gap = self.gap.unwrap_or(ctx.theme.spacing);
+        let mut col_unit: f64 = 0.0;
+        let mut row_unit: f64 = 0.0;
+        // Spanning children don't size flexible tracks, they just get what the tracks add up to
+        for (child, cell) in self.children.iter_mut().zip(&self.cells) {
+            let size = child.measure(ctx, available);
+            if cell.col_span == 1 && let Some(Track::Fr(fr)) = self.columns.get(cell.col) && *fr > 0.0 {
+                col_unit = col_unit.max(size.width / fr);
+            }
+            if cell.row_span == 1 && let Some(Track::Fr(fr)) = self.rows.get(cell.row) && *fr > 0.0 {
+                row_unit = row_unit.max(size.height / fr);
+            }
+        }
+        Size::new(
+            total(&track_sizes(&self.columns, col_unit), gap),
+            total(&track_sizes(&self.rows, row_unit), gap),
+        )
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.needs_layout = false;
+        let gap = self.gap.unwrap_or(ctx.theme.spacing);
+        let col_unit = fr_unit_within(&self.columns, gap, bounds.width());
+        let row_unit = fr_unit_within(&self.rows, gap, bounds.height());
+        let widths = track_sizes(&self.columns, col_unit);
+        let heights = track_sizes(&self.rows, row_unit);
+        let xs = track_starts(&widths, gap, bounds.x0);
+        let ys = track_starts(&heights, gap, bounds.y0);
+
+        for (child, cell) in self.children.iter_mut().zip(&self.cells) {
+            if cell.col >= self.columns.len() || cell.row >= self.rows.len() {
+                child.layout(ctx, Rect::ZERO);
+                continue;
+            }
+            let last_col = (cell.col + cell.col_span).min(self.columns.len()) - 1;
+            let last_row = (cell.row + cell.row_span).min(self.rows.len()) - 1;
+            let rect = Rect::new(
+                xs[cell.col],
+                ys[cell.row],
+                xs[last_col] + widths[last_col],
+                ys[last_row] + heights[last_row],
+            );
+            child.layout(ctx, rect);
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let order = paint_order(&self.children);
+        let children = &mut self.children;
+        ctx.with_clip(&self.bounds, |ctx| {
+            for index in order {
+                ctx.draw_child(children[index].as_mut());
+            }
+        });
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut self.children
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.needs_layout || self.children.iter().any(|child| child.needs_layout())
+    }
+}
@@ -1 +1,486 @@
+use vello::kurbo::{Affine, Rect, RoundedRect, Size};
+use vello::peniko::Fill;
+
+use crate::components::{paint_order, DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+mod grid;
+mod split_view;
+mod virtual_list;
+
+pub use grid::{Grid, Track};
+pub use split_view::SplitView;
+pub use virtual_list::VirtualList;
+
 pub struct LayoutEngine;
+
+#[cfg(debug_assertions)]
+thread_local! {
+    // Per thread, so apps on other threads (and tests) don't count towards each other
+    static LAYOUT_PASSES: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+}
+
+/// How many times the app on this thread has laid its widget tree out. Debug
+/// builds only, for checking that idle frames don't re-run layout.
+#[cfg(debug_assertions)]
+pub fn layout_passes() -> u64 {
+    LAYOUT_PASSES.get()
+}
+
+pub(crate) fn count_layout_pass() {
+    #[cfg(debug_assertions)]
+    LAYOUT_PASSES.set(LAYOUT_PASSES.get() + 1);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+// Shared implementation behind VStack and HStack
+struct Stack {
+    axis: Axis,
+    // Falls back to the theme's spacing
+    spacing: Option<f64>,
+    children: Vec<Box<dyn Widget>>,
+    // Children were added or removed since the last layout
+    needs_layout: bool,
+    bounds: Rect,
+}
+
+impl Stack {
+    fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            spacing: None,
+            children: Vec::new(),
+            needs_layout: true,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    fn push(&mut self, child: Box<dyn Widget>) {
+        self.children.push(child);
+        self.needs_layout = true;
+    }
+
+    fn remove(&mut self, index: usize) -> Box<dyn Widget> {
+        self.needs_layout = true;
+        self.children.remove(index)
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.needs_layout || self.children.iter().any(|child| child.needs_layout())
+    }
+
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        let spacing = self.spacing.unwrap_or(ctx.theme.spacing);
+        let gaps = spacing * self.children.len().saturating_sub(1) as f64;
+        let mut main = gaps;
+        let mut cross: f64 = 0.0;
+        for child in &mut self.children {
+            let size = child.measure(ctx, available);
+            match self.axis {
+                Axis::Vertical => {
+                    main += size.height;
+                    cross = cross.max(size.width);
+                }
+                Axis::Horizontal => {
+                    main += size.width;
+                    cross = cross.max(size.height);
+                }
+            }
+        }
+        match self.axis {
+            Axis::Vertical => Size::new(cross, main),
+            Axis::Horizontal => Size::new(main, cross),
+        }
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.needs_layout = false;
+        let spacing = self.spacing.unwrap_or(ctx.theme.spacing);
+        let mut cursor = match self.axis {
+            Axis::Vertical => bounds.y0,
+            Axis::Horizontal => bounds.x0,
+        };
+        for child in &mut self.children {
+            // Children take their measured extent along the stack axis and fill the cross axis
+            let size = child.measure(ctx, bounds.size());
+            let rect = match self.axis {
+                Axis::Vertical => Rect::new(bounds.x0, cursor, bounds.x1, cursor + size.height),
+                Axis::Horizontal => Rect::new(cursor, bounds.y0, cursor + size.width, bounds.y1),
+            };
+            child.layout(ctx, rect);
+            cursor += match self.axis {
+                Axis::Vertical => size.height,
+                Axis::Horizontal => size.width,
+            } + spacing;
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let order = paint_order(&self.children);
+        let children = &mut self.children;
+        ctx.with_clip(&self.bounds, |ctx| {
+            for index in order {
+                ctx.draw_child(children[index].as_mut());
+            }
+        });
+    }
+}
+
+/// Lays its children out top to bottom.
+pub struct VStack(Stack);
+
+impl Default for VStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VStack {
+    pub fn new() -> Self {
+        Self(Stack::new(Axis::Vertical))
+    }
+
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.0.spacing = Some(spacing);
+        self
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.0.push(Box::new(child));
+        self
+    }
+
+    pub fn push(&mut self, child: impl Widget + 'static) {
+        self.0.push(Box::new(child));
+    }
+
+    /// Panics if `index` is out of bounds, like `Vec::remove`.
+    pub fn remove(&mut self, index: usize) -> Box<dyn Widget> {
+        self.0.remove(index)
+    }
+
+    // For rebuilding the children wholesale, which always needs a layout pass
+    pub(crate) fn children_vec(&mut self) -> &mut Vec<Box<dyn Widget>> {
+        self.0.needs_layout = true;
+        &mut self.0.children
+    }
+}
+
+impl Widget for VStack {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size { self.0.measure(ctx, available) }
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) { self.0.layout(ctx, bounds) }
+    fn bounds(&self) -> Rect { self.0.bounds }
+    fn draw(&mut self, ctx: &mut DrawCtx) { self.0.draw(ctx) }
+    fn children(&self) -> &[Box<dyn Widget>] { &self.0.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.0.children }
+    fn needs_layout(&self) -> bool { self.0.needs_layout() }
+}
+
+/// Lays its children out left to right.
+pub struct HStack(Stack);
+
+impl Default for HStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HStack {
+    pub fn new() -> Self {
+        Self(Stack::new(Axis::Horizontal))
+    }
+
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.0.spacing = Some(spacing);
+        self
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.0.push(Box::new(child));
+        self
+    }
+
+    pub fn push(&mut self, child: impl Widget + 'static) {
+        self.0.push(Box::new(child));
+    }
+
+    /// Panics if `index` is out of bounds, like `Vec::remove`.
+    pub fn remove(&mut self, index: usize) -> Box<dyn Widget> {
+        self.0.remove(index)
+    }
+
+    // See `VStack::children_vec`
+    pub(crate) fn children_vec(&mut self) -> &mut Vec<Box<dyn Widget>> {
+        self.0.needs_layout = true;
+        &mut self.0.children
+    }
+}
+
+impl Widget for HStack {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size { self.0.measure(ctx, available) }
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) { self.0.layout(ctx, bounds) }
+    fn bounds(&self) -> Rect { self.0.bounds }
+    fn draw(&mut self, ctx: &mut DrawCtx) { self.0.draw(ctx) }
+    fn children(&self) -> &[Box<dyn Widget>] { &self.0.children }
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] { &mut self.0.children }
+    fn needs_layout(&self) -> bool { self.0.needs_layout() }
+}
+
+/// Layers its children on top of each other, each filling the whole bounds.
+/// Which one ends up on top is decided by z-index, then tree order.
+pub struct ZStack {
+    children: Vec<Box<dyn Widget>>,
+    needs_layout: bool,
+    bounds: Rect,
+}
+
+impl Default for ZStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZStack {
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            needs_layout: true,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn child(mut self, child: impl Widget + 'static) -> Self {
+        self.push(child);
+        self
+    }
+
+    pub fn push(&mut self, child: impl Widget + 'static) {
+        self.children.push(Box::new(child));
+        self.needs_layout = true;
+    }
+
+    /// Panics if `index` is out of bounds, like `Vec::remove`.
+    pub fn remove(&mut self, index: usize) -> Box<dyn Widget> {
+        self.needs_layout = true;
+        self.children.remove(index)
+    }
+}
+
+impl Widget for ZStack {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        self.children.iter_mut().fold(Size::ZERO, |size, child| {
+            let child = child.measure(ctx, available);
+            Size::new(size.width.max(child.width), size.height.max(child.height))
+        })
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.needs_layout = false;
+        for child in &mut self.children {
+            child.layout(ctx, bounds);
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let order = paint_order(&self.children);
+        let children = &mut self.children;
+        ctx.with_clip(&self.bounds, |ctx| {
+            for index in order {
+                ctx.draw_child(children[index].as_mut());
+            }
+        });
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut self.children
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.needs_layout || self.children.iter().any(|child| child.needs_layout())
+    }
+}
+
+const SCROLLBAR_WIDTH: f64 = 4.0;
+
+/// Shows a window onto a single, possibly taller, child.
+pub struct ScrollView {
+    child: Box<dyn Widget>,
+    scroll_offset: f64,
+    // The offset the child was last laid out at
+    laid_out_offset: Option<f64>,
+    content_height: f64,
+    bounds: Rect,
+}
+
+impl ScrollView {
+    pub fn new(child: impl Widget + 'static) -> Self {
+        Self {
+            child: Box::new(child),
+            scroll_offset: 0.0,
+            laid_out_offset: None,
+            content_height: 0.0,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    pub fn scroll_offset(&self) -> f64 {
+        self.scroll_offset
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_scroll_offset(&mut self, offset: f64) {
+        self.scroll_offset = offset.clamp(0.0, self.max_offset());
+    }
+
+    fn max_offset(&self) -> f64 {
+        (self.content_height - self.bounds.height()).max(0.0)
+    }
+
+}
+
+impl Widget for ScrollView {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        // Happy to take whatever we're given; the content scrolls within it
+        let content = self.child.measure(ctx, Size::new(available.width, f64::INFINITY));
+        Size::new(content.width.min(available.width), content.height.min(available.height))
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.content_height = self.child.measure(ctx, Size::new(bounds.width(), f64::INFINITY)).height;
+        // Content may have shrunk since the last layout
+        self.scroll_offset = self.scroll_offset.clamp(0.0, self.max_offset());
+        self.laid_out_offset = Some(self.scroll_offset);
+        let top = bounds.y0 - self.scroll_offset;
+        self.child.layout(ctx, Rect::new(bounds.x0, top, bounds.x1, top + self.content_height));
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let child = &mut self.child;
+        ctx.with_clip(&self.bounds, |ctx| ctx.draw_child(child.as_mut()));
+
+        let view_height = self.bounds.height();
+        if self.content_height > view_height {
+            let thumb_height = view_height * view_height / self.content_height;
+            let thumb_top = self.bounds.y0 + (view_height - thumb_height) * self.scroll_offset / self.max_offset();
+            let thumb = RoundedRect::new(
+                self.bounds.x1 - SCROLLBAR_WIDTH,
+                thumb_top,
+                self.bounds.x1,
+                thumb_top + thumb_height,
+                SCROLLBAR_WIDTH / 2.0,
+            );
+            let color = ctx.theme.foreground.with_alpha(0.4);
+            ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &thumb);
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        std::slice::from_ref(&self.child)
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        std::slice::from_mut(&mut self.child)
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.laid_out_offset != Some(self.scroll_offset) || self.child.needs_layout()
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::ScrollView);
+        node.set_scroll_y(self.scroll_offset);
+        node.set_scroll_y_min(0.0);
+        node.set_scroll_y_max(self.max_offset());
+        Some(node)
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        // Nested scroll views get the event first while it bubbles up, and let
+        // it carry on to the outer one once they can't scroll any further
+        match event {
+            UiEvent::Scroll { position, delta } if self.bounds.contains(*position) => {
+                let before = self.scroll_offset;
+                self.set_scroll_offset(self.scroll_offset - delta.y);
+                self.scroll_offset != before
+            }
+            _ => false,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.scroll_offset.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        // Clamped by the next layout, once the content's height is known again
+        if let Some(offset) = state.as_f64() {
+            self.scroll_offset = offset.max(0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vello::kurbo::{Point, Vec2};
+
+    use super::*;
+    use crate::components::test_widget::Block;
+    use crate::theme::Theme;
+
+    fn scroll(by: f64) -> UiEvent {
+        UiEvent::Scroll { position: Point::new(10.0, 10.0), delta: Vec2::new(0.0, by) }
+    }
+
+    #[test]
+    fn scroll_view_lets_scrolls_past_its_ends_bubble() {
+        let theme = Theme::default();
+        let ctx = LayoutCtx { theme: &theme };
+        let mut view = ScrollView::new(Block::new(100.0, 300.0));
+        view.layout(&ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+
+        assert!(!view.handle_event(&scroll(40.0)), "already at the top");
+        assert!(view.handle_event(&scroll(-150.0)));
+        assert_eq!(view.scroll_offset(), 150.0);
+        assert!(view.handle_event(&scroll(-150.0)), "moves the rest of the way");
+        assert_eq!(view.scroll_offset(), 200.0);
+        assert!(!view.handle_event(&scroll(-10.0)), "already at the bottom");
+    }
+
+    #[test]
+    fn scroll_view_restores_its_offset_before_layout() {
+        let theme = Theme::default();
+        let ctx = LayoutCtx { theme: &theme };
+        let mut view = ScrollView::new(Block::new(100.0, 300.0));
+        view.restore_state(&150.0.into());
+        assert!(view.needs_layout());
+        view.layout(&ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert_eq!(view.scroll_offset(), 150.0);
+
+        view.restore_state(&1000.0.into());
+        view.layout(&ctx, Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert_eq!(view.scroll_offset(), 200.0, "clamped once the content is measured");
+    }
+}
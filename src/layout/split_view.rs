@@ -0,0 +1,260 @@
+use vello::kurbo::{Affine, Line, Point, Rect, Size};
+use vello::peniko::Fill;
+use winit::event::MouseButton;
+use winit::window::CursorIcon;
+
+use super::Axis;
+use crate::components::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+
+// Thickness of the strip between the panes that can be dragged
+const DIVIDER_SIZE: f64 = 6.0;
+
+type ResizeHandler = Box<dyn FnMut(f64)>;
+
+/// Two panes side by side, or one above the other, with a divider between
+/// them that the user drags to share the space differently.
+///
+/// The split ratio is the first pane's share of the space the divider
+/// leaves, kept within each pane's minimum and maximum size.
+pub struct SplitView {
+    axis: Axis,
+    panes: [Box<dyn Widget>; 2],
+    ratio: f64,
+    // The ratio the panes were last laid out at
+    laid_out_ratio: Option<f64>,
+    min_sizes: [f64; 2],
+    max_sizes: [f64; 2],
+    // Where along the divider the drag grabbed it, while dragging
+    dragging: Option<f64>,
+    hovered: bool,
+    on_resize: Option<ResizeHandler>,
+    bounds: Rect,
+}
+
+impl SplitView {
+    /// `first` on the left and `second` on the right.
+    pub fn horizontal(first: impl Widget + 'static, second: impl Widget + 'static) -> Self {
+        Self::new(Axis::Horizontal, Box::new(first), Box::new(second))
+    }
+
+    /// `first` on top and `second` below.
+    pub fn vertical(first: impl Widget + 'static, second: impl Widget + 'static) -> Self {
+        Self::new(Axis::Vertical, Box::new(first), Box::new(second))
+    }
+
+    fn new(axis: Axis, first: Box<dyn Widget>, second: Box<dyn Widget>) -> Self {
+        Self {
+            axis,
+            panes: [first, second],
+            ratio: 0.5,
+            laid_out_ratio: None,
+            min_sizes: [0.0, 0.0],
+            max_sizes: [f64::INFINITY, f64::INFINITY],
+            dragging: None,
+            hovered: false,
+            on_resize: None,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// The first pane's share of the space, from 0 to 1. Defaults to an even split.
+    pub fn with_split_ratio(mut self, ratio: f64) -> Self {
+        self.set_split_ratio(ratio);
+        self
+    }
+
+    /// Sizes along the split the panes can't be dragged below, in pixels.
+    pub fn with_min_sizes(mut self, first: f64, second: f64) -> Self {
+        self.min_sizes = [first.max(0.0), second.max(0.0)];
+        self
+    }
+
+    /// Sizes along the split the panes can't be dragged past, in pixels.
+    pub fn with_max_sizes(mut self, first: f64, second: f64) -> Self {
+        self.max_sizes = [first.max(0.0), second.max(0.0)];
+        self
+    }
+
+    /// Called with the new ratio while the divider is dragged.
+    pub fn on_resize(mut self, f: impl FnMut(f64) + 'static) -> Self {
+        self.on_resize = Some(Box::new(f));
+        self
+    }
+
+    pub fn split_ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_split_ratio(&mut self, ratio: f64) {
+        self.ratio = ratio.clamp(0.0, 1.0);
+    }
+
+    // Start and length of the bounds along the split
+    fn main_extent(&self) -> (f64, f64) {
+        match self.axis {
+            Axis::Horizontal => (self.bounds.x0, self.bounds.width()),
+            Axis::Vertical => (self.bounds.y0, self.bounds.height()),
+        }
+    }
+
+    // Length of the first pane for `ratio`, within both panes' limits. When
+    // the limits can't all be met, the minimums win.
+    fn first_length(&self, ratio: f64) -> f64 {
+        let available = (self.main_extent().1 - DIVIDER_SIZE).max(0.0);
+        let [min_first, min_second] = self.min_sizes;
+        let [max_first, max_second] = self.max_sizes;
+        let lowest = min_first.max(available - max_second);
+        let highest = max_first.min(available - min_second);
+        (available * ratio).min(highest).max(lowest).clamp(0.0, available)
+    }
+
+    fn divider(&self) -> Rect {
+        let (start, _) = self.main_extent();
+        let position = start + self.first_length(self.ratio);
+        match self.axis {
+            Axis::Horizontal => Rect::new(position, self.bounds.y0, position + DIVIDER_SIZE, self.bounds.y1),
+            Axis::Vertical => Rect::new(self.bounds.x0, position, self.bounds.x1, position + DIVIDER_SIZE),
+        }
+    }
+
+    fn along(&self, point: Point) -> f64 {
+        match self.axis {
+            Axis::Horizontal => point.x,
+            Axis::Vertical => point.y,
+        }
+    }
+
+    // Moves the divider so the point it was grabbed at follows the pointer
+    fn drag_to(&mut self, position: Point, grab: f64) {
+        let (start, length) = self.main_extent();
+        let available = (length - DIVIDER_SIZE).max(0.0);
+        if available <= 0.0 {
+            return;
+        }
+        let wanted = (self.along(position) - grab - start) / available;
+        let ratio = self.first_length(wanted.clamp(0.0, 1.0)) / available;
+        if ratio != self.ratio {
+            self.ratio = ratio;
+            if let Some(on_resize) = &mut self.on_resize {
+                on_resize(ratio);
+            }
+        }
+    }
+}
+
+impl Widget for SplitView {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        let [first, second] = &mut self.panes;
+        let (first, second) = (first.measure(ctx, available), second.measure(ctx, available));
+        match self.axis {
+            Axis::Horizontal => Size::new(first.width + DIVIDER_SIZE + second.width, first.height.max(second.height)),
+            Axis::Vertical => Size::new(first.width.max(second.width), first.height + DIVIDER_SIZE + second.height),
+        }
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.laid_out_ratio = Some(self.ratio);
+        let divider = self.divider();
+        let (first, second) = match self.axis {
+            Axis::Horizontal => (
+                Rect::new(bounds.x0, bounds.y0, divider.x0, bounds.y1),
+                Rect::new(divider.x1, bounds.y0, bounds.x1, bounds.y1),
+            ),
+            Axis::Vertical => (
+                Rect::new(bounds.x0, bounds.y0, bounds.x1, divider.y0),
+                Rect::new(bounds.x0, divider.y1, bounds.x1, bounds.y1),
+            ),
+        };
+        self.panes[0].layout(ctx, first);
+        self.panes[1].layout(ctx, second);
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let divider = self.divider();
+        for pane in &mut self.panes {
+            // Panes laid out smaller than they'd like would spill over the divider
+            let clip = pane.bounds();
+            ctx.with_clip(&clip, |ctx| ctx.draw_child(pane.as_mut()));
+        }
+        let theme = ctx.theme;
+        if self.dragging.is_some() || self.hovered {
+            ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, theme.accent.with_alpha(0.3), None, &divider);
+        }
+        let center = divider.center();
+        let rule = match self.axis {
+            Axis::Horizontal => Line::new((center.x, divider.y0), (center.x, divider.y1)),
+            Axis::Vertical => Line::new((divider.x0, center.y), (divider.x1, center.y)),
+        };
+        ctx.stroke_hairline(Affine::IDENTITY, theme.foreground.with_alpha(0.2), &rule);
+    }
+
+    fn cursor_icon(&self) -> Option<CursorIcon> {
+        (self.dragging.is_some() || self.hovered).then_some(match self.axis {
+            Axis::Horizontal => CursorIcon::ColResize,
+            Axis::Vertical => CursorIcon::RowResize,
+        })
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.panes
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut self.panes
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.laid_out_ratio != Some(self.ratio) || self.panes.iter().any(|pane| pane.needs_layout())
+    }
+
+    // So the drag keeps going with the pointer over a pane or outside the window
+    fn captures_pointer(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            UiEvent::PointerMove { position } => {
+                if let Some(grab) = self.dragging {
+                    self.drag_to(*position, grab);
+                    return true;
+                }
+                // Moves over the panes bubble up here too
+                self.hovered = self.divider().contains(*position);
+                false
+            }
+            UiEvent::PointerLeave => {
+                self.hovered = false;
+                false
+            }
+            UiEvent::PointerDown { position, button: MouseButton::Left } if self.divider().contains(*position) => {
+                let divider = self.divider();
+                self.dragging = Some(self.along(*position) - self.along(divider.origin()));
+                true
+            }
+            UiEvent::PointerUp { position, button: MouseButton::Left } if self.dragging.is_some() => {
+                self.dragging = None;
+                self.hovered = self.divider().contains(*position);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.ratio.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        if let Some(ratio) = state.as_f64() {
+            self.set_split_ratio(ratio);
+        }
+    }
+}
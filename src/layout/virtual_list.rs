@@ -0,0 +1,287 @@
+use std::ops::Range;
+
+use vello::kurbo::{Affine, Rect, RoundedRect, Size};
+use vello::peniko::Fill;
+
+use super::SCROLLBAR_WIDTH;
+use crate::components::{DrawCtx, LayoutCtx, Widget};
+use crate::event::UiEvent;
+#[cfg(feature = "accesskit")]
+use accesskit::{Node, Role};
+
+// Rows kept built past each edge of the view, so a small scroll doesn't have
+// to build any
+const OVERSCAN: usize = 3;
+
+type RowBuilder = Box<dyn FnMut(usize) -> Box<dyn Widget>>;
+type RowRebinder = Box<dyn FnMut(&mut dyn Widget, usize)>;
+
+/// A scrolling list of any number of equally tall rows, of which only the
+/// ones in view, and a few either side, exist as widgets at any time.
+///
+/// Rows come from a builder called with each row's index as it scrolls into
+/// view. Rows that stay in view are kept, along with their hover and press
+/// state; those that scroll out are dropped, or with [`Self::with_rebind`]
+/// reused for the rows coming in.
+///
+/// All rows are as tall as the first one measures, unless a height is given
+/// with [`Self::with_row_height`].
+pub struct VirtualList {
+    row_count: usize,
+    build: RowBuilder,
+    rebind: Option<RowRebinder>,
+    row_height: Option<f64>,
+    // Of the first row, when no height was given
+    measured_row_height: Option<f64>,
+    // Built rows, in index order, covering `indices`
+    rows: Vec<Box<dyn Widget>>,
+    indices: Range<usize>,
+    scroll_offset: f64,
+    laid_out_offset: Option<f64>,
+    // The row count changed since the last layout
+    needs_layout: bool,
+    bounds: Rect,
+}
+
+impl VirtualList {
+    pub fn new(row_count: usize, build: impl FnMut(usize) -> Box<dyn Widget> + 'static) -> Self {
+        Self {
+            row_count,
+            build: Box::new(build),
+            rebind: None,
+            row_height: None,
+            measured_row_height: None,
+            rows: Vec::new(),
+            indices: 0..0,
+            scroll_offset: 0.0,
+            laid_out_offset: None,
+            needs_layout: true,
+            bounds: Rect::ZERO,
+        }
+    }
+
+    /// Every row's height, in pixels, saving the list measuring one.
+    pub fn with_row_height(mut self, height: f64) -> Self {
+        self.row_height = Some(height.max(0.0));
+        self
+    }
+
+    /// Reuses rows scrolled out of view for the rows scrolled into it, calling
+    /// `rebind` with each one and its new index to update it, instead of
+    /// building new ones.
+    pub fn with_rebind(mut self, rebind: impl FnMut(&mut dyn Widget, usize) + 'static) -> Self {
+        self.rebind = Some(Box::new(rebind));
+        self
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// Rebuilds every row on the next layout pass, as rows past the end may
+    /// be gone and the rest may show different data.
+    pub fn set_row_count(&mut self, row_count: usize) {
+        self.row_count = row_count;
+        self.rows.clear();
+        self.indices = 0..0;
+        self.needs_layout = true;
+    }
+
+    /// Indices of the rows that currently exist as widgets.
+    pub fn built_rows(&self) -> Range<usize> {
+        self.indices.clone()
+    }
+
+    pub fn scroll_offset(&self) -> f64 {
+        self.scroll_offset
+    }
+
+    /// Takes effect on the next layout pass.
+    pub fn set_scroll_offset(&mut self, offset: f64) {
+        self.scroll_offset = offset.clamp(0.0, self.max_offset());
+    }
+
+    /// Scrolls just far enough for row `index` to be fully in view.
+    pub fn scroll_to_row(&mut self, index: usize) {
+        let height = self.current_row_height();
+        let top = index.min(self.row_count.saturating_sub(1)) as f64 * height;
+        let offset = if top < self.scroll_offset {
+            top
+        } else {
+            self.scroll_offset.max(top + height - self.bounds.height())
+        };
+        self.set_scroll_offset(offset);
+    }
+
+    fn current_row_height(&self) -> f64 {
+        self.row_height.or(self.measured_row_height).unwrap_or(0.0)
+    }
+
+    fn content_height(&self) -> f64 {
+        self.row_count as f64 * self.current_row_height()
+    }
+
+    fn max_offset(&self) -> f64 {
+        (self.content_height() - self.bounds.height()).max(0.0)
+    }
+
+    // Measures the first row for the height of them all, if no height was given
+    fn row_height(&mut self, ctx: &LayoutCtx, width: f64) -> f64 {
+        if let Some(height) = self.row_height.or(self.measured_row_height) {
+            return height;
+        }
+        if self.row_count == 0 {
+            return 0.0;
+        }
+        let height = match self.indices.start {
+            0 if !self.rows.is_empty() => self.rows[0].measure(ctx, Size::new(width, f64::INFINITY)).height,
+            _ => (self.build)(0).measure(ctx, Size::new(width, f64::INFINITY)).height,
+        };
+        self.measured_row_height = Some(height);
+        height
+    }
+
+    // Builds the rows in `wanted`, keeping those already built
+    fn sync_rows(&mut self, wanted: Range<usize>) {
+        if wanted == self.indices {
+            return;
+        }
+        // Rows still in view keep their place, the rest are spare
+        let mut kept = Vec::new();
+        let mut spare = Vec::new();
+        for (row, index) in std::mem::take(&mut self.rows).into_iter().zip(self.indices.clone()) {
+            if wanted.contains(&index) {
+                kept.push((index, row));
+            } else {
+                spare.push(row);
+            }
+        }
+        let mut kept = kept.into_iter().peekable();
+        for index in wanted.clone() {
+            let row = match kept.next_if(|(kept_index, _)| *kept_index == index) {
+                Some((_, row)) => row,
+                None => match (&mut self.rebind, spare.pop()) {
+                    (Some(rebind), Some(mut row)) => {
+                        rebind(row.as_mut(), index);
+                        row
+                    }
+                    _ => (self.build)(index),
+                },
+            };
+            self.rows.push(row);
+        }
+        self.indices = wanted;
+    }
+}
+
+impl Widget for VirtualList {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        // Happy to take whatever we're given; the rows scroll within it
+        let height = self.row_height(ctx, available.width) * self.row_count as f64;
+        Size::new(if available.width.is_finite() { available.width } else { 0.0 }, height.min(available.height))
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.bounds = bounds;
+        self.needs_layout = false;
+        let row_height = self.row_height(ctx, bounds.width());
+        // Rows may have been removed since the last layout
+        self.scroll_offset = self.scroll_offset.clamp(0.0, self.max_offset());
+        self.laid_out_offset = Some(self.scroll_offset);
+
+        let wanted = if row_height > 0.0 {
+            let first = (self.scroll_offset / row_height).floor() as usize;
+            let last = ((self.scroll_offset + bounds.height()) / row_height).ceil() as usize;
+            first.saturating_sub(OVERSCAN)..(last + OVERSCAN).min(self.row_count)
+        } else {
+            0..0
+        };
+        self.sync_rows(wanted);
+
+        let top = bounds.y0 - self.scroll_offset;
+        for (row, index) in self.rows.iter_mut().zip(self.indices.clone()) {
+            let y = top + index as f64 * row_height;
+            row.measure(ctx, Size::new(bounds.width(), row_height));
+            row.layout(ctx, Rect::new(bounds.x0, y, bounds.x1, y + row_height));
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        let bounds = self.bounds;
+        let rows = &mut self.rows;
+        ctx.with_clip(&bounds, |ctx| {
+            for row in rows.iter_mut() {
+                if row.bounds().overlaps(bounds) {
+                    ctx.draw_child(row.as_mut());
+                }
+            }
+        });
+
+        let view_height = bounds.height();
+        let content_height = self.content_height();
+        if content_height > view_height {
+            // At least tall enough to grab among thousands of rows
+            let thumb_height = (view_height * view_height / content_height).max(SCROLLBAR_WIDTH * 4.0).min(view_height);
+            let thumb_top = bounds.y0 + (view_height - thumb_height) * self.scroll_offset / self.max_offset();
+            let thumb = RoundedRect::new(
+                bounds.x1 - SCROLLBAR_WIDTH,
+                thumb_top,
+                bounds.x1,
+                thumb_top + thumb_height,
+                SCROLLBAR_WIDTH / 2.0,
+            );
+            let color = ctx.theme.foreground.with_alpha(0.4);
+            ctx.scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &thumb);
+        }
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.rows
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        &mut self.rows
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.needs_layout
+            || self.laid_out_offset != Some(self.scroll_offset)
+            || self.rows.iter().any(|row| row.needs_layout())
+    }
+
+    #[cfg(feature = "accesskit")]
+    fn accessibility(&self) -> Option<Node> {
+        let mut node = Node::new(Role::List);
+        node.set_scroll_y(self.scroll_offset);
+        node.set_scroll_y_min(0.0);
+        node.set_scroll_y_max(self.max_offset());
+        Some(node)
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> bool {
+        match event {
+            // Left to an outer scroll view once this one can't go any further
+            UiEvent::Scroll { position, delta } if self.bounds.contains(*position) => {
+                let before = self.scroll_offset;
+                self.set_scroll_offset(self.scroll_offset - delta.y);
+                self.scroll_offset != before
+            }
+            _ => false,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.scroll_offset.into())
+    }
+
+    fn restore_state(&mut self, state: &serde_json::Value) {
+        // Clamped by the next layout, once the rows are measured again
+        if let Some(offset) = state.as_f64() {
+            self.scroll_offset = offset.max(0.0);
+        }
+    }
+}
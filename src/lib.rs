@@ -1,7 +1,19 @@
+pub mod animation;
 pub mod app;
+pub mod brush;
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod color;
 pub mod components;
+pub mod event;
 pub mod layout;
+mod macros;
 pub mod prelude;
 pub mod render;
 pub mod script;
+pub mod shapes;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod text;
+pub mod theme;
+pub mod ui;
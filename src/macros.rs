@@ -0,0 +1,59 @@
+/// Builds a widget tree from a nested description, expanding to the same
+/// builder calls it would take by hand.
+///
+/// A widget is its type, optionally followed by arguments for its `new` in
+/// parentheses and then a body in braces. The body lists, comma-separated:
+///
+/// - `name: value` to call the builder method `name` with `value`, e.g.
+///   `with_spacing: 8.0` or `on_click: || save()`.
+/// - Child widgets, each added with the parent's `child` method. A child in
+///   parentheses is taken as an expression, for widgets built elsewhere.
+///
+/// ```ignore
+/// use rasmalai::prelude::*;
+///
+/// let tree = rui! {
+///     VStack {
+///         with_spacing: 8.0,
+///         Title("Hi"),
+///         HStack {
+///             Button("OK") { on_click: || println!("ok"), with_tooltip: "Confirm" },
+///             (Button::new("Cancel")),
+///         },
+///         Panel(rui! { Label("Inside a panel") }),
+///     }
+/// };
+/// App::new().with_root(tree).run();
+/// ```
+#[macro_export]
+macro_rules! rui {
+    // A widget built elsewhere
+    (@node ($widget:expr)) => {
+        $widget
+    };
+    (@node $($ty:ident)::+ $(($($arg:expr),* $(,)?))? $({ $($body:tt)* })?) => {{
+        let widget = $($ty)::+::new($($($arg),*)?);
+        $crate::rui!(@body widget; $($($body)*)?)
+    }};
+
+    // Body items, one at a time
+    (@body $widget:ident;) => {
+        $widget
+    };
+    (@body $widget:ident; $method:ident : $value:expr $(, $($rest:tt)*)?) => {{
+        let $widget = $widget.$method($value);
+        $crate::rui!(@body $widget; $($($rest)*)?)
+    }};
+    (@body $widget:ident; ($child:expr) $(, $($rest:tt)*)?) => {{
+        let $widget = $widget.child($child);
+        $crate::rui!(@body $widget; $($($rest)*)?)
+    }};
+    (@body $widget:ident; $($ty:ident)::+ $(($($arg:tt)*))? $({ $($body:tt)* })? $(, $($rest:tt)*)?) => {{
+        let $widget = $widget.child($crate::rui!(@node $($ty)::+ $(($($arg)*))? $({ $($body)* })?));
+        $crate::rui!(@body $widget; $($($rest)*)?)
+    }};
+
+    ($($tree:tt)+) => {
+        $crate::rui!(@node $($tree)+)
+    };
+}
@@ -1,2 +1,14 @@
-pub use crate::app::App;
+pub use crate::animation::{Animation, Easing};
+pub use crate::app::{emit, emitting, emitting_with, hovered_files, pop_modal, push_modal, restore_snapshot, show_toast, take_snapshot, App, UiSnapshot};
 pub use crate::app::State;
+pub use crate::color::Hsv;
+pub use crate::components::{Button, Canvas, Checkbox, ColorPicker, ContextMenu, Dropdown, Image, Label, Opacity, Panel, ProgressBar, RadioGroup, Spinner, TabView, Title, Widget};
+#[cfg(feature = "svg")]
+pub use crate::components::Svg;
+pub use crate::event::{EventDispatcher, FocusDirection, GestureConfig, KeyCombination, UiEvent};
+pub use crate::layout::{Grid, HStack, ScrollView, SplitView, Track, VStack, VirtualList, ZStack};
+pub use crate::render::{DeviceLostAction, NinePatch, Shadow};
+pub use crate::rui;
+pub use crate::text::{FontWeight, TextOutline, TextStyle};
+pub use crate::theme::Theme;
+pub use crate::ui::{ImmediateView, Ui};
@@ -0,0 +1,48 @@
+use std::fmt;
+
+use vello::wgpu;
+use winit::dpi::PhysicalSize;
+
+/// What a [`RenderContext`](super::RenderContext) is rendering with, for bug
+/// reports. Formats as one `name: value` line per entry.
+#[derive(Clone, Debug)]
+pub struct RenderDiagnostics {
+    pub adapter: wgpu::AdapterInfo,
+    pub limits: wgpu::Limits,
+    pub surface_format: wgpu::TextureFormat,
+    pub present_mode: wgpu::PresentMode,
+    /// As asked of wgpu, which may clamp it.
+    pub frame_latency: u32,
+    pub surface_size: PhysicalSize<u32>,
+    /// Of the texture vello renders into, once there is one.
+    pub target_size: Option<PhysicalSize<u32>>,
+    /// Whether vello runs its shaders on the CPU.
+    pub use_cpu: bool,
+}
+
+impl fmt::Display for RenderDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let adapter = &self.adapter;
+        writeln!(f, "Adapter: {} ({:?})", adapter.name, adapter.device_type)?;
+        writeln!(f, "Backend: {:?}", adapter.backend)?;
+        if !adapter.driver.is_empty() {
+            writeln!(f, "Driver: {} {}", adapter.driver, adapter.driver_info)?;
+        }
+        writeln!(f, "Surface: {}x{} {:?}", self.surface_size.width, self.surface_size.height, self.surface_format)?;
+        writeln!(f, "Present mode: {:?}", self.present_mode)?;
+        writeln!(f, "Frame latency: {}", self.frame_latency)?;
+        match self.target_size {
+            Some(size) => writeln!(f, "Target texture: {}x{}", size.width, size.height)?,
+            None => writeln!(f, "Target texture: none yet")?,
+        }
+        writeln!(f, "Shaders: {}", if self.use_cpu { "CPU" } else { "GPU" })?;
+        // The limits vello's compute pipeline runs into first
+        let limits = &self.limits;
+        writeln!(f, "Max texture size: {}", limits.max_texture_dimension_2d)?;
+        writeln!(f, "Max buffer size: {}", limits.max_buffer_size)?;
+        writeln!(f, "Max storage binding: {}", limits.max_storage_buffer_binding_size)?;
+        writeln!(f, "Max storage buffers per stage: {}", limits.max_storage_buffers_per_shader_stage)?;
+        writeln!(f, "Max workgroup storage: {}", limits.max_compute_workgroup_storage_size)?;
+        write!(f, "Max workgroup invocations: {}", limits.max_compute_invocations_per_workgroup)
+    }
+}
@@ -0,0 +1,66 @@
+use vello::Scene;
+use vello::kurbo::{Affine, BezPath, PathEl, Point, Shape, Stroke};
+use vello::peniko::BrushRef;
+
+// How closely curved shapes like circles are turned into path segments
+const TOLERANCE: f64 = 0.1;
+
+/// Strokes `shape` exactly one pixel of `scene` wide, however much `transform`
+/// scales it, for borders and grid lines that should stay crisp. The app's
+/// scenes are in physical pixels, so that's one pixel on the display at any
+/// scale factor.
+///
+/// A stroke made under a transform is scaled with it, so a 1px stroke drawn
+/// at 2x comes out 2px wide, and at 1.5x smears across pixel boundaries. Here
+/// the shape is moved into the scene's space first and stroked there, with its
+/// points snapped to pixel centres so axis-aligned edges cover exactly one
+/// row or column of pixels.
+pub fn stroke_hairline<'b>(scene: &mut Scene, transform: Affine, brush: impl Into<BrushRef<'b>>, shape: &impl Shape) {
+    let snap = |point: Point| Point::new((point.x - 0.5).round() + 0.5, (point.y - 0.5).round() + 0.5);
+    let path: BezPath = shape
+        .path_elements(TOLERANCE)
+        .map(|element| match transform * element {
+            PathEl::MoveTo(p) => PathEl::MoveTo(snap(p)),
+            PathEl::LineTo(p) => PathEl::LineTo(snap(p)),
+            PathEl::QuadTo(p1, p2) => PathEl::QuadTo(snap(p1), snap(p2)),
+            PathEl::CurveTo(p1, p2, p3) => PathEl::CurveTo(snap(p1), snap(p2), snap(p3)),
+            PathEl::ClosePath => PathEl::ClosePath,
+        })
+        .collect();
+    scene.stroke(&Stroke::new(1.0), Affine::IDENTITY, brush, None, &path);
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use vello::kurbo::Line;
+    use vello::peniko::Color;
+
+    use super::*;
+
+    // Alpha down the middle column of a line drawn across a transparent frame,
+    // for the rows it touches
+    fn coverage(scene: &Scene) -> Option<Vec<(u32, u8)>> {
+        let mut renderer = crate::testing::headless()?;
+        let frame = renderer.render(scene, 32, 32, Color::TRANSPARENT).unwrap();
+        Some((0..32).map(|y| (y, frame.get_pixel(16, y)[3])).filter(|&(_, alpha)| alpha > 0).collect())
+    }
+
+    #[test]
+    fn hairlines_stay_one_pixel_wide_at_2x() {
+        // At 2x, y = 8 lands on the boundary between rows 15 and 16
+        let line = Line::new((2.0, 8.0), (14.0, 8.0));
+        let scale = Affine::scale(2.0);
+
+        let mut hairline = Scene::new();
+        stroke_hairline(&mut hairline, scale, Color::WHITE, &line);
+        let Some(hairline) = coverage(&hairline) else {
+            return;
+        };
+        let mut scaled = Scene::new();
+        scaled.stroke(&Stroke::new(1.0), scale, Color::WHITE, None, &line);
+        let scaled = coverage(&scaled).unwrap();
+
+        assert_eq!(hairline, [(16, 255)], "one whole row of pixels");
+        assert_eq!(scaled, [(15, 255), (16, 255)], "two rows, scaled with the transform");
+    }
+}
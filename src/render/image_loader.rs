@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::Context;
+use vello::peniko::{Blob, ImageAlphaType, ImageData, ImageFormat};
+
+use crate::app::{send_user_event, UserEvent};
+
+// Most decoder threads started, however many cores there are
+const MAX_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+type Slot = Arc<Mutex<Option<Result<ImageData, String>>>>;
+
+static JOBS: OnceLock<Sender<Job>> = OnceLock::new();
+
+/// An image being decoded on the loader's threads.
+pub(crate) struct PendingImage {
+    slot: Slot,
+}
+
+impl PendingImage {
+    pub(crate) fn is_ready(&self) -> bool {
+        self.slot.lock().unwrap().is_some()
+    }
+
+    /// The decoded image or why it failed, once there is one.
+    pub(crate) fn take(&self) -> Option<Result<ImageData, String>> {
+        self.slot.lock().unwrap().take()
+    }
+}
+
+/// Reads and decodes `path` off the calling thread, which can carry on drawing
+/// in the meantime. Failures are logged.
+pub(crate) fn load_image(path: PathBuf) -> PendingImage {
+    let slot = Slot::default();
+    let result = slot.clone();
+    let job = Box::new(move || {
+        let decoded = decode(&path).map_err(|err| {
+            log::error!("{:#}", err);
+            format!("{:#}", err)
+        });
+        *result.lock().unwrap() = Some(decoded);
+        // Wakes an idle app to draw it
+        send_user_event(UserEvent::Redraw);
+    });
+    if let Err(mpsc::SendError(job)) = jobs().send(job) {
+        // Every worker died, so decode here rather than never
+        job();
+    }
+    PendingImage { slot }
+}
+
+fn decode(path: &Path) -> anyhow::Result<ImageData> {
+    let image = image::open(path)
+        .with_context(|| format!("failed to load image {}", path.display()))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(ImageData {
+        data: Blob::new(Arc::new(image.into_raw())),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width,
+        height,
+    })
+}
+
+// Starts the workers on first use
+fn jobs() -> &'static Sender<Job> {
+    JOBS.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = std::thread::available_parallelism().map_or(1, |count| count.get().min(MAX_WORKERS));
+        for index in 0..workers {
+            let receiver = receiver.clone();
+            let spawned = std::thread::Builder::new()
+                .name(format!("image-loader-{}", index))
+                .spawn(move || work(&receiver));
+            if let Err(err) = spawned {
+                log::warn!("Failed to start image loader thread: {}", err);
+            }
+        }
+        sender
+    })
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // Held only while waiting, so the others can take the next job
+        let job = receiver.lock().unwrap().recv();
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
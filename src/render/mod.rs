@@ -1,39 +1,220 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use winit::dpi::PhysicalSize;
 use winit::window::Window;
 use vello::peniko::Color;
+use vello::peniko::color::{LinearSrgb, Srgb};
 use vello::util::{RenderContext as VelloRenderContext, RenderSurface};
 use vello::{Renderer, RendererOptions, Scene};
+use vello::kurbo::{Affine, Point, Rect, Shape, Size, Stroke, Vec2};
+use vello::peniko::{BlendMode, BrushRef, Fill};
 use vello::wgpu; // Use vello's re-exported wgpu if available, or just wgpu crate if versions match.
 
+use crate::animation::{Animation, Clock, Easing};
+use crate::shapes::{self, Circle, Line, RoundedRect, ShapeStyle};
+use throttled_log::ThrottledWarning;
+
+mod diagnostics;
+mod hairline;
+mod image_loader;
+mod nine_patch;
+mod readback;
+mod regions;
+mod shadow;
+mod throttled_log;
+#[cfg(feature = "threaded-render")]
+mod thread;
+
+pub use diagnostics::RenderDiagnostics;
+pub use hairline::stroke_hairline;
+pub(crate) use image_loader::{load_image, PendingImage};
+pub use nine_patch::NinePatch;
+pub use readback::{poll_device, read_texture};
+pub use regions::SceneRegions;
+pub use shadow::{draw_shadow, Shadow};
+
+// How long the surface has to keep its size before the target texture is
+// reallocated to match
+const RESIZE_SETTLE: Duration = Duration::from_millis(50);
+// Fraction of the render scale frames are drawn at during a smooth resize
+const SMOOTH_RESIZE_SCALE: f64 = 0.5;
+
+#[cfg(feature = "threaded-render")]
+pub use thread::RenderThread;
+
+// Opens a tracing span that lasts until the end of the enclosing block with
+// the `profiling` feature, and is nothing at all without it
+macro_rules! profile_scope {
+    ($name:literal) => {
+        #[cfg(feature = "profiling")]
+        let _span = tracing::info_span!($name).entered();
+    };
+}
+
+/// Runs `draw` with everything it paints clipped to `clip`.
+///
+/// Clips are vello layers, so nesting calls intersects them: a child can only
+/// ever narrow the area its parent allowed. Like [`with_blend`], the layer is
+/// popped even if `draw` panics.
+pub fn with_clip(scene: &mut Scene, transform: Affine, clip: &impl Shape, draw: impl FnOnce(&mut Scene)) {
+    // `Mix::Clip` is deprecated in peniko 0.5 in favour of a dedicated clip layer
+    scene.push_clip_layer(transform, clip);
+    let layer = PopLayer(scene);
+    draw(&mut *layer.0);
+}
+
+/// Runs `draw` in a layer clipped to `clip` that's composited onto what's
+/// already in the scene with `mode`, at `opacity`. The layer is popped even
+/// if `draw` panics, so an unwinding widget can't leave the rest of the
+/// scene stuck inside it.
+///
+/// `mode` is a [`Mix`](vello::peniko::Mix), which decides how each colour
+/// drawn combines with the colour under it:
+///
+/// - `Normal` paints over, as drawing without a layer does. Worth it only
+///   for the opacity, which fades everything in the layer together.
+/// - `Multiply`, `Darken` and `ColorBurn` only ever darken. Multiply by
+///   white changes nothing, which makes it the one for tints and shadows.
+/// - `Screen`, `Lighten` and `ColorDodge` only ever lighten. Screen with
+///   black changes nothing, which makes it the one for highlights and glows.
+/// - `Overlay`, `HardLight` and `SoftLight` add contrast, darkening darks
+///   and lightening lights.
+/// - `Difference` and `Exclusion` invert what's under anything bright.
+/// - `Hue`, `Saturation`, `Color` and `Luminosity` take that component from
+///   what's drawn and keep the rest from what's under it, e.g. `Color` to
+///   recolour a greyscale icon.
+///
+/// A [`BlendMode`] pairs the mix with a Porter-Duff [`Compose`](vello::peniko::Compose)
+/// operator, e.g. `SrcAtop` to only draw where something already is.
+/// `Mix::Clip` is deprecated; use [`with_clip`] instead.
+///
+/// ```ignore
+/// with_blend(scene, Mix::Screen, 0.6, &bounds, |scene| {
+///     scene.fill(Fill::NonZero, Affine::IDENTITY, highlight, None, &bounds);
+/// });
+/// ```
+pub fn with_blend(scene: &mut Scene, mode: impl Into<BlendMode>, opacity: f32, clip: &impl Shape, draw: impl FnOnce(&mut Scene)) {
+    scene.push_layer(mode, opacity.clamp(0.0, 1.0), Affine::IDENTITY, clip);
+    let layer = PopLayer(scene);
+    draw(&mut *layer.0);
+}
+
+// Pops the layer when dropped, including while unwinding
+struct PopLayer<'s>(&'s mut Scene);
+
+impl Drop for PopLayer<'_> {
+    fn drop(&mut self) {
+        self.0.pop_layer();
+    }
+}
+
+/// What the app should do once the GPU device has been lost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceLostAction {
+    /// Rebuild the device, surface and renderer from scratch.
+    Recover,
+    Exit,
+}
+
+/// Runs between vello rendering into the intermediate texture and the blit to the
+/// surface. See [`RenderContext::set_post_process`].
+pub type PostProcessHook = Box<dyn FnMut(&mut wgpu::CommandEncoder, &wgpu::TextureView)>;
+
+/// Draws one tile of the frame, see [`RenderContext::set_region_painter`].
+pub type RegionPainter = Box<dyn FnMut(&mut Scene, Rect)>;
+
 pub struct RenderContext {
+    // Weak so holding a context doesn't keep a closed window alive
+    window: Weak<Window>,
+    // Set by `invalidate`, cleared once a frame has been rendered
+    dirty: bool,
+    // Set from wgpu's device-lost callback, holding the reason
+    device_lost: Arc<Mutex<Option<String>>>,
+
     // Vello context
     vello_context: VelloRenderContext,
     renderers: Vec<Option<Renderer>>,
     surface: RenderSurface<'static>,
     scene: Scene,
+    // What `scene` is built into first when it has to be moved into a viewport
+    unplaced_scene: Scene,
+    // Part of the surface the scene is drawn into, or all of it
+    viewport: Option<Rect>,
+    // Replaces the built-in demo when set
+    content: Option<Scene>,
+    // Replaces the content when set, redrawn a tile at a time
+    regions: Option<(SceneRegions, RegionPainter)>,
+    // Between `begin_frame` and `end_frame`
+    frame_open: bool,
+    // Paths drawn through the public API, kept across frames until cleared
+    user_scene: Scene,
     use_cpu: bool,
+    // Kept so `recover` comes back on the same backends
+    backends: Option<wgpu::Backends>,
+    // Surface and target texture never grow past this
+    max_size: Option<PhysicalSize<u32>>,
+    background_color: Color,
+    // Set while the background fades from this colour to `background_color`
+    background_fade: Option<(Color, Animation)>,
+    // Size of the target texture relative to the surface
+    render_scale: f64,
     target_texture: Option<wgpu::Texture>,
+    // Last resize the target texture hasn't caught up with yet
+    resized_at: Option<Instant>,
+    smooth_resize: bool,
+    // Set once the surface has been configured for vello to render into
+    direct_surface: bool,
+    // Set once the surface has been configured to composite with alpha
+    transparent: bool,
+    post_process: Option<PostProcessHook>,
+    // Surface errors can repeat every frame during a GPU stall
+    surface_timeout: ThrottledWarning,
+    surface_outdated: ThrottledWarning,
     
     // Cached Blit resources
     blit_pipeline: wgpu::RenderPipeline,
     blit_bind_group_layout: wgpu::BindGroupLayout,
     blit_sampler: wgpu::Sampler,
+    blit_filter: wgpu::FilterMode,
     blit_bind_group: Option<wgpu::BindGroup>,
     
-    start_time: std::time::Instant,
+    // Drives the demo scene
+    clock: Clock,
 }
 
 impl RenderContext {
-    pub async fn new(window: Arc<Window>, use_cpu: bool) -> Self {
-        let mut vello_context = VelloRenderContext::new(); 
+    /// Restricts wgpu to `backends` when given. Otherwise vello picks, honouring
+    /// the `WGPU_BACKEND` environment variable.
+    ///
+    /// `srgb` asks for an `*Srgb` surface format rather than a linear one,
+    /// falling back to whichever the surface offers. Colours look the same
+    /// either way; it only matters on platforms that composite one kind of
+    /// surface better than the other. See [`Self::is_srgb`].
+    pub async fn new(window: Arc<Window>, use_cpu: bool, backends: Option<wgpu::Backends>, srgb: bool) -> Self {
+        let mut vello_context = match backends {
+            Some(backends) => VelloRenderContext {
+                instance: wgpu::Instance::new(&wgpu::InstanceDescriptor {
+                    backends,
+                    flags: wgpu::InstanceFlags::from_build_config().with_env(),
+                    memory_budget_thresholds: wgpu::MemoryBudgetThresholds::default(),
+                    backend_options: wgpu::BackendOptions::from_env_or_default(),
+                }),
+                devices: Vec::new(),
+            },
+            None => VelloRenderContext::new(),
+        };
         
         // Create surface
-        let surface = vello_context.create_surface(
-            window.clone(), 
-            window.inner_size().width, 
-            window.inner_size().height,
-            wgpu::PresentMode::AutoVsync,
-        ).await.expect("failed to create surface");
+        let surface = match create_surface(&mut vello_context, window.clone(), srgb).await {
+            Ok(surface) => surface,
+            Err(vello::Error::NoCompatibleDevice) if backends.is_some() => {
+                panic!("no GPU adapter found for the requested backends ({:?})", backends.unwrap())
+            }
+            Err(err) => panic!("failed to create surface: {}", err),
+        };
+
+        let adapter_info = vello_context.devices[surface.dev_id].adapter().get_info();
+        log::info!("Rendering with {:?} on {}", adapter_info.backend, adapter_info.name);
         
         let renderer_options = RendererOptions {
             antialiasing_support: vello::AaSupport::all(),
@@ -43,6 +224,18 @@ impl RenderContext {
         };
 
         let device = &vello_context.devices[surface.dev_id].device;
+
+        let device_lost = Arc::new(Mutex::new(None));
+        let lost_flag = device_lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            // We never destroy the device ourselves, so this is just the device being dropped
+            if reason == wgpu::DeviceLostReason::Destroyed {
+                return;
+            }
+            log::error!("GPU device lost ({:?}): {}", reason, message);
+            *lost_flag.lock().unwrap() = Some(message);
+        });
+
         let renderer = vello::Renderer::new(
             device,
             renderer_options, 
@@ -50,200 +243,839 @@ impl RenderContext {
 
         let scene = Scene::new();
         
-        // --- Initialize Blit Pipeline ---
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Blit Shader"),
-            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(r#"
-                struct VertexOutput {
-                    @builtin(position) position: vec4<f32>,
-                    @location(0) uv: vec2<f32>,
-                };
-
-                @vertex
-                fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
-                    var out: VertexOutput;
-                    var positions = array<vec2<f32>, 3>(
-                        vec2<f32>(-1.0, -1.0),
-                        vec2<f32>(3.0, -1.0),
-                        vec2<f32>(-1.0, 3.0)
-                    );
-                    out.position = vec4<f32>(positions[vertex_index], 0.0, 1.0);
-                    out.uv = positions[vertex_index] * 0.5 + 0.5;
-                    out.uv.y = 1.0 - out.uv.y; 
-                    return out;
-                }
-
-                @group(0) @binding(0) var t_diffuse: texture_2d<f32>;
-                @group(0) @binding(1) var s_diffuse: sampler;
-
-                @fragment
-                fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-                    return textureSample(t_diffuse, s_diffuse, in.uv);
-                }
-            "#)),
-        });
-
-        let blit_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Blit Bind Group Layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-        });
-
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Blit Pipeline Layout"),
-            bind_group_layouts: &[&blit_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-
-        let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Blit Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                compilation_options: Default::default(),
-                buffers: &[],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                compilation_options: Default::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface.config.format, 
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
-
-        let blit_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
+        let (blit_pipeline, blit_bind_group_layout) = create_blit_pipeline(device, surface.config.format);
+        let blit_sampler = create_blit_sampler(device, wgpu::FilterMode::Linear);
 
         Self {
+            window: Arc::downgrade(&window),
+            dirty: true,
+            frame_open: false,
+            device_lost,
             vello_context,
             renderers: vec![Some(renderer)],
             surface,
             scene,
+            unplaced_scene: Scene::new(),
+            viewport: None,
+            content: None,
+            regions: None,
+            user_scene: Scene::new(),
             use_cpu,
+            backends,
+            max_size: None,
+            background_color: Color::from_rgb8(20, 20, 20),
+            background_fade: None,
+            render_scale: 1.0,
             target_texture: None,
+            resized_at: None,
+            smooth_resize: false,
+            direct_surface: false,
+            transparent: false,
+            post_process: None,
             blit_pipeline,
             blit_bind_group_layout,
             blit_sampler,
+            blit_filter: wgpu::FilterMode::Linear,
             blit_bind_group: None,
-            start_time: std::time::Instant::now(),
+            surface_timeout: ThrottledWarning::new("Surface timeout"),
+            surface_outdated: ThrottledWarning::new("Surface outdated/lost"),
+            clock: Clock::new(),
         }
     }
 
-    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+    /// The device the context renders with, for creating resources that are
+    /// used alongside it, e.g. textures for [`Self::render_to_external`].
+    ///
+    /// The device belongs to this context: it's replaced by [`Self::recover`],
+    /// so anything created on it has to be recreated after a device loss.
+    /// wgpu handles are cheap to clone if one must outlive the borrow.
+    pub fn device(&self) -> &wgpu::Device {
+        &self.vello_context.devices[self.surface.dev_id].device
+    }
+
+    /// The queue for [`Self::device`], under the same constraints.
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.vello_context.devices[self.surface.dev_id].queue
+    }
+
+    /// Blocks until the GPU is idle, having finished everything submitted so
+    /// far, e.g. by [`Self::render_to_external`]. Buffer mapping callbacks are
+    /// only run while the device is polled, so call this after
+    /// [`wgpu::BufferSlice::map_async`] when mapping buffers yourself.
+    pub fn poll_device(&self) -> anyhow::Result<()> {
+        readback::poll_device(self.device())
+    }
+
+    /// Whether the surface has an `*Srgb` format.
+    ///
+    /// vello always renders into an `Rgba8Unorm` texture holding sRGB-encoded
+    /// colours, which is also what post-process hooks and
+    /// [`Self::render_to_external`] see. A linear surface gets those values
+    /// copied straight through. An sRGB one encodes whatever is written to it,
+    /// so the blit decodes them to linear first and the hardware turns them
+    /// back into the same sRGB values; without that they'd come out washed out.
+    pub fn is_srgb(&self) -> bool {
+        self.surface.config.format.is_srgb()
+    }
+
+    /// The adapter, limits and surface setup as they are now.
+    pub fn diagnostics(&self) -> RenderDiagnostics {
+        let device_handle = &self.vello_context.devices[self.surface.dev_id];
+        RenderDiagnostics {
+            adapter: device_handle.adapter().get_info(),
+            limits: device_handle.device.limits(),
+            surface_format: self.surface.config.format,
+            present_mode: self.current_present_mode(),
+            frame_latency: self.desired_maximum_frame_latency(),
+            surface_size: self.size(),
+            target_size: self
+                .target_texture
+                .as_ref()
+                .map(|texture| PhysicalSize::new(texture.width(), texture.height())),
+            use_cpu: self.use_cpu,
+        }
+    }
+
+    /// Why the GPU device was lost, if it has been. Nothing renders until [`Self::recover`].
+    pub fn device_lost(&self) -> Option<String> {
+        self.device_lost.lock().unwrap().clone()
+    }
+
+    /// Rebuilds the vello context, surface and renderer on a fresh device,
+    /// keeping the background colour, user paths, region painter,
+    /// post-process hook, present mode, frame latency, surface format,
+    /// transparency, blit filter, render scale, viewport, clock and any
+    /// background fade in progress.
+    /// GPU resources a hook created on the old device must be recreated by it.
+    pub fn recover(mut self) -> Self {
+        // The surface still holds the window, so it's alive until we drop ourselves
+        let window = self.window.upgrade().expect("window dropped while its surface was alive");
+        let use_cpu = self.use_cpu;
+        let backends = self.backends;
+        let srgb = self.is_srgb();
+        let background_color = self.background_color;
+        let user_scene = std::mem::take(&mut self.user_scene);
+        let post_process = self.post_process.take();
+        let regions = self.regions.take();
+        let max_size = self.max_size;
+        let viewport = self.viewport;
+        let clock = self.clock.clone();
+        // Its animation is timed on the clock, so carries on where it was
+        let background_fade = self.background_fade.take();
+        let present_mode = self.current_present_mode();
+        let frame_latency = self.desired_maximum_frame_latency();
+        let blit_filter = self.blit_filter;
+        let render_scale = self.render_scale;
+        let smooth_resize = self.smooth_resize;
+        let direct_surface = self.direct_surface;
+        let transparent = self.transparent;
+        // Release the old surface before creating a new one for the same window
+        drop(self);
+
+        log::info!("Recreating GPU resources after device loss");
+        let mut fresh = Self::new(window, use_cpu, backends, srgb).pollster_block_on();
+        fresh.background_color = background_color;
+        fresh.user_scene = user_scene;
+        fresh.post_process = post_process;
+        fresh.regions = regions;
+        fresh.max_size = max_size;
+        fresh.viewport = viewport;
+        fresh.clock = clock;
+        fresh.background_fade = background_fade;
+        fresh.set_blit_filter(blit_filter);
+        fresh.render_scale = render_scale;
+        fresh.smooth_resize = smooth_resize;
+        fresh.set_direct_surface(direct_surface);
+        fresh.set_transparent(transparent);
+        if fresh.current_present_mode() != present_mode {
+            fresh.set_present_mode(present_mode);
+        }
+        if fresh.desired_maximum_frame_latency() != frame_latency {
+            fresh.set_desired_maximum_frame_latency(frame_latency);
+        }
+        fresh
+    }
+
+    /// Marks the frame as out of date and asks the window for a redraw, for code
+    /// that changes what's on screen without going through the event loop.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+        if let Some(window) = self.window.upgrade() {
+            window.request_redraw();
+        }
+    }
+
+    /// Whether something has invalidated the frame since it was last rendered.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// What to draw from the next frame on, instead of the built-in demo.
+    /// Paths added with `fill_path`/`stroke_path` still go on top.
+    ///
+    /// Only marks the frame dirty, without asking the window for a redraw,
+    /// since the app hands over its content this way on every frame; call
+    /// [`Self::invalidate`] too when replacing it from outside the event loop.
+    pub fn set_content(&mut self, content: Option<Scene>) {
+        self.content = content;
+        self.dirty = true;
+    }
+
+    /// Draws the frame tile by tile with `painter` instead of the content,
+    /// keeping each tile's scene until [`Self::invalidate_region`] marks it
+    /// dirty, so small changes to a large frame only re-encode the tiles
+    /// around them. Every tile is drawn on the first frame and whenever the
+    /// size changes. `None` goes back to the content. See [`SceneRegions`].
+    pub fn set_region_painter(&mut self, tile_size: f64, painter: Option<RegionPainter>) {
+        self.regions = painter.map(|painter| (SceneRegions::new(tile_size), painter));
+        self.invalidate();
+    }
+
+    /// Has the region painter redraw the tiles `rect` touches on the next
+    /// frame, and asks for one. Without a painter this just invalidates the
+    /// whole frame.
+    pub fn invalidate_region(&mut self, rect: Rect) {
+        if let Some((regions, _)) = &mut self.regions {
+            regions.invalidate_region(rect);
+        }
+        self.invalidate();
+    }
+
+    /// Starts a frame built up from any number of sources: the content is
+    /// emptied, for each to draw its part into [`Self::frame_scene`], and
+    /// [`Self::end_frame`] then renders and presents the lot at once.
+    ///
+    /// ```ignore
+    /// render_context.begin_frame();
+    /// sidebar.draw(render_context.frame_scene());
+    /// chart.draw(render_context.frame_scene());
+    /// render_context.end_frame();
+    /// ```
+    pub fn begin_frame(&mut self) {
+        if std::mem::replace(&mut self.frame_open, true) {
+            log::warn!("Frame begun again before it was ended; starting it over");
+        }
+        self.content.get_or_insert_with(Scene::new).reset();
+    }
+
+    /// The content of the frame being built. Outside `begin_frame` and
+    /// `end_frame` it's the content as last set, drawn on top of.
+    pub fn frame_scene(&mut self) -> &mut Scene {
+        self.content.get_or_insert_with(Scene::new)
+    }
+
+    /// Renders the frame started by [`Self::begin_frame`] and presents it.
+    /// It stays the content, so [`Self::render`] shows it again until the
+    /// next frame is begun.
+    pub fn end_frame(&mut self) {
+        if !std::mem::take(&mut self.frame_open) {
+            log::warn!("Frame ended without being begun");
+        }
+        self.render();
+    }
+
+    /// Fills `path` on top of the app's own content, from the next frame on.
+    pub fn fill_path<'b>(&mut self, fill: Fill, transform: Affine, brush: impl Into<BrushRef<'b>>, path: &impl Shape) {
+        self.user_scene.fill(fill, transform, brush, None, path);
+        self.invalidate();
+    }
+
+    /// Strokes `path` on top of the app's own content, from the next frame on.
+    pub fn stroke_path<'b>(&mut self, stroke: &Stroke, transform: Affine, brush: impl Into<BrushRef<'b>>, path: &impl Shape) {
+        self.user_scene.stroke(stroke, transform, brush, None, path);
+        self.invalidate();
+    }
+
+    /// Fills or outlines `rect` like [`Self::fill_path`], until the paths are cleared.
+    pub fn draw_rect(&mut self, color: Color, rect: Rect, style: &ShapeStyle) {
+        shapes::draw_shape(&mut self.user_scene, Affine::IDENTITY, color, style, &rect);
+        self.invalidate();
+    }
+
+    pub fn draw_rounded_rect(&mut self, color: Color, rect: Rect, radius: f64, style: &ShapeStyle) {
+        RoundedRect::new(rect).with_radius(radius).draw(&mut self.user_scene, color, style);
+        self.invalidate();
+    }
+
+    pub fn draw_circle(&mut self, color: Color, center: Point, radius: f64, style: &ShapeStyle) {
+        Circle::new(center, radius).draw(&mut self.user_scene, color, style);
+        self.invalidate();
+    }
+
+    pub fn draw_line(&mut self, color: Color, from: Point, to: Point, width: f64) {
+        Line::new(from, to).draw(&mut self.user_scene, color, width);
+        self.invalidate();
+    }
+
+    /// Draws on top of the app's own content like [`Self::fill_path`], through
+    /// a layer blended with `mode` over the whole frame as it's sized now.
+    /// See [`with_blend`] for the modes.
+    pub fn with_blend(&mut self, mode: impl Into<BlendMode>, opacity: f32, draw: impl FnOnce(&mut Scene)) {
+        let size = self.size();
+        let clip = Rect::new(0.0, 0.0, size.width as f64, size.height as f64);
+        with_blend(&mut self.user_scene, mode, opacity, &clip, draw);
+        self.invalidate();
+    }
+
+    /// Forgets everything drawn with `fill_path`/`stroke_path`, `with_blend` and the `draw_*` helpers.
+    pub fn clear_paths(&mut self) {
+        self.user_scene.reset();
+        self.invalidate();
+    }
+
+    /// Present modes the window's surface can be configured with on this adapter.
+    pub fn supported_present_modes(&self) -> Vec<wgpu::PresentMode> {
+        let adapter = self.vello_context.devices[self.surface.dev_id].adapter();
+        self.surface.surface.get_capabilities(adapter).present_modes
+    }
+
+    pub fn current_present_mode(&self) -> wgpu::PresentMode {
+        self.surface.config.present_mode
+    }
+
+    /// Reconfigures the surface to present with `mode`, e.g. `AutoNoVsync` to
+    /// render as fast as possible. Modes the surface doesn't support are
+    /// ignored with a warning; the `Auto*` ones always are.
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) {
+        let automatic = matches!(mode, wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync);
+        if !automatic && !self.supported_present_modes().contains(&mode) {
+            log::warn!("Present mode {:?} isn't supported here; keeping {:?}", mode, self.current_present_mode());
+            return;
+        }
+        self.vello_context.set_present_mode(&mut self.surface, mode);
+        self.invalidate();
+    }
+
+    /// How many frames may be queued ahead of the one on screen, as asked of
+    /// wgpu. See [`Self::set_desired_maximum_frame_latency`].
+    pub fn desired_maximum_frame_latency(&self) -> u32 {
+        self.surface.config.desired_maximum_frame_latency
+    }
+
+    /// Reconfigures the surface to queue at most `frames` frames ahead of
+    /// the one on screen, 2 by default. 1 shows input soonest but can drop
+    /// to half the refresh rate when a frame runs long; 3 keeps throughput
+    /// up at the cost of another frame of latency. Goes with the present
+    /// mode: `Mailbox` or `Immediate` at 1 for the lowest latency, `Fifo` at
+    /// 2 or 3 for the steadiest frames.
+    ///
+    /// wgpu quietly clamps this to what the backend allows, so the value
+    /// it's likely to end up with is logged.
+    pub fn set_desired_maximum_frame_latency(&mut self, frames: u32) {
+        let frames = frames.max(1);
+        let backend = self.diagnostics().adapter.backend;
+        match effective_frame_latency(backend, frames) {
+            Some(effective) if effective != frames => {
+                log::info!("Frame latency {} isn't possible on {:?}; {} frames it is", frames, backend, effective)
+            }
+            Some(effective) => log::info!("Frame latency set to {} frames", effective),
+            None => log::info!("Frame latency set to {} frames, or as many as {:?} will queue", frames, backend),
+        }
+        self.surface.config.desired_maximum_frame_latency = frames;
+        self.surface.surface.configure(self.device(), &self.surface.config);
+        self.invalidate();
+    }
+
+    pub fn blit_filter(&self) -> wgpu::FilterMode {
+        self.blit_filter
+    }
+
+    /// How the rendered frame is sampled when it's copied to the window.
+    /// `Linear`, the default, smooths it; `Nearest` keeps pixels sharp, for
+    /// pixel art and captures that should match the scene exactly. It only
+    /// shows when the frame is scaled: with a render scale other than 1 (see
+    /// [`Self::set_render_scale`]) or while a resize is settling (see
+    /// [`Self::resize`]).
+    pub fn set_blit_filter(&mut self, filter: wgpu::FilterMode) {
+        if filter == self.blit_filter {
+            return;
+        }
+        self.blit_sampler = create_blit_sampler(self.device(), filter);
+        self.blit_filter = filter;
+        // Holds the old sampler
+        self.blit_bind_group = None;
+        self.invalidate();
+    }
+
+    /// Whether frames can be rendered straight into the surface, see
+    /// [`Self::set_direct_surface`].
+    pub fn direct_surface(&self) -> bool {
+        self.direct_surface
+    }
+
+    /// Has vello render straight into the window's surface, skipping the
+    /// intermediate texture and the pass that blits it over. Only possible
+    /// where the surface is `Rgba8Unorm` and can be written from a compute
+    /// shader; anywhere else this logs why and keeps blitting. Even then, a
+    /// frame is only rendered directly when it fills the whole surface at a
+    /// render scale of 1 over an opaque background, with no viewport, no
+    /// post-process hook and no resize settling; the rest still go through
+    /// the texture.
+    pub fn set_direct_surface(&mut self, direct: bool) {
+        if direct == self.direct_surface {
+            return;
+        }
+        let adapter = self.vello_context.devices[self.surface.dev_id].adapter();
+        let usages = self.surface.surface.get_capabilities(adapter).usages;
+        let format = self.surface.config.format;
+        if direct && format != wgpu::TextureFormat::Rgba8Unorm {
+            log::info!("Surface format {:?} can't be rendered into directly; blitting instead", format);
+            return;
+        }
+        if direct && !usages.contains(wgpu::TextureUsages::STORAGE_BINDING) {
+            log::info!("Surface can't be written by compute shaders; blitting instead");
+            return;
+        }
+        self.surface.config.usage.set(wgpu::TextureUsages::STORAGE_BINDING, direct);
+        self.surface.surface.configure(self.device(), &self.surface.config);
+        self.direct_surface = direct;
+        self.invalidate();
+    }
+
+    /// Whether the window is composited with the frame's alpha, see
+    /// [`Self::set_transparent`].
+    pub fn transparent(&self) -> bool {
+        self.transparent
+    }
+
+    /// Has the window's surface composited over whatever is behind it, using
+    /// the frame's alpha, so a translucent background colour lets the desktop
+    /// show through. The window must be created transparent too, see
+    /// [`App::with_transparent`](crate::app::App::with_transparent). Frames
+    /// are always presented with premultiplied alpha; surfaces that can't
+    /// composite that way log why and stay opaque.
+    pub fn set_transparent(&mut self, transparent: bool) {
+        if transparent == self.transparent {
+            return;
+        }
+        let adapter = self.vello_context.devices[self.surface.dev_id].adapter();
+        let alpha_modes = self.surface.surface.get_capabilities(adapter).alpha_modes;
+        if transparent && !alpha_modes.contains(&wgpu::CompositeAlphaMode::PreMultiplied) {
+            log::info!("Surface can't composite premultiplied alpha (only {:?}); staying opaque", alpha_modes);
+            return;
+        }
+        self.surface.config.alpha_mode = if transparent {
+            wgpu::CompositeAlphaMode::PreMultiplied
+        } else {
+            wgpu::CompositeAlphaMode::Auto
+        };
+        self.surface.surface.configure(self.device(), &self.surface.config);
+        self.transparent = transparent;
+        self.invalidate();
+    }
+
+    /// Installs a hook that records extra GPU work into the frame's encoder after
+    /// vello has rendered and before the result is blitted to the window.
+    ///
+    /// The view is the whole intermediate texture: `Rgba8Unorm`, the surface's
+    /// size times the render scale (except while a resize is settling, see
+    /// [`Self::resize`] and [`Self::set_render_scale`]),
+    /// with `STORAGE_BINDING | TEXTURE_BINDING | RENDER_ATTACHMENT | COPY_SRC`
+    /// usage. Whatever it contains when the hook returns is what gets shown.
+    pub fn set_post_process(&mut self, hook: Option<PostProcessHook>) {
+        self.post_process = hook;
+    }
+
+    /// Time on the render clock, which animates the built-in demo. It runs from
+    /// creation unless paused, reset or set.
+    pub fn clock(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    pub fn reset_clock(&mut self) {
+        self.clock.reset();
+        self.invalidate();
+    }
+
+    pub fn pause_clock(&mut self) {
+        self.clock.pause();
+    }
+
+    pub fn resume_clock(&mut self) {
+        self.clock.resume();
+        self.invalidate();
+    }
+
+    /// Stops the clock at `time`, for rendering animated content at an exact
+    /// moment, e.g. in golden-image tests. [`Self::resume_clock`] carries on from there.
+    pub fn set_clock(&mut self, time: Duration) {
+        self.clock.set(time);
+        self.invalidate();
+    }
+
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = color;
+        self.background_fade = None;
+        self.invalidate();
+    }
+
+    /// Fades the background from whatever it shows now to `color` over
+    /// `duration`, blending in linear RGB so the midpoint doesn't look muddy.
+    /// The last frame of the fade is drawn in exactly `color`. It runs on the
+    /// render clock (see [`Self::clock`]), so pausing that holds it too. See
+    /// [`Self::is_animating`] for keeping frames coming meanwhile.
+    pub fn set_background_color_animated(&mut self, color: Color, duration: Duration) {
+        let now = self.clock.elapsed();
+        let from = self.background_at(now);
+        self.background_color = color;
+        self.background_fade = (!duration.is_zero())
+            .then(|| (from, Animation::new(0.0, 1.0, duration, Easing::EaseInOut).starting_at(now)));
+        self.invalidate();
+    }
+
+    /// Whether the context is in the middle of something that needs a frame
+    /// every vsync, like a background fade, whether or not the content changes.
+    pub fn is_animating(&self) -> bool {
+        self.background_fade.is_some()
+    }
+
+    // At `now` on the render clock
+    fn background_at(&self, now: Duration) -> Color {
+        match &self.background_fade {
+            Some((from, fade)) if !fade.is_finished(now) => {
+                let t = fade.sample(now) as f32;
+                let to = self.background_color.convert::<LinearSrgb>();
+                from.convert::<LinearSrgb>().lerp_rect(to, t).convert::<Srgb>()
+            }
+            _ => self.background_color,
+        }
+    }
+
+    pub fn render_scale(&self) -> f64 {
+        self.render_scale
+    }
+
+    /// Renders the scene into a texture `scale` times the surface's size, which
+    /// the blit then stretches over the surface, e.g. 0.5 to draw a quarter of
+    /// the pixels on a slow GPU. Layout and input still use surface pixels; only
+    /// the resolution changes. [`Self::set_blit_filter`] decides how the
+    /// stretched frame looks. Defaults to 1.
+    ///
+    /// Scales that aren't positive and finite are ignored with a warning. The
+    /// texture never grows past what the device can allocate, whatever the scale.
+    pub fn set_render_scale(&mut self, scale: f64) {
+        if !(scale.is_finite() && scale > 0.0) {
+            log::warn!("Render scale must be positive; keeping {}", self.render_scale);
+            return;
+        }
+        if scale == self.render_scale {
+            return;
+        }
+        self.render_scale = scale;
+        // Reallocated at the new size on the next frame
+        self.target_texture = None;
+        self.resized_at = None;
+        self.invalidate();
+    }
+
+    // Size of the target texture for a surface of `width` x `height`
+    fn target_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let device_max = self.device().limits().max_texture_dimension_2d;
+        let scale = if self.smooth_resize && self.resized_at.is_some() {
+            self.render_scale * SMOOTH_RESIZE_SCALE
+        } else {
+            self.render_scale
+        };
+        let scaled = |length: u32| ((length as f64 * scale).round() as u32).clamp(1, device_max);
+        (scaled(width), scaled(height))
+    }
+
+    pub fn smooth_resize(&self) -> bool {
+        self.smooth_resize
+    }
+
+    /// While a resize is settling, renders each new size into a fresh texture
+    /// at half the render scale, rather than squeezing the scene into the
+    /// texture from before the resize. Frames stay the right shape throughout,
+    /// at a resolution cheap enough to draw every time the size changes. Off by
+    /// default. See [`Self::resize`].
+    pub fn set_smooth_resize(&mut self, smooth: bool) {
+        self.smooth_resize = smooth;
+    }
+
+    /// Caps the size [`Self::resize`] will grow the surface to, whatever size the
+    /// window reports. Sizes are always capped to what the device can allocate.
+    pub fn set_max_size(&mut self, max_size: Option<PhysicalSize<u32>>) {
+        self.max_size = max_size;
+    }
+
+    /// Confines rendering to `viewport`, in physical pixels of the surface, for
+    /// apps that only give the UI part of the window. The scene is drawn with
+    /// its origin at the viewport's top-left corner and clipped to it; the rest
+    /// of the surface is cleared to the background colour. `None` renders to
+    /// the whole surface again.
+    ///
+    /// Viewports are rounded to whole pixels and clamped to the surface, now
+    /// and whenever it's resized.
+    pub fn set_viewport(&mut self, viewport: Option<Rect>) {
+        if let Some(viewport) = viewport
+            && self.clamped_viewport(viewport) != viewport.round()
+        {
+            log::warn!(
+                "Viewport {:?} reaches outside the {}x{} surface; clamping it",
+                viewport,
+                self.surface.config.width,
+                self.surface.config.height
+            );
+        }
+        self.viewport = viewport;
+        self.invalidate();
+    }
+
+    pub fn viewport(&self) -> Option<Rect> {
+        self.viewport
+    }
+
+    /// When the frame after a resize should be rendered to bring the target
+    /// texture back to full resolution, if one is settling.
+    pub fn resize_settles_at(&self) -> Option<Instant> {
+        self.resized_at.map(|at| at + RESIZE_SETTLE)
+    }
+
+    fn clamped_viewport(&self, viewport: Rect) -> Rect {
+        let surface = Rect::new(0.0, 0.0, self.surface.config.width as f64, self.surface.config.height as f64);
+        let clamped = viewport.round().intersect(surface);
+        // A viewport entirely off the surface intersects to a flipped rect
+        if clamped.width() <= 0.0 || clamped.height() <= 0.0 {
+            Rect::ZERO
+        } else {
+            clamped
+        }
+    }
+
+    /// The surface's size in pixels, after any clamping in [`Self::resize`].
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.surface.config.width, self.surface.config.height)
+    }
+
+    /// Reconfigures the surface straight away. The target texture the scene is
+    /// rendered into is only reallocated once the size has held for 50ms; until
+    /// then the old one is drawn into, scaled to fit, and stretched over the
+    /// surface, or with [`Self::set_smooth_resize`] a low-resolution one is.
+    /// See [`Self::resize_settles_at`].
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
         if size.width == 0 || size.height == 0 {
             return;
         }
+        let device_max = self.vello_context.devices[self.surface.dev_id].device.limits().max_texture_dimension_2d;
+        let max = self.max_size.unwrap_or(PhysicalSize::new(u32::MAX, u32::MAX));
+        let size = PhysicalSize::new(
+            size.width.min(max.width).min(device_max),
+            size.height.min(max.height).min(device_max),
+        );
         self.vello_context.resize_surface(&mut self.surface, size.width, size.height);
-        // Invalidate target texture so it gets recreated
-        self.target_texture = None;
-        self.blit_bind_group = None;
+        // Dragging a window edge resizes every frame, and reallocating each time is wasted work
+        if self.target_texture.is_some() {
+            self.resized_at = Some(Instant::now());
+            // Small enough to reallocate at every size
+            if self.smooth_resize {
+                self.target_texture = None;
+            }
+        }
+    }
+
+    // The frame, moved into `viewport` if there is one, then scaled by `scale`
+    // from surface to target texture pixels
+    fn build_scene(&mut self, width: u32, height: u32, viewport: Option<Rect>, scale: Vec2) {
+        let mut scene = std::mem::take(&mut self.scene);
+        scene.reset();
+        if viewport.is_none() && scale == Vec2::new(1.0, 1.0) {
+            self.draw_frame(&mut scene, width, height);
+        } else {
+            let area = viewport.unwrap_or(Rect::new(0.0, 0.0, width as f64, height as f64));
+            let mut unplaced = std::mem::take(&mut self.unplaced_scene);
+            unplaced.reset();
+            self.draw_frame(&mut unplaced, area.width() as u32, area.height() as u32);
+            let to_texture = Affine::scale_non_uniform(scale.x, scale.y);
+            scene.push_clip_layer(to_texture, &area);
+            scene.append(&unplaced, Some(to_texture * Affine::translate(area.origin().to_vec2())));
+            scene.pop_layer();
+            self.unplaced_scene = unplaced;
+        }
+        self.scene = scene;
+    }
+
+    // The app's content, or the rotating rainbow triangle demo, with user paths
+    // on top, as if the surface were `width` x `height`
+    fn draw_frame(&mut self, scene: &mut Scene, width: u32, height: u32) {
+        if let Some((regions, painter)) = &mut self.regions {
+            regions.build(scene, Size::new(width as f64, height as f64), painter);
+        } else if let Some(content) = &self.content {
+            scene.append(content, None);
+        } else {
+            let time = self.clock().as_secs_f64();
+            let center = vello::kurbo::Point::new(width as f64 / 2.0, height as f64 / 2.0);
+            let radius = 200.0;
         
-        // Re-enable synchronous render for smooth resizing -> Reverted due to lag
-        // self.render();
+            // Create a triangle path
+            let mut path = vello::kurbo::BezPath::new();
+            for i in 0..3 {
+                let angle = time + (i as f64) * (2.0 * std::f64::consts::PI / 3.0);
+                let point = vello::kurbo::Point::new(
+                    center.x + radius * angle.cos(),
+                    center.y + radius * angle.sin(),
+                );
+                if i == 0 {
+                    path.move_to(point);
+                } else {
+                    path.line_to(point);
+                }
+            }
+            path.close_path();
+
+            let gradient = crate::brush::sweep_gradient(
+                center,
+                0.0,
+                std::f64::consts::PI as f32 * 2.0,
+                crate::brush::RAINBOW,
+            );
+
+            scene.fill(
+                vello::peniko::Fill::NonZero,
+                vello::kurbo::Affine::rotate_about(time, center),
+                &gradient,
+                None,
+                &path
+            );
+        }
+
+        scene.append(&self.user_scene, None);
     }
 
+    fn ensure_renderer(&mut self) {
+        let device = &self.vello_context.devices[self.surface.dev_id].device;
+        if self.renderers.len() <= self.surface.dev_id {
+            self.renderers.resize_with(self.surface.dev_id + 1, || None);
+        }
+        if self.renderers[self.surface.dev_id].is_none() {
+            let renderer = vello::Renderer::new(
+                device,
+                RendererOptions {
+                    antialiasing_support: vello::AaSupport::all(),
+                    num_init_threads: None,
+                    pipeline_cache: None,
+                    use_cpu: self.use_cpu,
+                },
+            ).expect("failed to create renderer");
+            self.renderers[self.surface.dev_id] = Some(renderer);
+        }
+    }
+
+    /// Renders the current frame into `view` instead of the window, without
+    /// touching or presenting the surface. `width` and `height` are the view's
+    /// size in pixels and are what the scene is laid out against.
+    ///
+    /// The view must come from a texture created on this context's device, be
+    /// `Rgba8Unorm` and have `STORAGE_BINDING` usage, since vello writes it from a
+    /// compute shader. Add `TEXTURE_BINDING` to sample it afterwards. The work is
+    /// submitted on this context's queue before returning. The post-process hook
+    /// isn't run. Like vello's own output, the texture holds straight alpha,
+    /// not the premultiplied alpha the window is shown with.
+    pub fn render_to_external(&mut self, view: &wgpu::TextureView, width: u32, height: u32) -> anyhow::Result<()> {
+        if let Some(reason) = self.device_lost() {
+            anyhow::bail!("GPU device lost: {}", reason);
+        }
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.build_scene(width, height, None, Vec2::new(1.0, 1.0));
+        self.ensure_renderer();
+        let background_color = self.background_at(self.clock.elapsed());
+
+        let device = &self.vello_context.devices[self.surface.dev_id].device;
+        let queue = &self.vello_context.devices[self.surface.dev_id].queue;
+        let renderer = self.renderers[self.surface.dev_id].as_mut().unwrap();
+        renderer.render_to_texture(
+            device,
+            queue,
+            &self.scene,
+            view,
+            &vello::RenderParams {
+                base_color: background_color,
+                width,
+                height,
+                antialiasing_method: vello::AaConfig::Area,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Renders the content, or the demo, with user paths on top, and presents
+    /// it. The scene handed to vello is put together afresh each time, so
+    /// this can be called again for the same content, e.g. after a resize.
+    ///
+    /// With the `profiling` feature each stage gets a span: building the
+    /// scene, vello's render, the blit, and acquiring and presenting the
+    /// surface texture. GPU work runs asynchronously, so the render and blit
+    /// spans time encoding and submitting it; waiting on the GPU or for vsync
+    /// shows up under acquire and present.
     pub fn render(&mut self) {
+        profile_scope!("render");
         let width = self.surface.config.width;
         let height = self.surface.config.height;
         if width == 0 || height == 0 { return; }
+        // Anything submitted to a lost device would just error
+        if self.device_lost.lock().unwrap().is_some() { return; }
 
-        let device = &self.vello_context.devices[self.surface.dev_id].device;
-        let queue = &self.vello_context.devices[self.surface.dev_id].queue;
- 
-        // 0. Update Scene Content (Rotating Rainbow Triangle)
-        self.scene.reset(); 
-        
-        let time = self.start_time.elapsed().as_secs_f64();
-        let center = vello::kurbo::Point::new(width as f64 / 2.0, height as f64 / 2.0);
-        let radius = 200.0;
-        
-        // Create a triangle path
-        let mut path = vello::kurbo::BezPath::new();
-        for i in 0..3 {
-            let angle = time + (i as f64) * (2.0 * std::f64::consts::PI / 3.0);
-            let point = vello::kurbo::Point::new(
-                center.x + radius * angle.cos(),
-                center.y + radius * angle.sin(),
-            );
-            if i == 0 {
-                path.move_to(point);
-            } else {
-                path.line_to(point);
-            }
+        let now = self.clock.elapsed();
+        let background_color = self.background_at(now);
+        // Only forgotten once a frame in the final colour is on screen
+        let fade_done = self.background_fade.as_ref().is_some_and(|(_, fade)| fade.is_finished(now));
+
+        if self.resize_settles_at().is_some_and(|at| at <= Instant::now()) {
+            self.target_texture = None;
+            self.resized_at = None;
         }
-        path.close_path();
-
-        // Rainbow gradient
-        let stops = [
-            vello::peniko::ColorStop { offset: 0.0, color: vello::peniko::Color::from_rgb8(255, 0, 0).into() },
-            vello::peniko::ColorStop { offset: 0.14, color: vello::peniko::Color::from_rgb8(255, 165, 0).into() },
-            vello::peniko::ColorStop { offset: 0.28, color: vello::peniko::Color::from_rgb8(255, 255, 0).into() },
-            vello::peniko::ColorStop { offset: 0.42, color: vello::peniko::Color::from_rgb8(0, 128, 0).into() },
-            vello::peniko::ColorStop { offset: 0.57, color: vello::peniko::Color::from_rgb8(0, 0, 255).into() },
-            vello::peniko::ColorStop { offset: 0.71, color: vello::peniko::Color::from_rgb8(75, 0, 130).into() },
-            vello::peniko::ColorStop { offset: 0.85, color: vello::peniko::Color::from_rgb8(238, 130, 238).into() },
-            vello::peniko::ColorStop { offset: 1.0, color: vello::peniko::Color::from_rgb8(255, 0, 0).into() },
-        ];
-        
-        let gradient = vello::peniko::Gradient::new_sweep(
-            center,
-            0.0,
-            std::f64::consts::PI as f32 * 2.0,
-        ).with_stops(stops.as_slice());
-
-        self.scene.fill(
-            vello::peniko::Fill::NonZero,
-            vello::kurbo::Affine::rotate_about(time, center),
-            &gradient,
-            None,
-            &path
+        // Until then the old texture stands in, with the scene scaled to fit it
+        let (texture_width, texture_height) = match &self.target_texture {
+            Some(texture) => (texture.width(), texture.height()),
+            None => self.target_size(width, height),
+        };
+        let scale = Vec2::new(
+            texture_width as f64 / width as f64,
+            texture_height as f64 / height as f64,
         );
 
+        // 0. Update Scene Content
+        let viewport = self.viewport.map(|viewport| self.clamped_viewport(viewport));
+        {
+            profile_scope!("build scene");
+            self.build_scene(width, height, viewport, scale);
+        }
+        self.ensure_renderer();
+
+        // vello writes straight alpha, which only matches what the blit
+        // presents while the frame is opaque
+        if self.direct_surface
+            && background_color.components[3] >= 1.0
+            && viewport.is_none()
+            && self.post_process.is_none()
+            && (texture_width, texture_height) == (width, height)
+        {
+            self.render_direct(background_color, width, height, fade_done);
+            return;
+        }
+
+        let device = &self.vello_context.devices[self.surface.dev_id].device;
+        let queue = &self.vello_context.devices[self.surface.dev_id].queue;
+
         // 1. Initialize target_texture if needed
         if self.target_texture.is_none() {
              let texture = device.create_texture(&wgpu::TextureDescriptor {
                 label: Some("Vello Target Texture"),
                 size: wgpu::Extent3d {
-                    width,
-                    height,
+                    width: texture_width,
+                    height: texture_height,
                     depth_or_array_layers: 1,
                 },
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8Unorm,
-                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+                usage: wgpu::TextureUsages::STORAGE_BINDING
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC,
                 view_formats: &[],
              });
              self.target_texture = Some(texture);
@@ -255,86 +1087,486 @@ impl RenderContext {
 
         // 2. Initialize Blit Bind Group if needed
         if self.blit_bind_group.is_none() {
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Blit Bind Group"),
-                layout: &self.blit_bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&target_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&self.blit_sampler),
-                    },
-                ],
-            });
+            let bind_group = create_blit_bind_group(device, &self.blit_bind_group_layout, &target_view, &self.blit_sampler);
             self.blit_bind_group = Some(bind_group);
         }
 
-        // 3. Ensure renderer exists
-        if self.renderers.len() <= self.surface.dev_id {
-             self.renderers.resize_with(self.surface.dev_id + 1, || None);
-        }
-        if self.renderers[self.surface.dev_id].is_none() {
-             let renderer = vello::Renderer::new(
+        let renderer = self.renderers[self.surface.dev_id].as_mut().unwrap();
+
+        // 4. Render to intermediate texture
+        let rendered = {
+            profile_scope!("vello render");
+            renderer.render_to_texture(
                 device,
-                RendererOptions {
-                    antialiasing_support: vello::AaSupport::all(),
-                    num_init_threads: None,
-                    pipeline_cache: None,
-                    use_cpu: self.use_cpu,
+                queue,
+                &self.scene,
+                &target_view,
+                &vello::RenderParams {
+                    base_color: background_color,
+                    width: texture_width,
+                    height: texture_height,
+                    antialiasing_method: vello::AaConfig::Area,
                 },
-            ).expect("failed to create renderer");
-            self.renderers[self.surface.dev_id] = Some(renderer);
+            )
+        };
+        if let Err(e) = rendered {
+            log::error!("failed to render to intermediate texture: {}", e);
+            return;
         }
 
-        let renderer = self.renderers[self.surface.dev_id].as_mut().unwrap();
+        // 5. Blit to surface
+        let Some(surface_texture) = self.current_surface_texture() else {
+            return;
+        };
+        let device = &self.vello_context.devices[self.surface.dev_id].device;
+        let queue = &self.vello_context.devices[self.surface.dev_id].queue;
 
-        // 4. Render to intermediate texture
-        renderer
-            .render_to_texture(
+        let surface_view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Clear colours are linear, and only get encoded on the way into an sRGB surface
+        let background = if self.is_srgb() {
+            background_color.convert::<LinearSrgb>().components
+        } else {
+            background_color.components
+        };
+        let [r, g, b, a] = background.map(f64::from);
+        let clear_color = wgpu::Color { r, g, b, a };
+        {
+            profile_scope!("blit");
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Blit Encoder") });
+            if let Some(post_process) = &mut self.post_process {
+                post_process(&mut encoder, &target_view);
+            }
+            {
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Blit Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &surface_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            // Only shows outside the viewport
+                            load: wgpu::LoadOp::Clear(clear_color),
+                            store: wgpu::StoreOp::Store, // Store the result
+                        },
+                        depth_slice: None,
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                // The blit stretches the whole target texture over the surface, so
+                // scissoring to the viewport shows the scene where it was drawn
+                if let Some(viewport) = viewport {
+                    rpass.set_scissor_rect(
+                        viewport.x0 as u32,
+                        viewport.y0 as u32,
+                        viewport.width() as u32,
+                        viewport.height() as u32,
+                    );
+                }
+                rpass.set_pipeline(&self.blit_pipeline);
+                rpass.set_bind_group(0, self.blit_bind_group.as_ref().unwrap(), &[]);
+                rpass.draw(0..3, 0..1);
+            }
+
+            queue.submit(Some(encoder.finish()));
+        }
+        {
+            profile_scope!("present");
+            surface_texture.present();
+        }
+        self.dirty = false;
+        if fade_done {
+            self.background_fade = None;
+        }
+    }
+
+    // The frame already built into `scene`, rendered by vello straight into
+    // the surface texture
+    fn render_direct(&mut self, background_color: Color, width: u32, height: u32, fade_done: bool) {
+        let Some(surface_texture) = self.current_surface_texture() else {
+            return;
+        };
+        let view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let device = &self.vello_context.devices[self.surface.dev_id].device;
+        let queue = &self.vello_context.devices[self.surface.dev_id].queue;
+        let renderer = self.renderers[self.surface.dev_id].as_mut().unwrap();
+        let rendered = {
+            profile_scope!("vello render");
+            renderer.render_to_texture(
                 device,
                 queue,
                 &self.scene,
-                &target_view,
+                &view,
                 &vello::RenderParams {
-                    base_color: Color::from_rgb8(20, 20, 20),
+                    base_color: background_color,
                     width,
                     height,
                     antialiasing_method: vello::AaConfig::Area,
                 },
             )
-            .expect("failed to render to intermediate texture");
+        };
+        if let Err(e) = rendered {
+            log::error!("failed to render to surface: {}", e);
+            return;
+        }
+        {
+            profile_scope!("present");
+            surface_texture.present();
+        }
+        self.dirty = false;
+        if fade_done {
+            self.background_fade = None;
+        }
+    }
 
-        // 5. Blit to surface
-        let surface_texture = match self.surface.surface.get_current_texture() {
-            Ok(texture) => texture,
+    // The texture to draw this frame into, or `None` if the surface can't
+    // give one out right now, which is logged
+    fn current_surface_texture(&mut self) -> Option<wgpu::SurfaceTexture> {
+        profile_scope!("acquire surface");
+        match self.surface.surface.get_current_texture() {
+            Ok(texture) => {
+                self.surface_timeout.clear();
+                self.surface_outdated.clear();
+                Some(texture)
+            }
             Err(wgpu::SurfaceError::Timeout) => {
-                log::warn!("Surface timeout");
-                return;
+                self.surface_timeout.warn();
+                None
             }
             Err(wgpu::SurfaceError::Outdated) | Err(wgpu::SurfaceError::Lost) => {
-                 // Reconfigure or ignore, usually resize handles this next frame. 
-                 // We can return early.
-                 log::warn!("Surface outdated/lost");
-                 return;
+                // Usually a resize reconfigures the surface before the next frame
+                self.surface_outdated.warn();
+                None
             }
             Err(e) => panic!("failed to get surface texture: {:?}", e),
-        };
-        
-        let surface_view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        }
+    }
+}
+
+// The frame latency wgpu 26 configures when asked for `frames`, which it
+// doesn't report. `None` where it depends on the surface, like Vulkan's
+// swapchain image counts.
+fn effective_frame_latency(backend: wgpu::Backend, frames: u32) -> Option<u32> {
+    match backend {
+        wgpu::Backend::Dx12 => Some(frames.clamp(1, 16)),
+        wgpu::Backend::Metal => Some(frames.clamp(1, 2)),
+        wgpu::Backend::Gl => Some(2),
+        _ => None,
+    }
+}
+
+// Draws the target texture over a whole surface of `format`, premultiplying
+// its alpha on the way
+fn create_blit_pipeline(device: &wgpu::Device, format: wgpu::TextureFormat) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Blit Shader"),
+        source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(r#"
+            struct VertexOutput {
+                @builtin(position) position: vec4<f32>,
+                @location(0) uv: vec2<f32>,
+            };
+
+            @vertex
+            fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+                var out: VertexOutput;
+                var positions = array<vec2<f32>, 3>(
+                    vec2<f32>(-1.0, -1.0),
+                    vec2<f32>(3.0, -1.0),
+                    vec2<f32>(-1.0, 3.0)
+                );
+                out.position = vec4<f32>(positions[vertex_index], 0.0, 1.0);
+                out.uv = positions[vertex_index] * 0.5 + 0.5;
+                out.uv.y = 1.0 - out.uv.y; 
+                return out;
+            }
+
+            @group(0) @binding(0) var t_diffuse: texture_2d<f32>;
+            @group(0) @binding(1) var s_diffuse: sampler;
+
+            // vello stores straight alpha, and surfaces composite
+            // premultiplied, so the colour is scaled by alpha on the way out
+            @fragment
+            fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+                let color = textureSample(t_diffuse, s_diffuse, in.uv);
+                return vec4<f32>(color.rgb * color.a, color.a);
+            }
+
+            // The target holds sRGB-encoded values, which an sRGB surface
+            // would encode a second time, so decode them first
+            @fragment
+            fn fs_main_srgb(in: VertexOutput) -> @location(0) vec4<f32> {
+                let color = textureSample(t_diffuse, s_diffuse, in.uv);
+                let low = color.rgb / 12.92;
+                let high = pow((color.rgb + 0.055) / 1.055, vec3<f32>(2.4));
+                let linear = select(high, low, color.rgb <= vec3<f32>(0.04045));
+                return vec4<f32>(linear * color.a, color.a);
+            }
+        "#)),
+    });
+
+    let blit_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Blit Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Blit Pipeline Layout"),
+        bind_group_layouts: &[&blit_bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Blit Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some(if format.is_srgb() { "fs_main_srgb" } else { "fs_main" }),
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                // vello has already composited the frame over the
+                // background, so blending it over the clear colour would
+                // apply the background twice wherever it's translucent
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+
+    (blit_pipeline, blit_bind_group_layout)
+}
+
+fn create_blit_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, view: &wgpu::TextureView, sampler: &wgpu::Sampler) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Blit Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+fn create_blit_sampler(device: &wgpu::Device, filter: wgpu::FilterMode) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    })
+}
+
+// Like vello's `create_surface`, except that it picks between sRGB and linear
+// formats instead of only accepting linear ones
+async fn create_surface(
+    vello_context: &mut VelloRenderContext,
+    window: Arc<Window>,
+    srgb: bool,
+) -> Result<RenderSurface<'static>, vello::Error> {
+    let size = window.inner_size();
+    let surface = vello_context.instance.create_surface(window)?;
+    let dev_id = vello_context.device(Some(&surface)).await.ok_or(vello::Error::NoCompatibleDevice)?;
+    let device_handle = &vello_context.devices[dev_id];
+    let device = &device_handle.device;
+
+    let formats = surface.get_capabilities(device_handle.adapter()).formats;
+    // The blit can write any 8-bit RGBA or BGRA format
+    let usable = |format: &&wgpu::TextureFormat| {
+        matches!(
+            format.remove_srgb_suffix(),
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Bgra8Unorm
+        )
+    };
+    let format = match formats.iter().filter(usable).find(|format| format.is_srgb() == srgb) {
+        Some(format) => *format,
+        None => {
+            let format = *formats.iter().find(usable).ok_or(vello::Error::UnsupportedSurfaceFormat)?;
+            log::warn!("No {} surface format available; using {:?}", if srgb { "sRGB" } else { "linear" }, format);
+            format
+        }
+    };
+
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::AutoVsync,
+        desired_maximum_frame_latency: 2,
+        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        view_formats: vec![],
+    };
+    surface.configure(device, &config);
+    // Unused, since we render into our own target texture, but RenderSurface needs them
+    let target_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let target_view = target_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    Ok(RenderSurface {
+        surface,
+        config,
+        dev_id,
+        format,
+        target_texture,
+        target_view,
+        blitter: wgpu::util::TextureBlitter::new(device, format),
+    })
+}
+
+pub trait PollsterBlockOn {
+    type Output;
+    fn pollster_block_on(self) -> Self::Output;
+}
+
+impl<F: std::future::Future> PollsterBlockOn for F {
+    type Output = F::Output;
+    fn pollster_block_on(self) -> Self::Output {
+        pollster::block_on(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use vello::peniko::Mix;
+
+    use super::*;
+    use crate::components::DrawCtx;
+    use crate::theme::Theme;
+
+    const BOUNDS: Rect = Rect::new(0.0, 0.0, 16.0, 16.0);
+
+    fn open_layers(scene: &Scene) -> u32 {
+        scene.encoding().n_open_clips
+    }
+
+    #[test]
+    fn clips_are_popped_when_drawing_panics() {
+        let mut scene = Scene::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            with_clip(&mut scene, Affine::IDENTITY, &BOUNDS, |scene| {
+                with_clip(scene, Affine::IDENTITY, &BOUNDS, |_| panic!("widget failed to draw"));
+            });
+        }));
+        assert!(result.is_err());
+        assert_eq!(open_layers(&scene), 0);
 
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Blit Encoder") });
+        let theme = Theme::default();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut ctx = DrawCtx::new(&mut scene, &theme);
+            ctx.with_clip(&BOUNDS, |_| panic!("widget failed to draw"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(open_layers(&scene), 0);
+    }
+
+    #[test]
+    fn blend_layers_are_popped_when_drawing_panics() {
+        let mut scene = Scene::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            with_blend(&mut scene, Mix::Screen, 0.5, &BOUNDS, |_| panic!("widget failed to draw"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(open_layers(&scene), 0);
+
+        let theme = Theme::default();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut ctx = DrawCtx::new(&mut scene, &theme);
+            ctx.with_blend(Mix::Multiply, 1.0, &BOUNDS, |_| panic!("widget failed to draw"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(open_layers(&scene), 0);
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod readback_tests {
+    use image::Rgba;
+
+    use super::*;
+    use crate::testing::{self, HeadlessRenderer};
+
+    const SIZE: u32 = 16;
+
+    // Renders `scene` over `base_color` and blits it as the window would, onto
+    // a linear target
+    fn composite(renderer: &mut HeadlessRenderer, scene: &Scene, base_color: Color) -> Rgba<u8> {
+        let rendered = renderer.render_to_texture(scene, SIZE, SIZE, base_color).unwrap();
+        let device = renderer.device();
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let (pipeline, layout) = create_blit_pipeline(device, format);
+        let sampler = create_blit_sampler(device, wgpu::FilterMode::Nearest);
+        let view = rendered.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = create_blit_bind_group(device, &layout, &view, &sampler);
+        let surface = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Composite Texture"),
+            size: wgpu::Extent3d { width: SIZE, height: SIZE, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let surface_view = surface.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Composite Encoder") });
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Blit Pass"),
+                label: Some("Composite Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                     view: &surface_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLUE),
-                        store: wgpu::StoreOp::Store, // Store the result
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
                 })],
@@ -342,24 +1574,32 @@ impl RenderContext {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
-            rpass.set_pipeline(&self.blit_pipeline);
-            rpass.set_bind_group(0, self.blit_bind_group.as_ref().unwrap(), &[]);
+            rpass.set_pipeline(&pipeline);
+            rpass.set_bind_group(0, &bind_group, &[]);
             rpass.draw(0..3, 0..1);
         }
+        renderer.queue().submit(Some(encoder.finish()));
+        *testing::read_texture(device, renderer.queue(), &surface).unwrap().get_pixel(SIZE / 2, SIZE / 2)
+    }
 
-        queue.submit(Some(encoder.finish()));
-        surface_texture.present();
+    fn assert_close(actual: Rgba<u8>, expected: [u8; 4]) {
+        let close = actual.0.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 2);
+        assert!(close, "{actual:?} is not close to {expected:?}");
     }
-}
 
-pub trait PollsterBlockOn {
-    type Output;
-    fn pollster_block_on(self) -> Self::Output;
-}
+    #[test]
+    fn blit_composites_premultiplied() {
+        let Some(mut renderer) = testing::headless() else {
+            return;
+        };
+        let mut scene = Scene::new();
+        let half_red = Color::from_rgba8(255, 0, 0, 128);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, half_red, None, &Rect::new(0.0, 0.0, SIZE as f64, SIZE as f64));
 
-impl<F: std::future::Future> PollsterBlockOn for F {
-    type Output = F::Output;
-    fn pollster_block_on(self) -> Self::Output {
-        pollster::block_on(self)
+        // A transparent window shows the shape with its colour scaled by its alpha,
+        // which is what keeps its edges from darkening
+        assert_close(composite(&mut renderer, &scene, Color::TRANSPARENT), [128, 0, 0, 128]);
+        // Over an opaque base colour it's blended in and comes out opaque
+        assert_close(composite(&mut renderer, &scene, Color::from_rgb8(0, 0, 255)), [128, 0, 127, 255]);
     }
 }
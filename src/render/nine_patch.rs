@@ -0,0 +1,125 @@
+use vello::Scene;
+use vello::kurbo::{Affine, Insets, Rect};
+use vello::peniko::{Blob, Fill, ImageBrush, ImageData};
+
+/// An image split into a 3x3 grid by inset margins, for backgrounds that
+/// stretch to any size: the corners are drawn as they are, the edges stretch
+/// along their length and the centre stretches both ways.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NinePatch {
+    // Row by row, each cut out of the image so filtering can't bleed in pixels
+    // from its neighbours. `None` where an inset is zero.
+    patches: [Option<ImageBrush>; 9],
+    // In image pixels, clamped to fit the image
+    insets: Insets,
+    scale: f64,
+}
+
+impl NinePatch {
+    /// `insets` are the widths of the fixed borders, in image pixels, and are
+    /// rounded to whole ones.
+    pub fn new(image: impl Into<ImageBrush>, insets: Insets) -> Self {
+        let image = image.into();
+        let (width, height) = (image.image.width as f64, image.image.height as f64);
+        let x0 = insets.x0.round().clamp(0.0, width);
+        let y0 = insets.y0.round().clamp(0.0, height);
+        let insets = Insets {
+            x0,
+            y0,
+            x1: insets.x1.round().clamp(0.0, width - x0),
+            y1: insets.y1.round().clamp(0.0, height - y0),
+        };
+        let columns = [0, x0 as u32, (width - insets.x1) as u32, image.image.width];
+        let rows = [0, y0 as u32, (height - insets.y1) as u32, image.image.height];
+        let patches = std::array::from_fn(|index| {
+            let (row, column) = (index / 3, index % 3);
+            let x = columns[column]..columns[column + 1];
+            let y = rows[row]..rows[row + 1];
+            (!x.is_empty() && !y.is_empty()).then(|| ImageBrush {
+                image: crop(&image.image, x, y),
+                sampler: image.sampler,
+            })
+        });
+        Self {
+            patches,
+            insets,
+            scale: 1.0,
+        }
+    }
+
+    /// How many target pixels one image pixel of the borders covers, e.g. the
+    /// window's scale factor for artwork made at 1x. Defaults to 1.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale.max(0.0);
+        self
+    }
+
+    pub fn insets(&self) -> Insets {
+        self.insets
+    }
+
+    /// Stretches the image over `target`.
+    ///
+    /// Borders keep the same scale on both axes, shrinking together when
+    /// `target` is too small to fit them, so corners never distort. Patch
+    /// edges are snapped to whole pixels so neighbouring patches meet without
+    /// antialiased seams.
+    pub fn draw(&self, scene: &mut Scene, target: Rect) {
+        let target = target.round();
+        let Insets { x0: left, y0: top, x1: right, y1: bottom } = self.insets;
+        let mut scale = self.scale;
+        if left + right > 0.0 {
+            scale = scale.min(target.width() / (left + right));
+        }
+        if top + bottom > 0.0 {
+            scale = scale.min(target.height() / (top + bottom));
+        }
+
+        let columns = [
+            target.x0,
+            (target.x0 + left * scale).round(),
+            (target.x1 - right * scale).round(),
+            target.x1,
+        ];
+        let rows = [
+            target.y0,
+            (target.y0 + top * scale).round(),
+            (target.y1 - bottom * scale).round(),
+            target.y1,
+        ];
+
+        for (index, patch) in self.patches.iter().enumerate() {
+            let (row, column) = (index / 3, index % 3);
+            let destination = Rect::new(columns[column], rows[row], columns[column + 1], rows[row + 1]);
+            if let Some(patch) = patch
+                && !destination.is_zero_area()
+            {
+                let brush_transform = Affine::translate(destination.origin().to_vec2())
+                    * Affine::scale_non_uniform(
+                        destination.width() / patch.image.width as f64,
+                        destination.height() / patch.image.height as f64,
+                    );
+                scene.fill(Fill::NonZero, Affine::IDENTITY, patch, Some(brush_transform), &destination);
+            }
+        }
+    }
+}
+
+// Copies a block of pixels out of `image`. Every format peniko has is 4 bytes a pixel.
+fn crop(image: &ImageData, x: std::ops::Range<u32>, y: std::ops::Range<u32>) -> ImageData {
+    let stride = image.width as usize * 4;
+    let bytes = image.data.data();
+    let (width, height) = (x.len() as u32, y.len() as u32);
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in y {
+        let start = row as usize * stride + x.start as usize * 4;
+        pixels.extend_from_slice(&bytes[start..start + width as usize * 4]);
+    }
+    ImageData {
+        data: Blob::new(std::sync::Arc::new(pixels)),
+        format: image.format,
+        alpha_type: image.alpha_type,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Context;
+use image::RgbaImage;
+use vello::wgpu;
+
+/// Blocks until the GPU has finished all work submitted to `device`, running
+/// any callbacks waiting on it, such as [`wgpu::BufferSlice::map_async`]'s.
+pub fn poll_device(device: &wgpu::Device) -> anyhow::Result<()> {
+    device.poll(wgpu::PollType::Wait).context("failed to wait for the GPU")?;
+    Ok(())
+}
+
+/// Copies an `Rgba8Unorm` texture with `COPY_SRC` usage back to the CPU,
+/// waiting for the GPU to finish.
+pub fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> anyhow::Result<RgbaImage> {
+    let (width, height) = (texture.width(), texture.height());
+    // Rows in a copy have to start at multiples of 256 bytes
+    let row_bytes = width * 4;
+    let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: padded_row_bytes as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Capture Encoder") });
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    poll_device(device)?;
+    receiver.recv()?.context("failed to map the capture buffer")?;
+
+    let mapped = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+    for row in mapped.chunks_exact(padded_row_bytes as usize) {
+        pixels.extend_from_slice(&row[..row_bytes as usize]);
+    }
+    drop(mapped);
+    buffer.unmap();
+    RgbaImage::from_raw(width, height, pixels).context("capture buffer was the wrong size")
+}
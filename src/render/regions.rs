@@ -0,0 +1,153 @@
+use vello::Scene;
+use vello::kurbo::{Affine, Rect, Size};
+
+/// A frame split into square tiles, each with its own [`Scene`] that's only
+/// redrawn once something marks it dirty, so a change to one small part of a
+/// large UI doesn't re-encode all of it.
+///
+/// [`Self::build`] calls the painter once for each dirty tile, with the
+/// tile's rect, and appends every tile's scene into the frame. What the
+/// painter draws is clipped to the tile, so it can draw everything and leave
+/// culling to the clip, but the time saved comes from skipping whatever lies
+/// outside the rect it's given.
+///
+/// ```ignore
+/// let mut regions = SceneRegions::new(256.0);
+/// regions.invalidate_region(button_bounds);
+/// regions.build(&mut frame, window_size, |scene, tile| {
+///     for widget in widgets.iter().filter(|w| w.bounds().overlaps(tile)) {
+///         widget.draw(scene);
+///     }
+/// });
+/// ```
+pub struct SceneRegions {
+    tile_size: f64,
+    // Row by row, covering `size`
+    tiles: Vec<Tile>,
+    columns: usize,
+    size: Size,
+}
+
+struct Tile {
+    scene: Scene,
+    dirty: bool,
+}
+
+impl SceneRegions {
+    /// Tiles `tile_size` pixels square. Smaller tiles redraw less around a
+    /// change but cost more to combine each frame.
+    pub fn new(tile_size: f64) -> Self {
+        Self {
+            tile_size: tile_size.max(1.0),
+            tiles: Vec::new(),
+            columns: 0,
+            size: Size::ZERO,
+        }
+    }
+
+    pub fn tile_size(&self) -> f64 {
+        self.tile_size
+    }
+
+    /// Marks every tile `rect` touches to be redrawn on the next build.
+    pub fn invalidate_region(&mut self, rect: Rect) {
+        let rect = rect.intersect(Rect::from_origin_size((0.0, 0.0), self.size));
+        if rect.is_zero_area() || self.columns == 0 {
+            return;
+        }
+        let first_column = (rect.x0 / self.tile_size).floor() as usize;
+        let last_column = ((rect.x1 / self.tile_size).ceil() as usize).min(self.columns);
+        let first_row = (rect.y0 / self.tile_size).floor() as usize;
+        let last_row = ((rect.y1 / self.tile_size).ceil() as usize).min(self.tiles.len() / self.columns);
+        for row in first_row..last_row {
+            for column in first_column..last_column {
+                self.tiles[row * self.columns + column].dirty = true;
+            }
+        }
+    }
+
+    /// Marks every tile to be redrawn on the next build.
+    pub fn invalidate_all(&mut self) {
+        for tile in &mut self.tiles {
+            tile.dirty = true;
+        }
+    }
+
+    /// How many tiles the next build will redraw.
+    pub fn dirty_tiles(&self) -> usize {
+        self.tiles.iter().filter(|tile| tile.dirty).count()
+    }
+
+    /// Redraws the dirty tiles of a frame of `size` with `paint` and appends
+    /// all of them to `scene`. A new size redraws everything.
+    pub fn build(&mut self, scene: &mut Scene, size: Size, mut paint: impl FnMut(&mut Scene, Rect)) {
+        if size != self.size {
+            self.resize(size);
+        }
+        for (index, tile) in self.tiles.iter_mut().enumerate() {
+            let column = index % self.columns;
+            let row = index / self.columns;
+            let origin = (column as f64 * self.tile_size, row as f64 * self.tile_size);
+            let rect = Rect::from_origin_size(origin, (self.tile_size, self.tile_size)).intersect(Rect::from_origin_size((0.0, 0.0), size));
+            if tile.dirty {
+                tile.scene.reset();
+                tile.scene.push_clip_layer(Affine::IDENTITY, &rect);
+                paint(&mut tile.scene, rect);
+                tile.scene.pop_layer();
+                tile.dirty = false;
+            }
+            scene.append(&tile.scene, None);
+        }
+    }
+
+    fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.columns = (size.width / self.tile_size).ceil().max(0.0) as usize;
+        let rows = (size.height / self.tile_size).ceil().max(0.0) as usize;
+        self.tiles.clear();
+        self.tiles.resize_with(self.columns * rows, || Tile {
+            scene: Scene::new(),
+            dirty: true,
+        });
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use image::Rgba;
+    use vello::peniko::{Color, Fill};
+
+    use super::*;
+
+    #[test]
+    fn only_dirty_tiles_are_repainted() {
+        let Some(mut renderer) = crate::testing::headless() else {
+            return;
+        };
+        let size = Size::new(64.0, 64.0);
+        let mut regions = SceneRegions::new(32.0);
+        let mut build = |regions: &mut SceneRegions, color: Color| {
+            let mut painted = 0;
+            let mut frame = Scene::new();
+            // Paints the whole frame each time, leaving the tile's clip to cut it down
+            regions.build(&mut frame, size, |scene, _| {
+                painted += 1;
+                scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &Rect::from_origin_size((0.0, 0.0), size));
+            });
+            (painted, renderer.render(&frame, 64, 64, Color::BLACK).unwrap())
+        };
+
+        let (painted, _) = build(&mut regions, Color::WHITE);
+        assert_eq!(painted, 4);
+        // Only the top-right tile
+        regions.invalidate_region(Rect::new(40.0, 8.0, 50.0, 20.0));
+        let (painted, frame) = build(&mut regions, Color::from_rgb8(255, 0, 0));
+        assert_eq!(painted, 1);
+
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(*frame.get_pixel(48, 16), Rgba([255, 0, 0, 255]), "repainted");
+        assert_eq!(*frame.get_pixel(16, 16), white, "kept from the first build");
+        assert_eq!(*frame.get_pixel(16, 48), white);
+        assert_eq!(*frame.get_pixel(48, 48), white);
+    }
+}
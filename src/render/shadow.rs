@@ -0,0 +1,45 @@
+use vello::Scene;
+use vello::kurbo::{Affine, Rect, Vec2};
+use vello::peniko::Color;
+
+/// A soft drop shadow cast by a rounded rectangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    /// How far the shadow is shifted from the shape casting it.
+    pub offset: Vec2,
+    /// Roughly how far the blur spreads past the shape's edge, like CSS's
+    /// `box-shadow` blur radius.
+    pub blur_radius: f64,
+    pub color: Color,
+}
+
+impl Default for Shadow {
+    /// A subtle shadow just below the shape.
+    fn default() -> Self {
+        Self::new(Vec2::new(0.0, 2.0), 8.0, Color::BLACK.with_alpha(0.35))
+    }
+}
+
+impl Shadow {
+    pub fn new(offset: Vec2, blur_radius: f64, color: Color) -> Self {
+        Self {
+            offset,
+            blur_radius,
+            color,
+        }
+    }
+}
+
+/// Draws the shadow `rect` with corners of `corner_radius` would cast. Call it
+/// before drawing the shape itself, so the shadow ends up underneath.
+pub fn draw_shadow(scene: &mut Scene, rect: Rect, corner_radius: f64, shadow: &Shadow) {
+    // A blur radius covers about two standard deviations of the gaussian
+    let std_dev = (shadow.blur_radius / 2.0).max(0.0);
+    scene.draw_blurred_rounded_rect(
+        Affine::IDENTITY,
+        rect + shadow.offset,
+        shadow.color,
+        corner_radius,
+        std_dev,
+    );
+}
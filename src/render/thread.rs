@@ -0,0 +1,239 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use vello::Scene;
+use vello::wgpu;
+use vello::peniko::Color;
+use winit::dpi::PhysicalSize;
+use winit::window::Window;
+
+use super::{PollsterBlockOn, RenderContext, RenderDiagnostics};
+
+enum RenderCommand {
+    Resize(PhysicalSize<u32>),
+    Redraw,
+    SetBackground(Color),
+    AnimateBackground(Color, Duration),
+    SetPresentMode(wgpu::PresentMode),
+    SetFrameLatency(u32),
+    SetBlitFilter(wgpu::FilterMode),
+    SetRenderScale(f64),
+    SetSmoothResize(bool),
+    SetDirectSurface(bool),
+    SetTransparent(bool),
+    SetMaxSize(Option<PhysicalSize<u32>>),
+    // Boxed since a Scene is much bigger than every other command
+    SetContent(Option<Box<Scene>>),
+    Recover,
+    Shutdown,
+}
+
+/// Owns a [`RenderContext`] on a dedicated thread so encoding, blitting and
+/// presenting never block the event loop.
+///
+/// The context is created on the render thread and never leaves it, so it
+/// doesn't have to be `Send`; only the `Arc<Window>` crosses over, which winit
+/// allows. The UI thread talks to it through a command channel and the two
+/// share nothing else except the device-lost and diagnostics slots behind
+/// mutexes.
+///
+/// Commands that pile up while a frame is being presented are coalesced: only
+/// the latest resize is applied and any number of redraws become one frame.
+pub struct RenderThread {
+    commands: Sender<RenderCommand>,
+    device_lost: Arc<Mutex<Option<String>>>,
+    diagnostics: Arc<Mutex<Option<RenderDiagnostics>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RenderThread {
+    /// See [`RenderContext::new`] for `backends` and `srgb`.
+    pub fn spawn(window: Arc<Window>, use_cpu: bool, backends: Option<wgpu::Backends>, srgb: bool) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let device_lost = Arc::new(Mutex::new(None));
+        let lost_slot = device_lost.clone();
+        let diagnostics = Arc::new(Mutex::new(None));
+        let diagnostics_slot = diagnostics.clone();
+        let handle = std::thread::Builder::new()
+            .name("rasmalai-render".into())
+            .spawn(move || run(window, use_cpu, backends, srgb, receiver, lost_slot, diagnostics_slot))
+            .expect("failed to spawn render thread");
+        Self {
+            commands,
+            device_lost,
+            diagnostics,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn resize(&self, size: PhysicalSize<u32>) {
+        self.send(RenderCommand::Resize(size));
+    }
+
+    /// Asks for a frame. Returns immediately; the frame is drawn on the render thread.
+    pub fn request_redraw(&self) {
+        self.send(RenderCommand::Redraw);
+    }
+
+    pub fn set_background_color(&self, color: Color) {
+        self.send(RenderCommand::SetBackground(color));
+    }
+
+    /// See [`RenderContext::set_background_color_animated`]. The render thread
+    /// keeps drawing frames by itself until the fade is done.
+    pub fn set_background_color_animated(&self, color: Color, duration: Duration) {
+        self.send(RenderCommand::AnimateBackground(color, duration));
+    }
+
+    /// See [`RenderContext::set_present_mode`].
+    pub fn set_present_mode(&self, mode: wgpu::PresentMode) {
+        self.send(RenderCommand::SetPresentMode(mode));
+    }
+
+    /// See [`RenderContext::set_desired_maximum_frame_latency`].
+    pub fn set_desired_maximum_frame_latency(&self, frames: u32) {
+        self.send(RenderCommand::SetFrameLatency(frames));
+    }
+
+    /// See [`RenderContext::set_blit_filter`].
+    pub fn set_blit_filter(&self, filter: wgpu::FilterMode) {
+        self.send(RenderCommand::SetBlitFilter(filter));
+    }
+
+    /// See [`RenderContext::set_render_scale`].
+    pub fn set_render_scale(&self, scale: f64) {
+        self.send(RenderCommand::SetRenderScale(scale));
+    }
+
+    /// See [`RenderContext::set_smooth_resize`].
+    pub fn set_smooth_resize(&self, smooth: bool) {
+        self.send(RenderCommand::SetSmoothResize(smooth));
+    }
+
+    /// See [`RenderContext::set_direct_surface`].
+    pub fn set_direct_surface(&self, direct: bool) {
+        self.send(RenderCommand::SetDirectSurface(direct));
+    }
+
+    /// See [`RenderContext::set_transparent`].
+    pub fn set_transparent(&self, transparent: bool) {
+        self.send(RenderCommand::SetTransparent(transparent));
+    }
+
+    /// See [`RenderContext::set_max_size`].
+    pub fn set_max_size(&self, max_size: Option<PhysicalSize<u32>>) {
+        self.send(RenderCommand::SetMaxSize(max_size));
+    }
+
+    /// See [`RenderContext::set_content`].
+    pub fn set_content(&self, content: Option<Scene>) {
+        self.send(RenderCommand::SetContent(content.map(Box::new)));
+    }
+
+    /// Why the GPU device was lost, if the render thread has noticed it has been.
+    /// Frames are skipped until [`Self::recover`].
+    pub fn device_lost(&self) -> Option<String> {
+        self.device_lost.lock().unwrap().clone()
+    }
+
+    /// See [`RenderContext::diagnostics`]. As of the last frame the render
+    /// thread drew, and `None` until it has drawn one.
+    pub fn diagnostics(&self) -> Option<RenderDiagnostics> {
+        self.diagnostics.lock().unwrap().clone()
+    }
+
+    /// See [`RenderContext::recover`].
+    pub fn recover(&self) {
+        self.send(RenderCommand::Recover);
+    }
+
+    fn send(&self, command: RenderCommand) {
+        // Only fails once the thread has died, which it has already logged
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for RenderThread {
+    /// Waits for the frame in flight, so the surface is gone before the window is.
+    fn drop(&mut self) {
+        self.send(RenderCommand::Shutdown);
+        if let Some(handle) = self.handle.take()
+            && handle.join().is_err()
+        {
+            log::error!("Render thread panicked");
+        }
+    }
+}
+
+fn run(
+    window: Arc<Window>,
+    use_cpu: bool,
+    backends: Option<wgpu::Backends>,
+    srgb: bool,
+    commands: Receiver<RenderCommand>,
+    device_lost: Arc<Mutex<Option<String>>>,
+    diagnostics: Arc<Mutex<Option<RenderDiagnostics>>>,
+) {
+    let mut render_context = RenderContext::new(window, use_cpu, backends, srgb).pollster_block_on();
+
+    // Blocks until there's something to do, then takes everything else already queued
+    loop {
+        // A settling resize needs one more frame once it's done, even if nothing
+        // asks for it, and a fade needs one straight after every other
+        let wake_at = if render_context.is_animating() {
+            Some(Instant::now())
+        } else {
+            render_context.resize_settles_at()
+        };
+        let first = match wake_at {
+            Some(at) => match commands.recv_timeout(at.saturating_duration_since(Instant::now())) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            },
+            None => match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return,
+            },
+        };
+        let mut resize = None;
+        let mut redraw = first.is_none();
+        for command in first.into_iter().chain(commands.try_iter()) {
+            match command {
+                RenderCommand::Resize(size) => resize = Some(size),
+                RenderCommand::Redraw => redraw = true,
+                RenderCommand::SetBackground(color) => render_context.set_background_color(color),
+                RenderCommand::AnimateBackground(color, duration) => {
+                    render_context.set_background_color_animated(color, duration)
+                }
+                RenderCommand::SetPresentMode(mode) => render_context.set_present_mode(mode),
+                RenderCommand::SetFrameLatency(frames) => render_context.set_desired_maximum_frame_latency(frames),
+                RenderCommand::SetBlitFilter(filter) => render_context.set_blit_filter(filter),
+                RenderCommand::SetRenderScale(scale) => render_context.set_render_scale(scale),
+                RenderCommand::SetSmoothResize(smooth) => render_context.set_smooth_resize(smooth),
+                RenderCommand::SetDirectSurface(direct) => render_context.set_direct_surface(direct),
+                RenderCommand::SetTransparent(transparent) => render_context.set_transparent(transparent),
+                RenderCommand::SetMaxSize(max_size) => render_context.set_max_size(max_size),
+                RenderCommand::SetContent(content) => render_context.set_content(content.map(|scene| *scene)),
+                RenderCommand::Recover => {
+                    render_context = render_context.recover();
+                    *device_lost.lock().unwrap() = None;
+                }
+                RenderCommand::Shutdown => return,
+            }
+        }
+
+        if let Some(size) = resize {
+            render_context.resize(size);
+        }
+        if redraw || resize.is_some() {
+            render_context.render();
+            *diagnostics.lock().unwrap() = Some(render_context.diagnostics());
+        }
+        if let Some(reason) = render_context.device_lost() {
+            *device_lost.lock().unwrap() = Some(reason);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+// Least time between two logs of the same warning
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// A warning that can come up every frame, logged at most once a second.
+/// Occurrences in between are counted and reported with the next one, or
+/// once the problem goes away.
+pub(crate) struct ThrottledWarning {
+    message: &'static str,
+    logged_at: Option<Instant>,
+    suppressed: u32,
+}
+
+impl ThrottledWarning {
+    pub(crate) const fn new(message: &'static str) -> Self {
+        Self {
+            message,
+            logged_at: None,
+            suppressed: 0,
+        }
+    }
+
+    pub(crate) fn warn(&mut self) {
+        let now = Instant::now();
+        if self.logged_at.is_some_and(|at| now - at < INTERVAL) {
+            self.suppressed += 1;
+            return;
+        }
+        match std::mem::take(&mut self.suppressed) {
+            0 => log::warn!("{}", self.message),
+            suppressed => log::warn!("{} ({} more since the last report)", self.message, suppressed),
+        }
+        self.logged_at = Some(now);
+    }
+
+    /// Reports any occurrences not logged yet, for when the condition has cleared.
+    pub(crate) fn clear(&mut self) {
+        let suppressed = std::mem::take(&mut self.suppressed);
+        if self.logged_at.take().is_some() && suppressed > 0 {
+            log::warn!("{} ({} more before it cleared)", self.message, suppressed);
+        }
+    }
+}
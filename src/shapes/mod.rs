@@ -0,0 +1,177 @@
+use vello::Scene;
+use vello::kurbo::{self, Affine, BezPath, Cap, Point, Rect, RoundedRectRadii, Shape, Stroke};
+use vello::peniko::{BrushRef, Fill};
+
+// How closely curves are approximated when turned into paths
+const TOLERANCE: f64 = 0.1;
+
+/// Whether a shape is filled in or outlined.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShapeStyle {
+    Fill,
+    Stroke(Stroke),
+}
+
+impl ShapeStyle {
+    /// An outline `width` wide.
+    pub fn stroke(width: f64) -> Self {
+        Self::Stroke(Stroke::new(width))
+    }
+
+    /// A dashed outline, see [`dashed`].
+    pub fn dashed(width: f64) -> Self {
+        Self::Stroke(dashed(width))
+    }
+
+    /// A dotted outline, see [`dotted`].
+    pub fn dotted(width: f64) -> Self {
+        Self::Stroke(dotted(width))
+    }
+}
+
+/// A stroke `width` wide broken into dashes three times as long as it is
+/// wide, with gaps twice as long.
+///
+/// Dash lengths are in the same units as the width, so like it they're
+/// scaled by the transform a shape is drawn with. Widgets draw in physical
+/// pixels, so scale the width by [`DrawCtx::scale_factor`](crate::components::DrawCtx)
+/// for dashes that look the same at any DPI. Use [`Stroke::with_dashes`]
+/// for any other pattern.
+pub fn dashed(width: f64) -> Stroke {
+    Stroke::new(width).with_dashes(0.0, [width * 3.0, width * 2.0])
+}
+
+/// A stroke `width` wide broken into square dots with gaps as long as them.
+/// Scales like [`dashed`].
+pub fn dotted(width: f64) -> Stroke {
+    Stroke::new(width).with_dashes(0.0, [width, width])
+}
+
+/// Fills or strokes any kurbo shape, which is all the builders below do.
+pub fn draw_shape<'b>(scene: &mut Scene, transform: Affine, brush: impl Into<BrushRef<'b>>, style: &ShapeStyle, shape: &impl Shape) {
+    match style {
+        ShapeStyle::Fill => scene.fill(Fill::NonZero, transform, brush, None, shape),
+        ShapeStyle::Stroke(stroke) => scene.stroke(stroke, transform, brush, None, shape),
+    }
+}
+
+/// A rectangle with rounded corners, square until given a radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RoundedRect {
+    rect: Rect,
+    radii: RoundedRectRadii,
+}
+
+impl RoundedRect {
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            radii: RoundedRectRadii::from_single_radius(0.0),
+        }
+    }
+
+    /// The same radius for every corner.
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radii = RoundedRectRadii::from_single_radius(radius);
+        self
+    }
+
+    /// A radius for each corner, clockwise from the top left.
+    pub fn with_radii(mut self, top_left: f64, top_right: f64, bottom_right: f64, bottom_left: f64) -> Self {
+        self.radii = RoundedRectRadii::new(top_left, top_right, bottom_right, bottom_left);
+        self
+    }
+
+    pub fn to_path(&self) -> BezPath {
+        kurbo::RoundedRect::from(*self).to_path(TOLERANCE)
+    }
+
+    pub fn draw<'b>(&self, scene: &mut Scene, brush: impl Into<BrushRef<'b>>, style: &ShapeStyle) {
+        draw_shape(scene, Affine::IDENTITY, brush, style, &kurbo::RoundedRect::from(*self));
+    }
+}
+
+impl From<RoundedRect> for kurbo::RoundedRect {
+    // Radii too large for the rect are scaled down by kurbo
+    fn from(shape: RoundedRect) -> Self {
+        kurbo::RoundedRect::from_rect(shape.rect, shape.radii)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    center: Point,
+    radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: impl Into<Point>, radius: f64) -> Self {
+        Self {
+            center: center.into(),
+            radius: radius.max(0.0),
+        }
+    }
+
+    /// The largest circle that fits in `rect`, centred in it.
+    pub fn inside(rect: Rect) -> Self {
+        Self::new(rect.center(), rect.width().min(rect.height()).abs() / 2.0)
+    }
+
+    pub fn to_path(&self) -> BezPath {
+        kurbo::Circle::from(*self).to_path(TOLERANCE)
+    }
+
+    pub fn draw<'b>(&self, scene: &mut Scene, brush: impl Into<BrushRef<'b>>, style: &ShapeStyle) {
+        draw_shape(scene, Affine::IDENTITY, brush, style, &kurbo::Circle::from(*self));
+    }
+}
+
+impl From<Circle> for kurbo::Circle {
+    fn from(shape: Circle) -> Self {
+        kurbo::Circle::new(shape.center, shape.radius)
+    }
+}
+
+/// A straight line, which only has an outline to draw.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Line {
+    from: Point,
+    to: Point,
+    cap: Cap,
+}
+
+impl Line {
+    pub fn new(from: impl Into<Point>, to: impl Into<Point>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            cap: Cap::Butt,
+        }
+    }
+
+    /// How the ends are drawn. Defaults to stopping flat at each point.
+    pub fn with_cap(mut self, cap: Cap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn to_path(&self) -> BezPath {
+        kurbo::Line::from(*self).to_path(TOLERANCE)
+    }
+
+    pub fn draw<'b>(&self, scene: &mut Scene, brush: impl Into<BrushRef<'b>>, width: f64) {
+        self.draw_stroke(scene, brush, &Stroke::new(width).with_caps(self.cap));
+    }
+
+    /// Draws the line with `stroke` as given, e.g. [`dashed`], ignoring
+    /// [`Self::with_cap`].
+    pub fn draw_stroke<'b>(&self, scene: &mut Scene, brush: impl Into<BrushRef<'b>>, stroke: &Stroke) {
+        scene.stroke(stroke, Affine::IDENTITY, brush, None, &kurbo::Line::from(*self));
+    }
+}
+
+impl From<Line> for kurbo::Line {
+    fn from(shape: Line) -> Self {
+        kurbo::Line::new(shape.from, shape.to)
+    }
+}
@@ -0,0 +1,334 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use image::{Rgba, RgbaImage};
+use vello::peniko::Color;
+use vello::util::RenderContext as VelloRenderContext;
+use vello::{wgpu, RendererOptions, Scene};
+
+pub use crate::render::{poll_device, read_texture};
+use crate::render::{PollsterBlockOn, RenderContext};
+
+/// Set to rewrite golden images with whatever is rendered now, after a change
+/// that is meant to alter the output.
+pub const UPDATE_GOLDENS_VAR: &str = "RASMALAI_UPDATE_GOLDENS";
+
+/// How far a frame is from its golden image. Per-pixel differences are the
+/// largest absolute difference of any channel, from 0 to 255.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameDiff {
+    pub max: u8,
+    pub mean: f64,
+    /// How many pixels differ at all.
+    pub differing_pixels: usize,
+}
+
+/// Renders the current frame offscreen at `width` x `height` and reads it
+/// back. The window's surface isn't touched; see
+/// [`RenderContext::render_to_external`].
+pub fn capture_frame(ctx: &mut RenderContext, width: u32, height: u32) -> anyhow::Result<RgbaImage> {
+    let texture = ctx.device().create_texture(&wgpu::TextureDescriptor {
+        label: Some("Capture Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    ctx.render_to_external(&view, width, height)?;
+    read_texture(ctx.device(), ctx.queue(), &texture)
+}
+
+/// A GPU device and vello renderer with no window, for rendering scenes
+/// offscreen where there's no display, as in tests and CI. Unlike
+/// [`capture_frame`], nothing of a [`RenderContext`]'s own drawing is
+/// involved: only the scene given is rendered.
+pub struct HeadlessRenderer {
+    context: VelloRenderContext,
+    dev_id: usize,
+    renderer: vello::Renderer,
+}
+
+impl HeadlessRenderer {
+    /// Picks an adapter the way [`RenderContext::new`] does. `Ok(None)` when
+    /// there's no adapter at all, for tests that should skip rather than fail
+    /// on machines without a GPU.
+    pub fn new() -> anyhow::Result<Option<Self>> {
+        let mut context = VelloRenderContext::new();
+        let Some(dev_id) = context.device(None).pollster_block_on() else {
+            return Ok(None);
+        };
+        let renderer = vello::Renderer::new(
+            &context.devices[dev_id].device,
+            RendererOptions {
+                antialiasing_support: vello::AaSupport::area_only(),
+                num_init_threads: None,
+                pipeline_cache: None,
+                use_cpu: false,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("failed to create renderer: {}", e))?;
+        Ok(Some(Self { context, dev_id, renderer }))
+    }
+
+    pub fn device(&self) -> &wgpu::Device {
+        &self.context.devices[self.dev_id].device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.context.devices[self.dev_id].queue
+    }
+
+    /// Renders `scene` over `base_color` into a new `Rgba8Unorm` texture with
+    /// `STORAGE_BINDING | TEXTURE_BINDING | COPY_SRC` usage. Like
+    /// [`RenderContext::render_to_external`], it holds straight alpha.
+    pub fn render_to_texture(&mut self, scene: &Scene, width: u32, height: u32, base_color: Color) -> anyhow::Result<wgpu::Texture> {
+        let device = &self.context.devices[self.dev_id].device;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.renderer.render_to_texture(
+            device,
+            &self.context.devices[self.dev_id].queue,
+            scene,
+            &view,
+            &vello::RenderParams {
+                base_color,
+                width,
+                height,
+                antialiasing_method: vello::AaConfig::Area,
+            },
+        )?;
+        Ok(texture)
+    }
+
+    /// Renders `scene` over `base_color` and reads it back.
+    pub fn render(&mut self, scene: &Scene, width: u32, height: u32, base_color: Color) -> anyhow::Result<RgbaImage> {
+        let texture = self.render_to_texture(scene, width, height, base_color)?;
+        read_texture(self.device(), self.queue(), &texture)
+    }
+}
+
+// For the crate's own GPU tests: `None`, with a note, where there's no GPU
+#[cfg(test)]
+pub(crate) fn headless() -> Option<HeadlessRenderer> {
+    let renderer = HeadlessRenderer::new().expect("failed to set up headless rendering");
+    if renderer.is_none() {
+        eprintln!("No GPU adapter found, skipping");
+    }
+    renderer
+}
+
+/// Compares two images of the same size pixel by pixel.
+pub fn diff_frames(actual: &RgbaImage, expected: &RgbaImage) -> anyhow::Result<FrameDiff> {
+    if actual.dimensions() != expected.dimensions() {
+        bail!("frame is {:?} but the golden image is {:?}", actual.dimensions(), expected.dimensions());
+    }
+    let mut max = 0;
+    let mut total = 0u64;
+    let mut differing_pixels = 0;
+    for (a, e) in actual.pixels().zip(expected.pixels()) {
+        let difference = pixel_difference(a, e);
+        max = max.max(difference);
+        total += difference as u64;
+        if difference > 0 {
+            differing_pixels += 1;
+        }
+    }
+    let count = (actual.width() as u64 * actual.height() as u64).max(1);
+    Ok(FrameDiff {
+        max,
+        mean: total as f64 / count as f64,
+        differing_pixels,
+    })
+}
+
+fn pixel_difference(a: &Rgba<u8>, b: &Rgba<u8>) -> u8 {
+    a.0.iter().zip(b.0).map(|(a, b)| a.abs_diff(b)).max().unwrap_or(0)
+}
+
+// The golden image dimmed to grey, with differing pixels in red as bright as
+// the difference
+fn diff_image(actual: &RgbaImage, expected: &RgbaImage) -> RgbaImage {
+    RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let (a, e) = (actual.get_pixel(x, y), expected.get_pixel(x, y));
+        match pixel_difference(a, e) {
+            0 => {
+                let grey = ((e[0] as u16 + e[1] as u16 + e[2] as u16) / 12) as u8;
+                Rgba([grey, grey, grey, 255])
+            }
+            difference => Rgba([difference.max(64), 0, 0, 255]),
+        }
+    })
+}
+
+// Next to the golden image, e.g. `button.png` -> `button.diff.png`
+fn sibling(golden: &Path, suffix: &str) -> PathBuf {
+    let stem = golden.file_stem().and_then(|stem| stem.to_str()).unwrap_or("frame");
+    golden.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+/// Renders the current frame at the golden image's size and panics unless no
+/// pixel differs from it by more than `tolerance` (0 to 255, on any channel).
+///
+/// On failure the frame and a diff image are written next to the golden one,
+/// as `<name>.actual.png` and `<name>.diff.png`. A missing golden image is
+/// written from the frame, and the assertion fails so it gets looked at. Set
+/// [`UPDATE_GOLDENS_VAR`] to overwrite golden images instead of comparing.
+///
+/// ```ignore
+/// render_context.set_content(Some(scene));
+/// rasmalai::testing::assert_frame_matches(&mut render_context, "tests/golden/button.png", 2);
+/// ```
+pub fn assert_frame_matches(ctx: &mut RenderContext, golden_path: impl AsRef<Path>, tolerance: u8) {
+    if let Err(e) = check_frame(ctx, golden_path.as_ref(), tolerance) {
+        panic!("{:#}", e);
+    }
+}
+
+/// Like [`assert_frame_matches`], for a frame rendered some other way, e.g.
+/// with a [`HeadlessRenderer`]. The frame has to be the golden image's size.
+pub fn assert_image_matches(actual: &RgbaImage, golden_path: impl AsRef<Path>, tolerance: u8) {
+    if let Err(e) = check_image(actual, golden_path.as_ref(), tolerance) {
+        panic!("{:#}", e);
+    }
+}
+
+fn check_frame(ctx: &mut RenderContext, golden_path: &Path, tolerance: u8) -> anyhow::Result<()> {
+    let update = std::env::var_os(UPDATE_GOLDENS_VAR).is_some();
+    let (width, height) = if update || !golden_path.exists() {
+        // Sized like the window when there's nothing to match yet
+        let size = ctx.size();
+        (size.width, size.height)
+    } else {
+        image::image_dimensions(golden_path).with_context(|| format!("failed to read {}", golden_path.display()))?
+    };
+    let actual = capture_frame(ctx, width, height)?;
+    check_image(&actual, golden_path, tolerance)
+}
+
+fn check_image(actual: &RgbaImage, golden_path: &Path, tolerance: u8) -> anyhow::Result<()> {
+    let update = std::env::var_os(UPDATE_GOLDENS_VAR).is_some();
+    if update || !golden_path.exists() {
+        if let Some(dir) = golden_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        actual
+            .save(golden_path)
+            .with_context(|| format!("failed to write {}", golden_path.display()))?;
+        if update {
+            log::info!("Updated golden image {}", golden_path.display());
+            return Ok(());
+        }
+        bail!("no golden image at {}; wrote the current frame there, check it and rerun", golden_path.display());
+    }
+
+    let expected = image::open(golden_path)
+        .with_context(|| format!("failed to read {}", golden_path.display()))?
+        .into_rgba8();
+    let diff = diff_frames(actual, &expected)?;
+    if diff.max <= tolerance {
+        return Ok(());
+    }
+
+    let actual_path = sibling(golden_path, "actual");
+    let diff_path = sibling(golden_path, "diff");
+    actual.save(&actual_path)?;
+    diff_image(actual, &expected).save(&diff_path)?;
+    bail!(
+        "frame differs from {} by up to {} (mean {:.3}, {} pixels, tolerance {}); see {} and {}",
+        golden_path.display(),
+        diff.max,
+        diff.mean,
+        diff.differing_pixels,
+        tolerance,
+        actual_path.display(),
+        diff_path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use vello::kurbo::{Affine, Circle, Rect};
+    use vello::peniko::Fill;
+
+    use super::*;
+    use crate::render;
+
+    const SIZE: u32 = 64;
+
+    #[test]
+    fn clips_compose_when_nested() {
+        let Some(mut renderer) = headless() else {
+            return;
+        };
+        let mut scene = Scene::new();
+        // A child twice the size of its parent, inside a second clip that only
+        // overlaps the parent's top-left quarter
+        let parent = Rect::new(16.0, 16.0, 48.0, 48.0);
+        render::with_clip(&mut scene, Affine::IDENTITY, &parent, |scene| {
+            render::with_clip(scene, Affine::IDENTITY, &Rect::new(0.0, 0.0, 32.0, 32.0), |scene| {
+                scene.fill(Fill::NonZero, Affine::IDENTITY, Color::WHITE, None, &Rect::new(0.0, 0.0, 64.0, 64.0));
+            });
+        });
+        let frame = renderer.render(&scene, SIZE, SIZE, Color::BLACK).unwrap();
+
+        let white = Rgba([255, 255, 255, 255]);
+        let black = Rgba([0, 0, 0, 255]);
+        assert_eq!(*frame.get_pixel(24, 24), white, "inside both clips");
+        assert_eq!(*frame.get_pixel(8, 8), black, "outside the parent");
+        assert_eq!(*frame.get_pixel(40, 40), black, "inside the parent but outside the inner clip");
+        assert_eq!(*frame.get_pixel(60, 60), black, "where the child overflows");
+    }
+
+    #[test]
+    fn frames_are_checked_against_golden_images() {
+        let Some(mut renderer) = headless() else {
+            return;
+        };
+        let dir = std::env::temp_dir().join(format!("rasmalai-golden-{}", std::process::id()));
+        let golden = dir.join("circle.png");
+        let draw = |color| {
+            let mut scene = Scene::new();
+            scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &Circle::new((32.0, 32.0), 20.0));
+            scene
+        };
+        let frame = renderer.render(&draw(Color::WHITE), SIZE, SIZE, Color::BLACK).unwrap();
+
+        let missing = check_image(&frame, &golden, 0).unwrap_err();
+        assert!(missing.to_string().contains("no golden image"), "{missing:#}");
+        assert!(golden.exists(), "written from the frame");
+        let again = renderer.render(&draw(Color::WHITE), SIZE, SIZE, Color::BLACK).unwrap();
+        check_image(&again, &golden, 0).unwrap();
+
+        let grey = renderer.render(&draw(Color::from_rgb8(200, 200, 200)), SIZE, SIZE, Color::BLACK).unwrap();
+        let diff = diff_frames(&grey, &frame).unwrap();
+        assert_eq!(diff.max, 55);
+        assert!(diff.differing_pixels > 0 && diff.differing_pixels < (SIZE * SIZE) as usize);
+        assert!(check_image(&grey, &golden, 54).is_err());
+        assert!(dir.join("circle.actual.png").exists() && dir.join("circle.diff.png").exists());
+        check_image(&grey, &golden, 55).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1 +1,706 @@
-pub struct TextEngine;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use parley::fontique::{Blob, FontInfoOverride};
+use parley::{
+    Affinity, BoundingBox, Cursor, FontContext, FontFamily, FontStack, FontStyle, GlyphRun, Layout,
+    LayoutContext, OverflowWrap, PositionedLayoutItem, Selection, StyleProperty,
+};
+pub use parley::FontWeight;
+use vello::Scene;
+use vello::kurbo::{Affine, BezPath, ParamCurve, ParamCurveArclen, ParamCurveDeriv, PathSeg, Point, Rect, Shape, Size};
+use vello::peniko::{Color, Fill};
+use vello::kurbo::Stroke;
+use skrifa::instance::{NormalizedCoord, Size as FontSize};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::{GlyphId, MetadataProvider};
+
+thread_local! {
+    // Font discovery is expensive, so every widget on the UI thread shares one engine
+    static ENGINE: RefCell<TextEngine> = RefCell::new(TextEngine::new());
+    // Widgets measure every layout pass, mostly the same text each time
+    static MEASURED: RefCell<HashMap<MeasureKey, TextMetrics>> = RefCell::new(HashMap::new());
+}
+
+// Entries kept before `measure_text` starts afresh, so ever-changing text
+// like a frame counter can't grow the cache without bound
+const MEASURE_CACHE_SIZE: usize = 1024;
+
+/// How a run of text looks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    pub size: f32,
+    pub weight: FontWeight,
+    pub italic: bool,
+    pub color: Color,
+    /// A registered or system font family; `None` or an unknown name uses the default.
+    pub font: Option<String>,
+    pub outline: Option<TextOutline>,
+}
+
+impl TextStyle {
+    /// Regular weight, upright, in the default font.
+    pub fn new(size: f32, color: Color) -> Self {
+        Self {
+            size,
+            weight: FontWeight::NORMAL,
+            italic: false,
+            color,
+            font: None,
+            outline: None,
+        }
+    }
+
+    pub fn with_weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    pub fn with_font(mut self, font: Option<&str>) -> Self {
+        self.font = font.map(str::to_string);
+        self
+    }
+
+    pub fn with_outline(mut self, outline: TextOutline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+}
+
+/// A stroke around every glyph, for text that has to stand out from a busy
+/// background.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextOutline {
+    pub width: f64,
+    pub color: Color,
+    /// Whether the glyphs are filled with the style's colour too. The outline
+    /// goes behind the fill, so only half its width shows around filled glyphs.
+    pub fill: bool,
+}
+
+impl TextOutline {
+    /// Filled glyphs with an outline `width` wide.
+    pub fn new(width: f64, color: Color) -> Self {
+        Self { width, color, fill: true }
+    }
+
+    pub fn with_fill(mut self, fill: bool) -> Self {
+        self.fill = fill;
+        self
+    }
+}
+
+/// Fonts available to text layout: the system's, plus any registered by name.
+pub struct FontRegistry {
+    font_cx: FontContext,
+    // Unknown names already reported, so a missing font warns once rather than every frame
+    warned: HashSet<String>,
+}
+
+impl Default for FontRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontRegistry {
+    pub fn new() -> Self {
+        Self {
+            font_cx: FontContext::new(),
+            warned: HashSet::new(),
+        }
+    }
+
+    /// Makes the fonts in `bytes` (a TTF, OTF or collection) available as `name`,
+    /// whatever family name they declare themselves.
+    pub fn register_font(&mut self, name: &str, bytes: impl Into<Vec<u8>>) -> anyhow::Result<()> {
+        let info_override = FontInfoOverride {
+            family_name: Some(name),
+            ..Default::default()
+        };
+        let registered = self
+            .font_cx
+            .collection
+            .register_fonts(Blob::from(bytes.into()), Some(info_override));
+        if registered.is_empty() {
+            bail!("no fonts found in the data registered as {:?}", name);
+        }
+        self.warned.remove(name);
+        Ok(())
+    }
+
+    pub fn register_font_file(&mut self, name: &str, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("failed to read font {}", path.display()))?;
+        self.register_font(name, bytes)
+    }
+
+    /// Whether `name` is a registered or system font family.
+    pub fn contains(&mut self, name: &str) -> bool {
+        self.font_cx.collection.family_id(name).is_some()
+    }
+
+    // `name` if it can be used, warning about it otherwise
+    fn resolve<'a>(&mut self, name: Option<&'a str>) -> Option<&'a str> {
+        let name = name?;
+        if self.contains(name) {
+            return Some(name);
+        }
+        if self.warned.insert(name.to_string()) {
+            log::warn!("Unknown font {:?}, using the default font", name);
+        }
+        None
+    }
+}
+
+pub struct TextEngine {
+    fonts: FontRegistry,
+    layout_cx: LayoutContext<()>,
+    // Styles already reported as synthesized, so the note is logged once
+    synthesized: HashSet<String>,
+}
+
+impl Default for TextEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextEngine {
+    pub fn new() -> Self {
+        Self {
+            fonts: FontRegistry::new(),
+            layout_cx: LayoutContext::new(),
+            synthesized: HashSet::new(),
+        }
+    }
+
+    pub fn fonts(&mut self) -> &mut FontRegistry {
+        &mut self.fonts
+    }
+
+    /// Lays `text` out in `style`'s font, size, weight and slant. Lines break at
+    /// newlines and, given a `max_width`, wherever they would overflow it.
+    pub fn layout(&mut self, text: &str, style: &TextStyle, max_width: Option<f32>) -> Layout<()> {
+        let family = self.fonts.resolve(style.font.as_deref());
+        let mut builder = self.layout_cx.ranged_builder(&mut self.fonts.font_cx, text, 1.0, true);
+        builder.push_default(StyleProperty::FontSize(style.size));
+        builder.push_default(StyleProperty::FontWeight(style.weight));
+        // Words too long for a line on their own get broken wherever they must be
+        builder.push_default(StyleProperty::OverflowWrap(OverflowWrap::Anywhere));
+        if style.italic {
+            builder.push_default(StyleProperty::FontStyle(FontStyle::Italic));
+        }
+        if let Some(family) = family {
+            builder.push_default(StyleProperty::FontStack(FontStack::Single(FontFamily::Named(family.into()))));
+        }
+        let mut layout = builder.build(text);
+        layout.break_all_lines(max_width);
+        self.note_synthesis(&layout, style);
+        layout
+    }
+
+    // The font picked for a style may not have a matching face, in which case
+    // fontique asks for emboldening or a skew and `draw_text` fakes it
+    fn note_synthesis(&mut self, layout: &Layout<()>, style: &TextStyle) {
+        let synthesized = layout.lines().flat_map(|line| line.runs()).any(|run| {
+            let synthesis = run.synthesis();
+            synthesis.embolden() || synthesis.skew().is_some()
+        });
+        if !synthesized {
+            return;
+        }
+        if self.synthesized.insert(synthesis_key(style)) {
+            log::info!(
+                "No face for {} weight {}{}; synthesizing it",
+                style.font.as_deref().unwrap_or("the default font"),
+                style.weight.value(),
+                if style.italic { " italic" } else { "" }
+            );
+        }
+    }
+}
+
+/// Registers a font for the UI thread's text. See [`FontRegistry::register_font`].
+fn synthesis_key(style: &TextStyle) -> String {
+    format!("{:?} {} {}", style.font, style.weight.value(), style.italic)
+}
+
+pub fn register_font(name: &str, bytes: impl Into<Vec<u8>>) -> anyhow::Result<()> {
+    // Text measured in a font that was missing until now came out in the default one
+    MEASURED.with_borrow_mut(HashMap::clear);
+    ENGINE.with_borrow_mut(|engine| engine.fonts().register_font(name, bytes))
+}
+
+/// Registers a font file for the UI thread's text. See [`FontRegistry::register_font_file`].
+pub fn register_font_file(name: &str, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    MEASURED.with_borrow_mut(HashMap::clear);
+    ENGINE.with_borrow_mut(|engine| engine.fonts().register_font_file(name, path))
+}
+
+/// One line of a [`Paragraph`], relative to its top-left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Byte range of the source text on this line, including any trailing whitespace.
+    pub text_range: Range<usize>,
+    pub top: f64,
+    pub baseline: f64,
+    pub height: f64,
+    /// Advance of the line, not counting trailing whitespace.
+    pub width: f64,
+}
+
+/// Text broken into lines to fit a width.
+///
+/// Breaks happen at word boundaries, or mid-word for a word that is wider than
+/// the whole line. Newlines always break, so blank lines keep their height.
+pub struct Paragraph {
+    layout: Layout<()>,
+    style: TextStyle,
+    lines: Vec<LineMetrics>,
+}
+
+impl Paragraph {
+    /// Lays `text` out on the UI thread's engine. `None` only breaks at newlines.
+    pub fn new(text: &str, style: &TextStyle, max_width: Option<f64>) -> Self {
+        let layout = ENGINE.with_borrow_mut(|engine| engine.layout(text, style, max_width.map(|w| w as f32)));
+        let lines = layout
+            .lines()
+            .map(|line| {
+                let metrics = line.metrics();
+                // Parley lays empty text out as a placeholder character, so keep ranges inside `text`
+                let range = line.text_range();
+                let text_range = range.start.min(text.len())..range.end.min(text.len());
+                // ...and an empty last line can report the advance of the line before it
+                let width = if text[text_range.clone()].trim_end().is_empty() {
+                    0.0
+                } else {
+                    (metrics.advance - metrics.trailing_whitespace) as f64
+                };
+                LineMetrics {
+                    text_range,
+                    top: metrics.min_coord as f64,
+                    baseline: metrics.baseline as f64,
+                    height: metrics.line_height as f64,
+                    width,
+                }
+            })
+            .collect();
+        Self {
+            layout,
+            style: style.clone(),
+            lines,
+        }
+    }
+
+    pub fn lines(&self) -> &[LineMetrics] {
+        &self.lines
+    }
+
+    /// Widest line by the whole paragraph's height.
+    pub fn size(&self) -> Size {
+        let width = self.lines.iter().map(|line| line.width).fold(0.0, f64::max);
+        Size::new(width, self.layout.height() as f64)
+    }
+
+    /// Draws the paragraph with its top-left corner at `origin`.
+    pub fn draw(&self, scene: &mut Scene, origin: Point) {
+        draw_layout(scene, &self.layout, origin, &self.style);
+    }
+
+    /// Where a caret before byte `index` of the text sits: a zero-width rect
+    /// as tall as its line. Indices inside a character snap to its start.
+    pub fn caret_rect(&self, index: usize) -> Rect {
+        to_rect(Cursor::from_byte_index(&self.layout, index, Affinity::Downstream).geometry(&self.layout, 0.0))
+    }
+
+    /// Byte index of the caret position closest to `point`.
+    pub fn index_at(&self, point: Point) -> usize {
+        Cursor::from_point(&self.layout, point.x as f32, point.y as f32).index()
+    }
+
+    /// Byte range of the word at `point`, for selecting it on a double click.
+    pub fn word_at(&self, point: Point) -> Range<usize> {
+        Selection::word_from_point(&self.layout, point.x as f32, point.y as f32).text_range()
+    }
+
+    /// Rects covering the bytes in `range`, one for each line it touches, for
+    /// highlighting a selection behind the text.
+    pub fn selection_rects(&self, range: Range<usize>) -> Vec<Rect> {
+        let anchor = Cursor::from_byte_index(&self.layout, range.start, Affinity::Downstream);
+        let focus = Cursor::from_byte_index(&self.layout, range.end, Affinity::Upstream);
+        Selection::new(anchor, focus)
+            .geometry(&self.layout)
+            .into_iter()
+            .map(|(bounds, _)| to_rect(bounds))
+            .collect()
+    }
+}
+
+fn to_rect(bounds: BoundingBox) -> Rect {
+    Rect::new(bounds.x0, bounds.y0, bounds.x1, bounds.y1)
+}
+
+/// The size and lines of some text, from [`measure_text`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextMetrics {
+    /// Widest line by the height of them all, as [`Paragraph::size`].
+    pub size: Size,
+    pub lines: Vec<LineMetrics>,
+}
+
+impl TextMetrics {
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+// Everything about a style that changes how text is laid out; colour and
+// outline don't
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct MeasureKey {
+    text: String,
+    size: u32,
+    weight: u32,
+    italic: bool,
+    font: Option<String>,
+    max_width: Option<u64>,
+}
+
+/// Lays `text` out as a [`Paragraph`] would, breaking lines to fit
+/// `max_width` if given, and returns its size and lines without drawing it.
+///
+/// Results are cached on the UI thread by text, layout-affecting style and
+/// width, so measuring the same text again, as every layout pass does, is
+/// a lookup rather than reshaping it.
+pub fn measure_text(text: &str, style: &TextStyle, max_width: Option<f64>) -> TextMetrics {
+    let key = MeasureKey {
+        text: text.to_string(),
+        size: style.size.to_bits(),
+        weight: style.weight.value().to_bits(),
+        italic: style.italic,
+        font: style.font.clone(),
+        max_width: max_width.map(f64::to_bits),
+    };
+    if let Some(metrics) = MEASURED.with_borrow(|measured| measured.get(&key).cloned()) {
+        return metrics;
+    }
+    let paragraph = Paragraph::new(text, style, max_width);
+    let metrics = TextMetrics {
+        size: paragraph.size(),
+        lines: paragraph.lines,
+    };
+    MEASURED.with_borrow_mut(|measured| {
+        if measured.len() >= MEASURE_CACHE_SIZE {
+            measured.clear();
+        }
+        measured.insert(key, metrics.clone());
+    });
+    metrics
+}
+
+/// Size of `text` set on a single line.
+pub fn text_size(text: &str, style: &TextStyle) -> Size {
+    let layout = ENGINE.with_borrow_mut(|engine| engine.layout(text, style, None));
+    Size::new(layout.width() as f64, layout.height() as f64)
+}
+
+// Outline width, relative to the font size, used to fake a bold face
+const EMBOLDEN_STROKE: f64 = 1.0 / 24.0;
+
+/// Draws `text` on a single line with its top-left corner at `origin`.
+pub fn draw_text(scene: &mut Scene, text: &str, origin: Point, style: &TextStyle) {
+    let layout = ENGINE.with_borrow_mut(|engine| engine.layout(text, style, None));
+    draw_layout(scene, &layout, origin, style);
+}
+
+fn draw_layout(scene: &mut Scene, layout: &Layout<()>, origin: Point, style: &TextStyle) {
+    let transform = Affine::translate(origin.to_vec2());
+    for line in layout.lines() {
+        for item in line.items() {
+            let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                continue;
+            };
+            let glyphs: Vec<vello::Glyph> = glyph_run
+                .positioned_glyphs()
+                .map(|glyph| vello::Glyph {
+                    id: glyph.id,
+                    x: glyph.x,
+                    y: glyph.y,
+                })
+                .collect();
+            draw_glyphs(scene, &glyph_run, glyphs, transform, style);
+        }
+    }
+}
+
+// Fills, and outlines if the style asks, some of a run's glyphs placed by
+// `transform`, faking whatever the font's face is missing
+fn draw_glyphs(scene: &mut Scene, glyph_run: &GlyphRun<'_, ()>, mut glyphs: Vec<vello::Glyph>, transform: Affine, style: &TextStyle) {
+    let run = glyph_run.run();
+    let synthesis = run.synthesis();
+    // Fontique reports the slant in degrees
+    let glyph_transform = synthesis
+        .skew()
+        .map(|angle| Affine::skew(angle.to_radians().tan() as f64, 0.0));
+    if let Some(outline) = style.outline.filter(|outline| outline.width > 0.0) {
+        let unstroked = stroke_glyphs(scene, glyph_run, &glyphs, transform, glyph_transform, &outline);
+        // Glyphs without an outline are filled even when the rest aren't,
+        // rather than left out
+        if !outline.fill {
+            glyphs = unstroked;
+        }
+    }
+    if glyphs.is_empty() {
+        return;
+    }
+    let mut fill = |style_ref: vello::peniko::StyleRef| {
+        scene
+            .draw_glyphs(run.font())
+            .font_size(run.font_size())
+            .normalized_coords(run.normalized_coords())
+            .transform(transform)
+            .glyph_transform(glyph_transform)
+            .brush(style.color)
+            .draw(style_ref, glyphs.iter().copied());
+    };
+    fill(Fill::NonZero.into());
+    if synthesis.embolden() {
+        // Thickening the outline is what browsers do for a missing bold face
+        fill((&Stroke::new(run.font_size() as f64 * EMBOLDEN_STROKE)).into());
+    }
+}
+
+// How closely arc lengths along a path are worked out, in pixels
+const ARCLEN_ACCURACY: f64 = 1e-3;
+
+/// Draws `text` on a single line following `path`, each glyph placed by
+/// its distance along the path and turned to match its direction there,
+/// with the baseline on the path. Text starts at the path's start; text
+/// longer than the path is cut short with an ellipsis. Only the path's
+/// first subpath is followed.
+///
+/// ```ignore
+/// let arc = Arc::new((200.0, 200.0), (150.0, 150.0), PI, PI, 0.0);
+/// text::draw_text_on_path(scene, "Curved heading", &arc, &style);
+/// ```
+pub fn draw_text_on_path(scene: &mut Scene, text: &str, path: &impl Shape, style: &TextStyle) {
+    let along = PathWalker::new(path);
+    let text = fit_text(text, style, along.length);
+    let layout = ENGINE.with_borrow_mut(|engine| engine.layout(&text, style, None));
+    let Some(line) = layout.lines().next() else {
+        return;
+    };
+    for item in line.items() {
+        let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+            continue;
+        };
+        for glyph in glyph_run.positioned_glyphs() {
+            // Placed by its middle, so glyphs lean into a curve together
+            let middle = glyph.x as f64 + glyph.advance as f64 / 2.0;
+            let Some((point, angle)) = along.at(middle) else {
+                continue;
+            };
+            let transform = Affine::translate(point.to_vec2())
+                * Affine::rotate(angle)
+                * Affine::translate((-middle, -glyph.y as f64));
+            let glyph = vello::Glyph {
+                id: glyph.id,
+                x: glyph.x,
+                y: glyph.y,
+            };
+            draw_glyphs(scene, &glyph_run, vec![glyph], transform, style);
+        }
+    }
+}
+
+// The longest start of `text` that fits in `width` along with an ellipsis,
+// or all of it if it fits as it is
+fn fit_text(text: &str, style: &TextStyle, width: f64) -> String {
+    let fits = |text: &str| text_size(text, style).width <= width;
+    if fits(text) {
+        return text.to_string();
+    }
+    // Where the text would end keeping each number of characters
+    let mut ends: Vec<usize> = vec![0];
+    ends.extend(text.char_indices().map(|(index, c)| index + c.len_utf8()));
+    let shortened = |kept: usize| format!("{}…", text[..ends[kept]].trim_end());
+    // Bisected, since each try lays the text out again
+    let (mut low, mut high) = (0, ends.len() - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if fits(&shortened(mid)) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    shortened(low)
+}
+
+// Finds points along a path by their distance from its start
+struct PathWalker {
+    // Each with the distance along the path it starts at
+    segments: Vec<(PathSeg, f64)>,
+    length: f64,
+}
+
+impl PathWalker {
+    fn new(path: &impl Shape) -> Self {
+        let mut segments = Vec::new();
+        let mut length = 0.0;
+        for segment in path.path_segments(0.1) {
+            // A second subpath starts away from where the first ended
+            if let Some((last, _)) = segments.last()
+                && PathSeg::end(last) != segment.start()
+            {
+                break;
+            }
+            segments.push((segment, length));
+            length += segment.arclen(ARCLEN_ACCURACY);
+        }
+        Self { segments, length }
+    }
+
+    // The point `distance` along the path and the angle the path heads off
+    // at from there, unless that's past its end
+    fn at(&self, distance: f64) -> Option<(Point, f64)> {
+        if !(0.0..=self.length).contains(&distance) {
+            return None;
+        }
+        let index = self.segments.partition_point(|(_, start)| *start <= distance).saturating_sub(1);
+        let (segment, start) = self.segments.get(index)?;
+        let t = segment.inv_arclen(distance - start, ARCLEN_ACCURACY);
+        let mut tangent = segment.to_cubic().deriv().eval(t).to_vec2();
+        // Control points on top of an end leave no direction there
+        if tangent.hypot2() < 1e-12 {
+            tangent = segment.eval((t + 1e-3).min(1.0)) - segment.eval((t - 1e-3).max(0.0));
+        }
+        Some((segment.eval(t), tangent.atan2()))
+    }
+}
+
+// Strokes the outlines the font has for `glyphs`, placed the way vello places
+// filled glyphs. Returns the glyphs it couldn't get an outline for.
+fn stroke_glyphs(
+    scene: &mut Scene,
+    glyph_run: &GlyphRun<'_, ()>,
+    glyphs: &[vello::Glyph],
+    transform: Affine,
+    glyph_transform: Option<Affine>,
+    outline: &TextOutline,
+) -> Vec<vello::Glyph> {
+    let run = glyph_run.run();
+    let font = run.font();
+    let Ok(font_ref) = skrifa::FontRef::from_index(font.data.as_ref(), font.index) else {
+        return glyphs.to_vec();
+    };
+    let outlines = font_ref.outline_glyphs();
+    let coords: Vec<NormalizedCoord> = run
+        .normalized_coords()
+        .iter()
+        .map(|&coord| NormalizedCoord::from_bits(coord))
+        .collect();
+    let size = FontSize::new(run.font_size());
+    let stroke = Stroke::new(outline.width);
+    let mut unstroked = Vec::new();
+    for glyph in glyphs {
+        let mut path = GlyphPath(BezPath::new());
+        let drawn = outlines
+            .get(GlyphId::new(glyph.id))
+            .is_some_and(|outline| outline.draw(DrawSettings::unhinted(size, coords.as_slice()), &mut path).is_ok());
+        if !drawn {
+            unstroked.push(*glyph);
+            continue;
+        }
+        // Outlines come y-up, with any synthesized slant applied before flipping
+        let placement = transform
+            * Affine::translate((glyph.x as f64, glyph.y as f64))
+            * Affine::FLIP_Y
+            * glyph_transform.unwrap_or(Affine::IDENTITY);
+        scene.stroke(&stroke, placement, outline.color, None, &path.0);
+    }
+    unstroked
+}
+
+// Collects a glyph outline from skrifa, scaled to the font size
+struct GlyphPath(BezPath);
+
+impl OutlinePen for GlyphPath {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to((x as f64, y as f64));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to((x as f64, y as f64));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.0.quad_to((cx0 as f64, cy0 as f64), (x as f64, y as f64));
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.0
+            .curve_to((cx0 as f64, cy0 as f64), (cx1 as f64, cy1 as f64), (x as f64, y as f64));
+    }
+
+    fn close(&mut self) {
+        self.0.close_path();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "The quick brown fox jumps over the lazy dog";
+
+    fn style() -> TextStyle {
+        TextStyle::new(16.0, Color::WHITE)
+    }
+
+    #[test]
+    fn bold_text_is_wider_than_regular() {
+        let regular = text_size(TEXT, &style());
+        let bold_style = style().with_weight(FontWeight::BOLD);
+        let bold = text_size(TEXT, &bold_style);
+        if bold.width > regular.width {
+            return;
+        }
+        // Without a bold face the advances stay the same, but the emboldening
+        // must at least have been noted
+        let noted = ENGINE.with_borrow(|engine| engine.synthesized.contains(&synthesis_key(&bold_style)));
+        assert!(noted, "bold measured {} against regular {}, with no synthesis", bold.width, regular.width);
+    }
+
+    #[test]
+    fn caret_rects_map_back_to_their_index() {
+        let paragraph = Paragraph::new(TEXT, &style(), Some(120.0));
+        assert!(paragraph.lines().len() > 1, "should wrap");
+        for index in TEXT.char_indices().map(|(index, _)| index).chain([TEXT.len()]) {
+            let caret = paragraph.caret_rect(index);
+            assert_eq!(paragraph.index_at(caret.center()), index, "caret {caret:?}");
+        }
+    }
+
+    #[test]
+    fn line_widths_match_the_text_set_alone() {
+        let paragraph = Paragraph::new(TEXT, &style(), Some(120.0));
+        for line in paragraph.lines() {
+            let text = TEXT[line.text_range.clone()].trim_end();
+            let alone = text_size(text, &style()).width;
+            assert!((line.width - alone).abs() < 0.5, "{text:?}: {} vs {alone}", line.width);
+        }
+        let unwrapped = Paragraph::new(TEXT, &style(), None);
+        assert_eq!(unwrapped.lines().len(), 1);
+        assert!((unwrapped.size().width - text_size(TEXT, &style()).width).abs() < 0.5);
+    }
+}
@@ -0,0 +1,42 @@
+use vello::peniko::Color;
+
+/// Defaults every widget draws with unless told otherwise.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub background: Color,
+    pub foreground: Color,
+    pub accent: Color,
+    pub corner_radius: f64,
+    pub font_size: f32,
+    pub spacing: f64,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Self {
+            background: Color::from_rgb8(245, 245, 245),
+            foreground: Color::from_rgb8(25, 25, 25),
+            accent: Color::from_rgb8(0, 102, 204),
+            corner_radius: 6.0,
+            font_size: 16.0,
+            spacing: 8.0,
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background: Color::from_rgb8(20, 20, 20),
+            foreground: Color::from_rgb8(230, 230, 230),
+            accent: Color::from_rgb8(64, 128, 255),
+            corner_radius: 6.0,
+            font_size: 16.0,
+            spacing: 8.0,
+        }
+    }
+}
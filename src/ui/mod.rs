@@ -0,0 +1,270 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use vello::kurbo::{Rect, Size};
+
+use crate::components::{Button, Checkbox, DrawCtx, Label, LayoutCtx, Widget};
+use crate::layout::{HStack, VStack};
+
+// Set from a widget's callback, and read by the next pass
+type Signal = Rc<Cell<bool>>;
+
+/// What happened to a widget between the previous pass and this one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Response {
+    clicked: bool,
+    changed: bool,
+}
+
+impl Response {
+    /// Whether the widget was clicked or toggled, by pointer or keyboard.
+    pub fn clicked(&self) -> bool {
+        self.clicked
+    }
+
+    /// Whether the value the widget edits changed.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+}
+
+// Signals of the widgets built so far, by their place in the calls
+#[derive(Default)]
+struct Signals {
+    by_path: HashMap<Vec<usize>, Signal>,
+    // Places reached this pass; the rest belong to widgets that are gone
+    seen: HashSet<Vec<usize>>,
+    // Whether any signal had fired, so another pass can show what it changed
+    fired: bool,
+}
+
+/// Describes a UI from scratch every frame, immediate-mode style, inside the
+/// closure given to [`App::with_ui`](crate::app::App::with_ui) or
+/// [`ImmediateView::new`].
+///
+/// ```ignore
+/// App::new().with_ui(move |ui| {
+///     if ui.button("Click").clicked() {
+///         count += 1;
+///     }
+///     ui.label(&format!("Clicked {} times", count));
+/// })
+/// ```
+///
+/// Behind it is an ordinary retained tree. Each call lands in the next slot
+/// of the container it's made in, and reuses the widget the previous pass put
+/// there if it's the same kind, so hover, focus and press state carry over as
+/// long as the calls come in the same order. A call that lands on a different
+/// kind of widget replaces it, and slots left over at the end are removed.
+pub struct Ui<'a> {
+    children: &'a mut Vec<Box<dyn Widget>>,
+    // Place of the container in the calls
+    path: Vec<usize>,
+    next: usize,
+    signals: &'a mut Signals,
+}
+
+impl Ui<'_> {
+    pub fn label(&mut self, text: &str) {
+        self.place(
+            |label: &mut Label| {
+                if label.text() != text {
+                    label.set_text(text);
+                }
+                true
+            },
+            |_| Label::new(text),
+        );
+    }
+
+    pub fn button(&mut self, text: &str) -> Response {
+        let (_, clicked) = self.place(
+            |button: &mut Button| {
+                if button.text() != text {
+                    button.set_text(text);
+                }
+                true
+            },
+            |signal| Button::new(text).on_click(move || signal.set(true)),
+        );
+        Response { clicked, changed: false }
+    }
+
+    /// A checkbox showing `checked`, which a toggle updates.
+    pub fn checkbox(&mut self, label: &str, checked: &mut bool) -> Response {
+        let (index, toggled) = self.place(
+            |checkbox: &mut Checkbox| checkbox.label() == label,
+            |signal| Checkbox::new(label).with_checked(*checked).on_toggle(move |_| signal.set(true)),
+        );
+        let checkbox = downcast::<Checkbox>(&mut self.children[index]);
+        let changed = toggled && checkbox.is_checked() != *checked;
+        if toggled {
+            *checked = checkbox.is_checked();
+        } else {
+            checkbox.set_checked(*checked);
+        }
+        Response { clicked: toggled, changed }
+    }
+
+    /// Places `widget`, unless the slot already holds a widget of the same
+    /// type, which is kept with its state instead. Either way, returns the
+    /// widget in the slot.
+    pub fn add<W: Widget>(&mut self, widget: W) -> &mut W {
+        let (index, _) = self.place(|_: &mut W| true, |_| widget);
+        downcast(&mut self.children[index])
+    }
+
+    /// Lays out whatever `build` adds from left to right.
+    pub fn horizontal(&mut self, build: impl FnOnce(&mut Ui)) {
+        self.nested(HStack::new, HStack::children_vec, build);
+    }
+
+    /// Lays out whatever `build` adds from top to bottom.
+    pub fn vertical(&mut self, build: impl FnOnce(&mut Ui)) {
+        self.nested(VStack::new, VStack::children_vec, build);
+    }
+
+    fn nested<S: Widget>(
+        &mut self,
+        create: fn() -> S,
+        children: fn(&mut S) -> &mut Vec<Box<dyn Widget>>,
+        build: impl FnOnce(&mut Ui),
+    ) {
+        let (index, _) = self.place(|_: &mut S| true, |_| create());
+        let mut path = self.path.clone();
+        path.push(index);
+        let mut ui = Ui {
+            children: children(downcast(&mut self.children[index])),
+            path,
+            next: 0,
+            signals: self.signals,
+        };
+        build(&mut ui);
+        ui.finish();
+    }
+
+    // Fills the next slot: keeps the widget already there if it's a `W` that
+    // `reuse` accepts, and puts one from `create` there otherwise, handing it
+    // the signal its callbacks should fire. Returns the slot and whether the
+    // signal has fired since the last pass.
+    fn place<W: Widget>(&mut self, reuse: impl FnOnce(&mut W) -> bool, create: impl FnOnce(Signal) -> W) -> (usize, bool) {
+        let index = self.next;
+        self.next += 1;
+        let mut path = self.path.clone();
+        path.push(index);
+        let reusable = self
+            .children
+            .get_mut(index)
+            .and_then(|child| (child.as_mut() as &mut dyn Any).downcast_mut::<W>())
+            .is_some_and(reuse);
+        let signal = match self.signals.by_path.get(&path) {
+            Some(signal) if reusable => signal.clone(),
+            _ => {
+                let signal = Signal::default();
+                let widget: Box<dyn Widget> = Box::new(create(signal.clone()));
+                match self.children.get_mut(index) {
+                    Some(child) => *child = widget,
+                    None => self.children.push(widget),
+                }
+                self.signals.by_path.insert(path.clone(), signal.clone());
+                signal
+            }
+        };
+        let fired = signal.replace(false);
+        self.signals.fired |= fired;
+        self.signals.seen.insert(path);
+        (index, fired)
+    }
+
+    // Drops the slots this pass didn't reach
+    fn finish(self) {
+        self.children.truncate(self.next);
+    }
+}
+
+// The widget `place` just filled a slot with
+fn downcast<W: Widget>(child: &mut Box<dyn Widget>) -> &mut W {
+    (child.as_mut() as &mut dyn Any)
+        .downcast_mut()
+        .expect("slot holds the widget just placed in it")
+}
+
+/// A widget whose content is described by a closure every frame, see [`Ui`].
+/// The closure runs on the first measure after each draw, and the content is
+/// laid out again every frame.
+pub struct ImmediateView {
+    build: Box<dyn FnMut(&mut Ui)>,
+    content: VStack,
+    signals: Signals,
+    // Whether the closure has run since the last draw
+    built: bool,
+}
+
+impl ImmediateView {
+    pub fn new(build: impl FnMut(&mut Ui) + 'static) -> Self {
+        Self {
+            build: Box::new(build),
+            content: VStack::new(),
+            signals: Signals::default(),
+            built: false,
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.signals.seen.clear();
+        self.signals.fired = false;
+        let mut ui = Ui {
+            children: self.content.children_vec(),
+            path: Vec::new(),
+            next: 0,
+            signals: &mut self.signals,
+        };
+        (self.build)(&mut ui);
+        ui.finish();
+        let seen = &self.signals.seen;
+        self.signals.by_path.retain(|path, _| seen.contains(path));
+        self.built = true;
+    }
+}
+
+impl Widget for ImmediateView {
+    fn measure(&mut self, ctx: &LayoutCtx, available: Size) -> Size {
+        if !self.built {
+            self.rebuild();
+        }
+        self.content.measure(ctx, available)
+    }
+
+    fn layout(&mut self, ctx: &LayoutCtx, bounds: Rect) {
+        self.content.layout(ctx, bounds);
+    }
+
+    fn bounds(&self) -> Rect {
+        self.content.bounds()
+    }
+
+    fn draw(&mut self, ctx: &mut DrawCtx) {
+        self.content.draw(ctx);
+        self.built = false;
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        self.content.children()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Widget>] {
+        self.content.children_mut()
+    }
+
+    // A click is only seen by the pass after it, so one more frame shows
+    // whatever that pass changed in calls made before the button's
+    fn is_animating(&self) -> bool {
+        self.signals.fired || self.content.is_animating()
+    }
+
+    fn needs_layout(&self) -> bool {
+        true
+    }
+}